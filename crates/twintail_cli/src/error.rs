@@ -1,4 +1,7 @@
 use thiserror::Error;
+use twintail_sekai::ErrorKind;
+
+use crate::strings;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -8,9 +11,59 @@ pub enum Error {
     #[error("core error: {0}")]
     TwintailCore(#[from] twintail_core::Error),
 
+    #[error("sekai error: {0}")]
+    Sekai(#[from] twintail_sekai::Error),
+
     #[error("JSON de/serialization error: {0}")]
     SerdeJson(#[from] serde_json::Error),
 
+    #[error("assetbundle not found: {0}")]
+    BundleNotFound(String),
+
+    #[error("could not find a location for the search index, provide one with --index")]
+    NoSearchIndexPath,
+
+    #[error("could not find a location for the watch state, provide one with --state")]
+    NoWatchStatePath,
+
+    #[error("the custom server has no urls, provide them with --endpoints")]
+    MissingEndpoints,
+
+    #[error(
+        "some files of the update could not be downloaded, and will be fetched again by the next check"
+    )]
+    WatchDownloadIncomplete,
+
+    #[error("checking {0} of the watched servers for updates failed")]
+    WatchCheckFailed(usize),
+
+    #[error("invalid --resolve value, expected HOST=IP:PORT: {0}")]
+    InvalidResolve(String),
+
     #[error("tokio join error")]
     TokioJoin(#[from] tokio::task::JoinError),
 }
+
+impl Error {
+    /// Returns a suggestion for how to resolve the error, if there is one.
+    pub fn hint(&self) -> Option<&'static str> {
+        let Self::TwintailCore(err) = self else {
+            return None;
+        };
+        match err {
+            twintail_core::Error::Maintenance => return Some(strings::error::HINT_MAINTENANCE),
+            twintail_core::Error::ServerMismatch(_) => {
+                return Some(strings::error::HINT_SERVER_MISMATCH);
+            }
+            _ => {}
+        }
+
+        match err.kind()? {
+            ErrorKind::UpgradeRequired => Some(strings::error::HINT_UPGRADE_REQUIRED),
+            ErrorKind::Forbidden => Some(strings::error::HINT_FORBIDDEN),
+            ErrorKind::RateLimited => Some(strings::error::HINT_RATE_LIMITED),
+            ErrorKind::Transport => Some(strings::error::HINT_TRANSPORT),
+            _ => None,
+        }
+    }
+}
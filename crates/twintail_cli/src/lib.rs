@@ -1,17 +1,19 @@
 mod color;
 mod error;
+mod output;
 mod progress;
 mod strings;
 mod subcommands;
 
 use color::get_clap_styles;
 pub use error::Error;
+use output::OutputFormat;
 
 use clap::{Parser, Subcommand};
 use subcommands::{
-    app_info,
+    abinfo, app_info,
     crypt::{decrypt, encrypt},
-    fetch,
+    fetch, search, verify, watch,
 };
 
 #[derive(Debug, Subcommand)]
@@ -24,6 +26,14 @@ enum Commands {
     Encrypt(encrypt::EncryptArgs),
     /// Extract app version & hash from an apk file
     AppInfo(app_info::AppInfoArgs),
+    /// Commands that check downloaded files for problems
+    Verify(verify::VerifyArgs),
+    /// Commands that inspect assetbundle info files
+    Abinfo(abinfo::AbInfoArgs),
+    /// Search for assetbundles by name or by the paths of the assets they contain
+    Search(search::SearchArgs),
+    /// Periodically check servers for new versions, and fetch their files
+    Watch(watch::WatchArgs),
 }
 
 #[derive(Debug, Parser)]
@@ -31,28 +41,56 @@ enum Commands {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// How progress and results are output
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
 }
 
 /// Runs the twintail CLI.
 pub async fn run() -> Result<(), clap::Error> {
     let cli = Cli::try_parse()?;
 
+    let output = cli.output;
     let command_result = match cli.command {
-        Commands::Fetch(args) => fetch::fetch(args).await,
-        Commands::Decrypt(args) => decrypt::decrypt(args).await,
-        Commands::Encrypt(args) => encrypt::encrypt(args).await,
-        Commands::AppInfo(args) => app_info::app_info(args),
+        Commands::Fetch(args) => fetch::fetch(args, output).await,
+        Commands::Decrypt(args) => decrypt::decrypt(args, output).await,
+        Commands::Encrypt(args) => encrypt::encrypt(args, output).await,
+        Commands::AppInfo(args) => app_info::app_info(args, output),
+        Commands::Verify(args) => verify::verify(args, output).await,
+        Commands::Abinfo(args) => abinfo::abinfo(args, output).await,
+        Commands::Search(args) => search::search(args, output).await,
+        Commands::Watch(args) => watch::watch(args, output).await,
     };
 
-    // print error if result is an error
+    // print error if result is an error, and report it through the exit status
     if let Err(err) = command_result {
+        print_error(&err, output);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Prints an error, along with a hint for how to resolve it if there is one.
+fn print_error(err: &Error, output: OutputFormat) {
+    if output.is_json() {
+        output::print_error(err);
+        return;
+    }
+
+    println!(
+        "{}{}{}",
+        color::ERROR.render_fg(),
+        err,
+        color::TEXT.render_fg()
+    );
+    if let Some(hint) = err.hint() {
         println!(
             "{}{}{}",
-            color::ERROR.render_fg(),
-            err,
+            color::TEXT_VARIANT.render_fg(),
+            hint,
             color::TEXT.render_fg()
-        )
+        );
     }
-
-    Ok(())
 }
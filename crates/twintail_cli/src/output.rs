@@ -0,0 +1,86 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use twintail_core::{state::StateReceiver, summary::Summary};
+
+use crate::Error;
+
+/// How commands output their progress and results.
+#[derive(Debug, Clone, Copy, PartialEq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable status messages and progress bars
+    #[default]
+    Text,
+    /// One JSON object per line for every state change, followed by a final result or error object
+    Json,
+}
+
+impl OutputFormat {
+    /// Returns true if output should be machine-readable JSON lines.
+    pub fn is_json(&self) -> bool {
+        *self == Self::Json
+    }
+}
+
+/// A line of JSON output.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Line<'a, T: Serialize> {
+    /// the state of the command changed
+    State { state: T },
+    /// the command finished
+    Result {
+        command: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        summary: Option<&'a Summary>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        data: Option<T>,
+    },
+    /// the command failed
+    Error {
+        message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hint: Option<&'static str>,
+    },
+}
+
+/// Prints a line of JSON output.
+fn print_line<T: Serialize>(line: &Line<T>) {
+    if let Ok(serialized) = serde_json::to_string(line) {
+        println!("{}", serialized);
+    }
+}
+
+/// Receives every state from a [`StateReceiver`], and prints each one as a line of JSON.
+///
+/// Returns once the sender of the channel is dropped.
+pub async fn watch_state<S: Serialize>(mut receiver: StateReceiver<S>) {
+    while let Some(state) = receiver.recv().await {
+        print_line(&Line::State { state });
+    }
+}
+
+/// Prints the summary of a finished command as a line of JSON.
+pub fn print_summary(command: &str, summary: &Summary) {
+    print_line::<()>(&Line::Result {
+        command,
+        summary: Some(summary),
+        data: None,
+    });
+}
+
+/// Prints the data that a finished command produced as a line of JSON.
+pub fn print_data<T: Serialize>(command: &str, data: T) {
+    print_line(&Line::Result {
+        command,
+        summary: None,
+        data: Some(data),
+    });
+}
+
+/// Prints an error as a line of JSON.
+pub fn print_error(err: &Error) {
+    print_line::<()>(&Line::Error {
+        message: err.to_string(),
+        hint: err.hint(),
+    });
+}
@@ -9,6 +9,18 @@ pub mod crypto {
     }
 }
 
+pub mod error {
+    pub const HINT_UPGRADE_REQUIRED: &str = "The game has been updated. Provide the latest app version and hash with --version and --hash, or leave them out to use the latest values.";
+    pub const HINT_FORBIDDEN: &str =
+        "The request was refused. Make sure that the provided versions and hashes are correct.";
+    pub const HINT_SERVER_MISMATCH: &str = "Download each server and platform into its own output directory, or use --no-manifest to not read or write a manifest.";
+    pub const HINT_MAINTENANCE: &str = "The game servers are under maintenance. Try again once maintenance has ended, or wait for it with --wait-for-maintenance.";
+    pub const HINT_RATE_LIMITED: &str =
+        "Too many requests were sent. Try again later, or with a lower --concurrent value.";
+    pub const HINT_TRANSPORT: &str =
+        "Could not communicate with the game servers. Check your internet connection.";
+}
+
 pub mod command {
     pub const DOWNLOADING: &str = "Downloading files...";
     pub const DOWNLOADED: &str = "downloaded";
@@ -25,8 +37,70 @@ pub mod command {
     pub const PATHS_SAVED_TO: &str = "Paths saved to ";
 
     // assetbundle
-    pub const INVALID_RE: &str =
-        "Invalid filter regular expression provided. No filter will be applied.";
+    pub const CHECKING_EXISTING: &str = "Checking existing assetbundles...";
+    pub const CORRUPT_BUNDLES: &str =
+        "The following assetbundles did not match their expected size or CRC:";
+    pub const PLAN_FILES: &str = "files would be downloaded";
+    pub const PLAN_AVAILABLE: &str = "available";
+    pub const PLAN_SAVED_TO: &str = "Plan saved to ";
+    pub const PRUNING: &str = "Pruning";
+    pub const PRUNING_FILES: &str = "files of removed assetbundles...";
+    pub const PRUNED: &str = "The following files of removed assetbundles were pruned:";
+    pub const PRUNE_DRY_RUN: &str = "The following files of removed assetbundles would be pruned:";
+
+    // verify
+    pub const VERIFY_SCANNING: &str = "Scanning files...";
+    pub const VERIFY_CHECKING: &str = "Checking assetbundles...";
+    pub const VERIFY_VALID: &str = "valid";
+    pub const VERIFY_MISSING: &str = "The following assetbundles are missing:";
+    pub const VERIFY_ENCRYPTION: &str =
+        "The following assetbundles are not in the expected encryption state:";
+    pub const VERIFY_ORPHANS: &str = "The following files are not listed in the assetbundle info:";
+    pub const VERIFY_REPAIRING: &str = "Repairing broken assetbundles...";
+    pub const VERIFY_NOTHING_TO_REPAIR: &str = "No assetbundles need to be repaired.";
+
+    // abinfo
+    pub const AB_VERSION: &str = "[Assetbundle Version]:";
+    pub const DIFF_NO_DIFFERENCES: &str = "The assetbundle infos are identical.";
+    pub const DIFF_BY_PREFIX: &str = "Differences by prefix:";
+    pub const DIFF_ADDED: &str = "Added assetbundles:";
+    pub const DIFF_REMOVED: &str = "Removed assetbundles:";
+    pub const DIFF_CHANGED: &str = "Changed assetbundles:";
+    pub const STATS_TOTAL: &str = "Total:";
+    pub const STATS_BUILTIN: &str = "Builtin:";
+    pub const STATS_ON_DEMAND: &str = "On demand:";
+    pub const STATS_BY_CATEGORY: &str = "Size by category:";
+    pub const STATS_BY_PREFIX: &str = "Size by prefix:";
+    pub const STATS_LARGEST: &str = "Largest assetbundles:";
+    pub const STATS_FAN_IN: &str = "Most depended on assetbundles:";
+    pub const GRAPH_SAVED_TO: &str = "Graph saved to ";
+
+    // search
+    pub const SEARCH_INDEXED: &str = "assetbundle info files added to the search index";
+    pub const SEARCH_VERSIONS: &str = "asset versions indexed";
+    pub const SEARCH_EMPTY_INDEX: &str =
+        "The search index is empty. Add assetbundle info files to it with --add.";
+    pub const SEARCH_NO_MATCHES: &str = "No assetbundles matched the query.";
+    pub const SEARCH_FIRST_SEEN: &str = "first seen in";
+
+    // watch
+    pub const WATCH_UP_TO_DATE: &str = "No new versions.";
+    pub const WATCH_NEW_APP_VERSION: &str = "New app version:";
+    pub const WATCH_NEW_DATA_VERSION: &str = "New data version:";
+    pub const WATCH_NEW_ASSET_VERSION: &str = "New asset version:";
+    pub const WATCH_DOWNLOADED: &str = "Downloaded";
+    pub const WATCH_SUITE_FILES: &str = "suitemaster files";
+    pub const WATCH_AB_FILES: &str = "assetbundles";
+    pub const WATCH_COMMAND_FAILED: &str = "The update command failed:";
+    pub const WATCH_CHECK_FAILED: &str = "Checking for updates failed:";
+    pub const WATCH_MAINTENANCE_SKIPPED: &str = "Skipped, the game servers are under maintenance.";
+    pub const WATCH_NEXT_CHECK: &str = "Checking again in";
+    pub const WATCH_SECONDS: &str = "seconds...";
+
+    // maintenance
+    pub const MAINTENANCE_WAITING: &str =
+        "The game servers are under maintenance. Checking again in";
+    pub const MAINTENANCE_SECONDS: &str = "seconds...";
 
     // extract hash
     pub const EXTRACTING: &str = "Extracting version and hash from file...";
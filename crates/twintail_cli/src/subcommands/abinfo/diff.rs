@@ -0,0 +1,168 @@
+use clap::Args;
+use humansize::{DECIMAL, format_size};
+use twintail_core::abinfo::diff::AssetbundleInfoDiff;
+
+use super::format_size_delta;
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+    subcommands::fetch::ab::read_assetbundle_info,
+};
+
+#[derive(Debug, Args)]
+pub struct AbInfoDiffArgs {
+    /// The number of leading directories of a bundle name that bundles are grouped by in the summary
+    #[arg(long, short, default_value_t = 2)]
+    pub depth: usize,
+
+    /// If set, only the summary grouped by bundle name prefix is output
+    #[arg(long, default_value_t = false)]
+    pub summary: bool,
+
+    /// Path to the older assetbundle info file
+    pub old: String,
+
+    /// Path to the newer assetbundle info file
+    pub new: String,
+}
+
+/// Prints the differences between two assetbundle infos in a human readable format.
+fn print_diff(diff: &AssetbundleInfoDiff, depth: usize, summary_only: bool) {
+    println!(
+        "{}{} {}{} -> {}",
+        color::TEXT_VARIANT.render_fg(),
+        strings::command::AB_VERSION,
+        color::TEXT.render_fg(),
+        diff.old_version,
+        diff.new_version,
+    );
+
+    if diff.is_empty() {
+        println!(
+            "{}{}{}",
+            color::SUCCESS.render_fg(),
+            strings::command::DIFF_NO_DIFFERENCES,
+            color::TEXT.render_fg(),
+        );
+        return;
+    }
+
+    println!(
+        "{}{} added, {} removed, {} changed ({}){}",
+        color::SUCCESS.render_fg(),
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        format_size_delta(diff.size_delta()),
+        color::TEXT.render_fg(),
+    );
+
+    // summary grouped by prefix
+    println!(
+        "{}{}{}",
+        color::TEXT_VARIANT.render_fg(),
+        strings::command::DIFF_BY_PREFIX,
+        color::TEXT.render_fg(),
+    );
+    for summary in diff.summarize_by_prefix(depth) {
+        let prefix = if summary.prefix.is_empty() {
+            "/"
+        } else {
+            &summary.prefix
+        };
+        println!(
+            "   {} +{} -{} ~{} ({})",
+            prefix,
+            summary.added,
+            summary.removed,
+            summary.changed,
+            format_size_delta(summary.size_delta),
+        );
+    }
+
+    if summary_only {
+        return;
+    }
+
+    if !diff.added.is_empty() {
+        println!(
+            "{}{}{}",
+            color::TEXT_VARIANT.render_fg(),
+            strings::command::DIFF_ADDED,
+            color::TEXT.render_fg(),
+        );
+        for bundle in &diff.added {
+            println!(
+                "   + {} ({:?}, {})",
+                bundle.bundle_name,
+                bundle.category,
+                format_size(bundle.file_size, DECIMAL),
+            );
+        }
+    }
+
+    if !diff.removed.is_empty() {
+        println!(
+            "{}{}{}",
+            color::TEXT_VARIANT.render_fg(),
+            strings::command::DIFF_REMOVED,
+            color::TEXT.render_fg(),
+        );
+        for bundle in &diff.removed {
+            println!(
+                "   - {} ({:?}, {})",
+                bundle.bundle_name,
+                bundle.category,
+                format_size(bundle.file_size, DECIMAL),
+            );
+        }
+    }
+
+    if !diff.changed.is_empty() {
+        println!(
+            "{}{}{}",
+            color::TEXT_VARIANT.render_fg(),
+            strings::command::DIFF_CHANGED,
+            color::TEXT.render_fg(),
+        );
+        for change in &diff.changed {
+            println!(
+                "   ~ {} ({:?}, {} -> {}, {})",
+                change.bundle_name,
+                change.category,
+                change.old_hash,
+                change.new_hash,
+                format_size_delta(change.size_delta),
+            );
+        }
+    }
+}
+
+/// Compares two assetbundle info files using the provided arguments.
+pub async fn abinfo_diff(args: AbInfoDiffArgs, output: OutputFormat) -> Result<(), Error> {
+    let old_info = read_assetbundle_info(&args.old).await?;
+    let new_info = read_assetbundle_info(&args.new).await?;
+
+    let diff = AssetbundleInfoDiff::new(&old_info, &new_info);
+
+    if output.is_json() {
+        let data = if args.summary {
+            serde_json::json!({
+                "oldVersion": diff.old_version,
+                "newVersion": diff.new_version,
+                "prefixes": diff.summarize_by_prefix(args.depth),
+            })
+        } else {
+            serde_json::json!({
+                "diff": diff,
+                "prefixes": diff.summarize_by_prefix(args.depth),
+            })
+        };
+        output::print_data("abinfo diff", &data);
+    } else {
+        print_diff(&diff, args.depth, args.summary);
+    }
+
+    Ok(())
+}
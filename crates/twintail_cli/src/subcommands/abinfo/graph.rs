@@ -0,0 +1,136 @@
+use clap::{Args, ValueEnum};
+use tokio::{fs::File, io::AsyncWriteExt, time::Instant};
+use twintail_core::{abinfo::graph::DependencyGraph, summary::Summary};
+
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+    subcommands::fetch::ab::read_assetbundle_info,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GraphFormat {
+    Dot,
+    Json,
+}
+
+#[derive(Debug, Args)]
+pub struct AbInfoDepsArgs {
+    /// If set, the bundles that depend on the bundle are listed, instead of the bundles it depends on
+    #[arg(long, short, default_value_t = false)]
+    pub reverse: bool,
+
+    /// If set, bundles that are depended on through other bundles are also listed
+    #[arg(long, short, default_value_t = false)]
+    pub transitive: bool,
+
+    /// Path to the assetbundle info file
+    pub info: String,
+
+    /// The name of the bundle to query
+    pub bundle: String,
+}
+
+#[derive(Debug, Args)]
+pub struct AbInfoUnreferencedArgs {
+    /// Path to the assetbundle info file
+    pub info: String,
+}
+
+#[derive(Debug, Args)]
+pub struct AbInfoGraphArgs {
+    /// The format to export the graph in
+    #[arg(long, short, value_enum, default_value_t = GraphFormat::Dot)]
+    pub format: GraphFormat,
+
+    /// The file to save the graph to. If not set, the graph is printed
+    #[arg(long, short)]
+    pub out: Option<String>,
+
+    /// Path to the assetbundle info file
+    pub info: String,
+}
+
+/// Prints a list of bundle names, either one per line or as a line of JSON.
+fn print_bundles(command: &str, bundles: &[&str], output: OutputFormat) {
+    if output.is_json() {
+        output::print_data(command, bundles);
+    } else {
+        for bundle in bundles {
+            println!("{}", bundle);
+        }
+    }
+}
+
+/// Lists the dependencies or dependents of a bundle using the provided arguments.
+pub async fn abinfo_deps(args: AbInfoDepsArgs, output: OutputFormat) -> Result<(), Error> {
+    let info = read_assetbundle_info(&args.info).await?;
+    let graph = DependencyGraph::new(&info);
+
+    if !graph.contains(&args.bundle) {
+        return Err(Error::BundleNotFound(args.bundle));
+    }
+
+    let bundles = match (args.reverse, args.transitive) {
+        (false, false) => graph.dependencies(&args.bundle),
+        (false, true) => graph.transitive_dependencies(&args.bundle),
+        (true, false) => graph.dependents(&args.bundle),
+        (true, true) => graph.transitive_dependents(&args.bundle),
+    };
+
+    print_bundles("abinfo deps", &bundles, output);
+    Ok(())
+}
+
+/// Lists the bundles that no other bundle depends on using the provided arguments.
+pub async fn abinfo_unreferenced(
+    args: AbInfoUnreferencedArgs,
+    output: OutputFormat,
+) -> Result<(), Error> {
+    let info = read_assetbundle_info(&args.info).await?;
+    let graph = DependencyGraph::new(&info);
+
+    print_bundles("abinfo unreferenced", &graph.unreferenced(), output);
+    Ok(())
+}
+
+/// Exports the dependency graph of an assetbundle info file using the provided arguments.
+pub async fn abinfo_graph(args: AbInfoGraphArgs, output: OutputFormat) -> Result<(), Error> {
+    let export_start = Instant::now();
+    let info = read_assetbundle_info(&args.info).await?;
+    let graph = DependencyGraph::new(&info);
+
+    let exported = match args.format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Json => serde_json::to_string_pretty(&graph)?,
+    };
+
+    match args.out {
+        Some(out_path) => {
+            File::create(&out_path)
+                .await?
+                .write_all(exported.as_bytes())
+                .await?;
+
+            if output.is_json() {
+                output::print_summary(
+                    "abinfo graph",
+                    &Summary::new(1, 1, export_start.elapsed()).with_paths(vec![out_path.into()]),
+                );
+            } else {
+                println!(
+                    "{}{}{}{}",
+                    color::SUCCESS.render_fg(),
+                    strings::command::GRAPH_SAVED_TO,
+                    out_path,
+                    color::TEXT.render_fg()
+                );
+            }
+        }
+        None if output.is_json() => output::print_data("abinfo graph", &exported),
+        None => print!("{}", exported),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+pub mod diff;
+pub mod graph;
+pub mod stats;
+
+use crate::{Error, output::OutputFormat};
+use clap::{Args, Subcommand};
+use diff::AbInfoDiffArgs;
+use graph::{AbInfoDepsArgs, AbInfoGraphArgs, AbInfoUnreferencedArgs};
+use humansize::{DECIMAL, format_size};
+use stats::AbInfoStatsArgs;
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Compare two assetbundle info files
+    Diff(AbInfoDiffArgs),
+    /// Show size statistics of an assetbundle info file
+    Stats(AbInfoStatsArgs),
+    /// List the dependencies, or dependents, of a bundle
+    Deps(AbInfoDepsArgs),
+    /// List the bundles that no other bundle depends on
+    Unreferenced(AbInfoUnreferencedArgs),
+    /// Export the dependency graph of an assetbundle info file
+    Graph(AbInfoGraphArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct AbInfoArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Command handler for the abinfo subcommand.
+pub async fn abinfo(args: AbInfoArgs, output: OutputFormat) -> Result<(), Error> {
+    match args.command {
+        Commands::Diff(args) => diff::abinfo_diff(args, output).await,
+        Commands::Stats(args) => stats::abinfo_stats(args, output).await,
+        Commands::Deps(args) => graph::abinfo_deps(args, output).await,
+        Commands::Unreferenced(args) => graph::abinfo_unreferenced(args, output).await,
+        Commands::Graph(args) => graph::abinfo_graph(args, output).await,
+    }
+}
+
+/// Formats a difference in bytes as a human readable size with a leading sign.
+pub fn format_size_delta(size_delta: i64) -> String {
+    let sign = if size_delta < 0 { "-" } else { "+" };
+    format!(
+        "{}{}",
+        sign,
+        format_size(size_delta.unsigned_abs(), DECIMAL)
+    )
+}
@@ -0,0 +1,104 @@
+use clap::Args;
+use humansize::{DECIMAL, format_size};
+use twintail_core::abinfo::stats::{AssetbundleInfoStats, SizeTotal};
+
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+    subcommands::fetch::ab::read_assetbundle_info,
+};
+
+#[derive(Debug, Args)]
+pub struct AbInfoStatsArgs {
+    /// The number of leading directories of a bundle name that bundles are grouped by
+    #[arg(long, short, default_value_t = 1)]
+    pub depth: usize,
+
+    /// The number of largest bundles, and most depended on bundles, to list
+    #[arg(long, short, default_value_t = 10)]
+    pub top: usize,
+
+    /// Path to the assetbundle info file
+    pub info: String,
+}
+
+/// Prints the header of a section of statistics.
+fn print_section(title: &str) {
+    println!(
+        "{}{}{}",
+        color::TEXT_VARIANT.render_fg(),
+        title,
+        color::TEXT.render_fg(),
+    );
+}
+
+/// Prints a labelled size total.
+fn print_total(label: &str, total: &SizeTotal) {
+    println!(
+        "   {} {} ({} bundles)",
+        label,
+        format_size(total.total_size, DECIMAL),
+        total.count,
+    );
+}
+
+/// Prints the statistics of an assetbundle info in a human readable format.
+fn print_stats(stats: &AssetbundleInfoStats) {
+    println!(
+        "{}{} {}{}",
+        color::TEXT_VARIANT.render_fg(),
+        strings::command::AB_VERSION,
+        color::TEXT.render_fg(),
+        stats.version,
+    );
+    print_total(strings::command::STATS_TOTAL, &stats.total);
+    print_total(strings::command::STATS_BUILTIN, &stats.builtin);
+    print_total(strings::command::STATS_ON_DEMAND, &stats.on_demand);
+
+    print_section(strings::command::STATS_BY_CATEGORY);
+    for category in &stats.by_category {
+        print_total(&format!("{:?}:", category.category), &category.total);
+    }
+
+    print_section(strings::command::STATS_BY_PREFIX);
+    for prefix in &stats.by_prefix {
+        let label = if prefix.prefix.is_empty() {
+            "/"
+        } else {
+            &prefix.prefix
+        };
+        print_total(label, &prefix.total);
+    }
+
+    print_section(strings::command::STATS_LARGEST);
+    for bundle in &stats.largest {
+        println!(
+            "   {} ({:?}, {})",
+            bundle.bundle_name,
+            bundle.category,
+            format_size(bundle.file_size, DECIMAL),
+        );
+    }
+
+    if !stats.fan_in.is_empty() {
+        print_section(strings::command::STATS_FAN_IN);
+        for fan_in in &stats.fan_in {
+            println!("   {} ({})", fan_in.bundle_name, fan_in.dependents);
+        }
+    }
+}
+
+/// Computes the statistics of an assetbundle info file using the provided arguments.
+pub async fn abinfo_stats(args: AbInfoStatsArgs, output: OutputFormat) -> Result<(), Error> {
+    let info = read_assetbundle_info(&args.info).await?;
+    let stats = AssetbundleInfoStats::new(&info, args.depth, args.top);
+
+    if output.is_json() {
+        output::print_data("abinfo stats", &stats);
+    } else {
+        print_stats(&stats);
+    }
+
+    Ok(())
+}
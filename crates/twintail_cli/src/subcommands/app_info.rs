@@ -4,7 +4,11 @@ use clap::Args;
 use indicatif::ProgressBar;
 use twintail_core::apk_extractor::ApkExtractor;
 
-use crate::{Error, color, strings};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+};
 
 #[derive(Debug, Args)]
 pub struct AppInfoArgs {
@@ -16,7 +20,13 @@ pub struct AppInfoArgs {
 ///
 /// If successful, the hash and app version
 /// will be printed out to the console.
-pub fn app_info(args: AppInfoArgs) -> Result<(), Error> {
+pub fn app_info(args: AppInfoArgs, output: OutputFormat) -> Result<(), Error> {
+    if output.is_json() {
+        let app_info = ApkExtractor::from_file(&args.apk_path)?.extract()?;
+        output::print_data("app-info", &app_info);
+        return Ok(());
+    }
+
     // create assetbundle spinner
     println!(
         "{}[1/1] {}{}",
@@ -2,7 +2,7 @@ pub mod ab;
 pub mod json;
 pub mod suite;
 
-use crate::Error;
+use crate::{Error, output::OutputFormat};
 use ab::DecryptAbArgs;
 use clap::{Args, Subcommand};
 use json::DecryptJsonArgs;
@@ -25,10 +25,10 @@ pub struct DecryptArgs {
 }
 
 /// Command handler for the decrypt subcommand.
-pub async fn decrypt(args: DecryptArgs) -> Result<(), Error> {
+pub async fn decrypt(args: DecryptArgs, output: OutputFormat) -> Result<(), Error> {
     match args.command {
-        Commands::Ab(args) => ab::decrypt_ab(args).await,
-        Commands::Suite(args) => suite::decrypt_suite(args).await,
-        Commands::Json(args) => json::decrypt_json(args).await,
+        Commands::Ab(args) => ab::decrypt_ab(args, output).await,
+        Commands::Suite(args) => suite::decrypt_suite(args, output).await,
+        Commands::Json(args) => json::decrypt_json(args, output).await,
     }
 }
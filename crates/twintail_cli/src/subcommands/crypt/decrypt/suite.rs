@@ -1,12 +1,20 @@
 use std::time::Duration;
 
-use crate::{Error, color, progress::ProgressBar, strings};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+    subcommands::crypt::CryptServer,
+};
 use clap::Args;
-use tokio::{sync::watch::Receiver, time::Instant};
-use twintail_common::models::{OptionalBuilder, enums::Server};
+use tokio::time::Instant;
+use twintail_common::models::OptionalBuilder;
 use twintail_core::{
     config::crypt_config::CryptConfig,
     crypto::{CryptState, DecryptSuitePathState, decrypt::Decrypter},
+    state::StateReceiver,
+    summary::Summary,
 };
 
 #[derive(Debug, Args)]
@@ -20,8 +28,8 @@ pub struct DecryptSuiteArgs {
     pub concurrent: Option<usize>,
 
     /// The server to decrypt the suitemasterfiles for
-    #[arg(short, long, value_enum, default_value_t = Server::Japan)]
-    pub server: Server,
+    #[arg(short, long, value_enum, default_value_t = CryptServer::Japan)]
+    pub server: CryptServer,
 
     /// Whether to output status messages
     #[arg(short, long, default_value_t = false)]
@@ -38,13 +46,13 @@ pub struct DecryptSuiteArgs {
     pub out_path: String,
 }
 
-/// Watches a [`tokio::sync::watch::Receiver`] for state changes.
+/// Watches a [`StateReceiver`] for state changes.
 ///
 /// Prints information related to the progress of a suite decrypt.
-async fn watch_decrypt_suite_state(mut receiver: Receiver<CryptState>) {
+async fn watch_decrypt_suite_state(mut receiver: StateReceiver<CryptState>) {
     let mut progress_bar: Option<indicatif::ProgressBar> = None;
-    while receiver.changed().await.is_ok() {
-        match *receiver.borrow_and_update() {
+    while let Some(crypt_state) = receiver.recv().await {
+        match crypt_state {
             CryptState::DecryptSuitePath(DecryptSuitePathState::Start(file_count)) => {
                 println!(
                     "{}[1/1] {}{}",
@@ -73,12 +81,12 @@ async fn watch_decrypt_suite_state(mut receiver: Receiver<CryptState>) {
 }
 
 /// Decrypts encrypted suitemaster files into individual .json files.
-pub async fn decrypt_suite(args: DecryptSuiteArgs) -> Result<(), Error> {
+pub async fn decrypt_suite(args: DecryptSuiteArgs, output: OutputFormat) -> Result<(), Error> {
     let decrypt_start_instant = Instant::now();
 
     let config = CryptConfig::builder()
         .recursive(args.recursive)
-        .server(args.server)
+        .server(args.server.into())
         .pretty_json(!args.compact)
         .map(args.concurrent, |config, concurrency| {
             config.concurrency(concurrency)
@@ -87,7 +95,12 @@ pub async fn decrypt_suite(args: DecryptSuiteArgs) -> Result<(), Error> {
 
     let (decrypter, state_recv) = Decrypter::new(config);
 
-    let state_watcher = if args.quiet {
+    let out_path = args.out_path.clone();
+
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet {
+        drop(state_recv);
         None
     } else {
         Some(tokio::spawn(watch_decrypt_suite_state(state_recv)))
@@ -96,17 +109,30 @@ pub async fn decrypt_suite(args: DecryptSuiteArgs) -> Result<(), Error> {
     let success_count = decrypter
         .decrypt_suite_path(args.in_path, args.out_path)
         .await?;
+    drop(decrypter);
 
     if let Some(watcher) = state_watcher {
         watcher.await?;
-        println!(
-            "{}Successfully {} {} files in {:?}.{}",
-            color::SUCCESS.render_fg(),
-            strings::crypto::decrypt::PROCESSED,
-            success_count,
-            Instant::now().duration_since(decrypt_start_instant),
-            color::TEXT.render_fg(),
-        );
+        if output.is_json() {
+            output::print_summary(
+                "decrypt suite",
+                &Summary::new(
+                    success_count,
+                    success_count,
+                    decrypt_start_instant.elapsed(),
+                )
+                .with_paths(vec![out_path.into()]),
+            );
+        } else {
+            println!(
+                "{}Successfully {} {} files in {:?}.{}",
+                color::SUCCESS.render_fg(),
+                strings::crypto::decrypt::PROCESSED,
+                success_count,
+                Instant::now().duration_since(decrypt_start_instant),
+                color::TEXT.render_fg(),
+            );
+        }
     }
 
     Ok(())
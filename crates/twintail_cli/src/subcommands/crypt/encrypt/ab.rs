@@ -1,10 +1,17 @@
-use crate::{Error, color, progress::ProgressBar, strings};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+};
 use clap::Args;
-use tokio::{sync::watch::Receiver, time::Instant};
+use tokio::time::Instant;
 use twintail_common::models::OptionalBuilder;
 use twintail_core::{
     config::crypt_config::CryptConfig,
     crypto::{CryptAssetbundlePathState, CryptState, encrypt::Encrypter},
+    state::StateReceiver,
+    summary::Summary,
 };
 
 #[derive(Debug, Args)]
@@ -28,13 +35,13 @@ pub struct EncryptAbArgs {
     pub out_path: Option<String>,
 }
 
-/// Watches a [`tokio::sync::watch::Receiver`] for state changes.
+/// Watches a [`StateReceiver`] for state changes.
 ///
 /// Prints information related to the progress of an assetbundle encrypt.
-async fn watch_encrypt_ab_state(mut receiver: Receiver<CryptState>) {
+async fn watch_encrypt_ab_state(mut receiver: StateReceiver<CryptState>) {
     let mut progress_bar: Option<indicatif::ProgressBar> = None;
-    while receiver.changed().await.is_ok() {
-        match *receiver.borrow_and_update() {
+    while let Some(crypt_state) = receiver.recv().await {
+        match crypt_state {
             CryptState::AssetbundlePath(CryptAssetbundlePathState::Scan) => {
                 println!(
                     "{}[1/2] {}Scanning files...",
@@ -73,7 +80,7 @@ async fn watch_encrypt_ab_state(mut receiver: Receiver<CryptState>) {
 }
 
 /// Encrypts a file/folder using the provided arguments.
-pub async fn encrypt_ab(args: EncryptAbArgs) -> Result<(), Error> {
+pub async fn encrypt_ab(args: EncryptAbArgs, output: OutputFormat) -> Result<(), Error> {
     let crypt_start = Instant::now();
 
     let config = CryptConfig::builder()
@@ -85,7 +92,15 @@ pub async fn encrypt_ab(args: EncryptAbArgs) -> Result<(), Error> {
 
     let (encrypter, state_recv) = Encrypter::new(config);
 
-    let state_watcher = if args.quiet {
+    let out_path = args
+        .out_path
+        .clone()
+        .unwrap_or_else(|| args.in_path.clone());
+
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet {
+        drop(state_recv);
         None
     } else {
         Some(tokio::spawn(watch_encrypt_ab_state(state_recv)))
@@ -94,18 +109,27 @@ pub async fn encrypt_ab(args: EncryptAbArgs) -> Result<(), Error> {
     let (encrypt_count, total_file_count) = encrypter
         .encrypt_ab_path(args.in_path, args.out_path)
         .await?;
+    drop(encrypter);
 
     if let Some(watcher) = state_watcher {
         watcher.await?;
-        println!(
-            "{}Successfully {} {} / {} files in {:?}.{}",
-            color::SUCCESS.render_fg(),
-            strings::crypto::encrypt::PROCESSED,
-            encrypt_count,
-            total_file_count,
-            Instant::now().duration_since(crypt_start),
-            color::TEXT.render_fg(),
-        );
+        if output.is_json() {
+            output::print_summary(
+                "encrypt ab",
+                &Summary::new(encrypt_count, total_file_count, crypt_start.elapsed())
+                    .with_paths(vec![out_path.into()]),
+            );
+        } else {
+            println!(
+                "{}Successfully {} {} / {} files in {:?}.{}",
+                color::SUCCESS.render_fg(),
+                strings::crypto::encrypt::PROCESSED,
+                encrypt_count,
+                total_file_count,
+                Instant::now().duration_since(crypt_start),
+                color::TEXT.render_fg(),
+            );
+        }
     }
 
     Ok(())
@@ -1,8 +1,16 @@
-use crate::{Error, color, strings};
+use std::path::PathBuf;
+
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+    subcommands::crypt::CryptServer,
+};
 use clap::Args;
 use tokio::time::Instant;
-use twintail_common::models::enums::Server;
-use twintail_core::{config::crypt_config::CryptConfig, crypto::encrypt::Encrypter};
+use twintail_core::{
+    config::crypt_config::CryptConfig, crypto::encrypt::Encrypter, summary::Summary,
+};
 
 #[derive(Debug, Args)]
 pub struct EncryptJsonArgs {
@@ -11,8 +19,8 @@ pub struct EncryptJsonArgs {
     pub quiet: bool,
 
     /// The server to encrypt the suitemasterfiles for
-    #[arg(short, long, value_enum, default_value_t = Server::Japan)]
-    pub server: Server,
+    #[arg(short, long, value_enum, default_value_t = CryptServer::Japan)]
+    pub server: CryptServer,
 
     /// Path to a JSON file
     pub in_path: String,
@@ -22,15 +30,16 @@ pub struct EncryptJsonArgs {
 }
 
 /// Encrypts a file/folder using the provided arguments.
-pub async fn encrypt_json(args: EncryptJsonArgs) -> Result<(), Error> {
-    let quiet = args.quiet;
+pub async fn encrypt_json(args: EncryptJsonArgs, output: OutputFormat) -> Result<(), Error> {
+    let quiet = args.quiet || output.is_json();
 
-    let config = CryptConfig::builder().server(args.server).build();
+    let config = CryptConfig::builder().server(args.server.into()).build();
 
     let (encrypter, _) = Encrypter::new(config);
 
     let in_path = args.in_path;
     let out_path = args.out_path.unwrap_or(in_path.clone());
+    let out_path_buf = PathBuf::from(&out_path);
 
     let encrypt_start = Instant::now();
 
@@ -47,7 +56,12 @@ pub async fn encrypt_json(args: EncryptJsonArgs) -> Result<(), Error> {
         .encrypt_file_aes_msgpack(in_path, out_path)
         .await?;
 
-    if !quiet {
+    if output.is_json() {
+        output::print_summary(
+            "encrypt json",
+            &Summary::new(1, 1, encrypt_start.elapsed()).with_paths(vec![out_path_buf]),
+        );
+    } else if !quiet {
         println!(
             "{}Successfully {} json file in {:?}.{}",
             color::SUCCESS.render_fg(),
@@ -2,7 +2,7 @@ pub mod ab;
 pub mod json;
 pub mod suite;
 
-use crate::Error;
+use crate::{Error, output::OutputFormat};
 use ab::EncryptAbArgs;
 use clap::{Args, Subcommand};
 use json::EncryptJsonArgs;
@@ -25,10 +25,10 @@ pub struct EncryptArgs {
 }
 
 /// Command handler for the decrypt subcommand.
-pub async fn encrypt(args: EncryptArgs) -> Result<(), Error> {
+pub async fn encrypt(args: EncryptArgs, output: OutputFormat) -> Result<(), Error> {
     match args.command {
-        Commands::Ab(args) => ab::encrypt_ab(args).await,
-        Commands::Suite(args) => suite::encrypt_suite(args).await,
-        Commands::Json(args) => json::encrypt_json(args).await,
+        Commands::Ab(args) => ab::encrypt_ab(args, output).await,
+        Commands::Suite(args) => suite::encrypt_suite(args, output).await,
+        Commands::Json(args) => json::encrypt_json(args, output).await,
     }
 }
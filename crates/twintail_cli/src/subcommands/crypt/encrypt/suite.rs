@@ -1,12 +1,20 @@
 use clap::Args;
-use tokio::{sync::watch::Receiver, time::Instant};
-use twintail_common::models::{OptionalBuilder, enums::Server};
+use tokio::time::Instant;
+use twintail_common::models::OptionalBuilder;
 use twintail_core::{
     config::crypt_config::CryptConfig,
     crypto::{CryptState, EncryptSuitePathState, EncryptSuiteValuesState, encrypt::Encrypter},
+    state::StateReceiver,
+    summary::Summary,
 };
 
-use crate::{Error, color, progress::ProgressBar, strings};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+    subcommands::crypt::CryptServer,
+};
 
 #[derive(Debug, Args)]
 pub struct EncryptSuiteArgs {
@@ -19,8 +27,8 @@ pub struct EncryptSuiteArgs {
     pub concurrent: Option<usize>,
 
     /// The server to encrypt the suitemasterfiles for
-    #[arg(short, long, value_enum, default_value_t = Server::Japan)]
-    pub server: Server,
+    #[arg(short, long, value_enum, default_value_t = CryptServer::Japan)]
+    pub server: CryptServer,
 
     /// The number of files to split the encrypted suitemaster files into
     #[arg(long, default_value_t = 7)]
@@ -37,13 +45,13 @@ pub struct EncryptSuiteArgs {
     pub out_path: String,
 }
 
-/// Watches a [`tokio::sync::watch::Receiver`] for state changes.
+/// Watches a [`StateReceiver`] for state changes.
 ///
 /// Prints information related to the progress of a suite encrypt.
-async fn watch_encrypt_suite_state(mut receiver: Receiver<CryptState>) {
+async fn watch_encrypt_suite_state(mut receiver: StateReceiver<CryptState>) {
     let mut progress_bar: Option<indicatif::ProgressBar> = None;
-    while receiver.changed().await.is_ok() {
-        match *receiver.borrow_and_update() {
+    while let Some(crypt_state) = receiver.recv().await {
+        match crypt_state {
             CryptState::EncryptSuitePath(EncryptSuitePathState::Process) => {
                 println!(
                     "{}{}{}",
@@ -82,12 +90,12 @@ async fn watch_encrypt_suite_state(mut receiver: Receiver<CryptState>) {
     }
 }
 
-pub async fn encrypt_suite(args: EncryptSuiteArgs) -> Result<(), Error> {
+pub async fn encrypt_suite(args: EncryptSuiteArgs, output: OutputFormat) -> Result<(), Error> {
     let encrypt_start = Instant::now();
 
     let config = CryptConfig::builder()
         .recursive(args.recursive)
-        .server(args.server)
+        .server(args.server.into())
         .map(args.concurrent, |config, concurrency| {
             config.concurrency(concurrency)
         })
@@ -95,7 +103,12 @@ pub async fn encrypt_suite(args: EncryptSuiteArgs) -> Result<(), Error> {
 
     let (encrypter, state_recv) = Encrypter::new(config);
 
-    let state_watcher = if args.quiet {
+    let out_path = args.out_path.clone();
+
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet {
+        drop(state_recv);
         None
     } else {
         Some(tokio::spawn(watch_encrypt_suite_state(state_recv)))
@@ -104,16 +117,25 @@ pub async fn encrypt_suite(args: EncryptSuiteArgs) -> Result<(), Error> {
     encrypter
         .encrypt_suite_path(args.in_path, args.out_path, args.split)
         .await?;
+    drop(encrypter);
 
     if let Some(watcher) = state_watcher {
         watcher.await?;
-        println!(
-            "{}Successfully {} suite master files in {:?}.{}",
-            color::SUCCESS.render_fg(),
-            strings::crypto::encrypt::PROCESSED,
-            Instant::now().duration_since(encrypt_start),
-            color::TEXT.render_fg(),
-        );
+        if output.is_json() {
+            output::print_summary(
+                "encrypt suite",
+                &Summary::new(args.split, args.split, encrypt_start.elapsed())
+                    .with_paths(vec![out_path.into()]),
+            );
+        } else {
+            println!(
+                "{}Successfully {} suite master files in {:?}.{}",
+                color::SUCCESS.render_fg(),
+                strings::crypto::encrypt::PROCESSED,
+                Instant::now().duration_since(encrypt_start),
+                color::TEXT.render_fg(),
+            );
+        }
     }
 
     Ok(())
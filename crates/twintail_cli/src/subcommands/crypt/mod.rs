@@ -1,2 +1,23 @@
+use clap::ValueEnum;
+use twintail_common::models::enums::Server;
+
 pub mod decrypt;
 pub mod encrypt;
+
+/// The servers whose files can be encrypted and decrypted.
+///
+/// The custom server has no keys of its own, so it can not be selected.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum CryptServer {
+    Japan,
+    Global,
+}
+
+impl From<CryptServer> for Server {
+    fn from(value: CryptServer) -> Self {
+        match value {
+            CryptServer::Japan => Server::Japan,
+            CryptServer::Global => Server::Global,
+        }
+    }
+}
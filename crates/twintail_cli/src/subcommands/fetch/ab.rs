@@ -1,9 +1,9 @@
 use clap::Args;
-use std::path::Path;
+use humansize::{DECIMAL, format_size};
+use std::path::{Path, PathBuf};
 use tokio::{
     fs::File,
-    io::{AsyncReadExt, BufReader},
-    sync::watch::Receiver,
+    io::{AsyncReadExt, AsyncWriteExt, BufReader},
     time::Instant,
 };
 use twintail_common::models::{
@@ -11,12 +11,26 @@ use twintail_common::models::{
     enums::{Platform, Server},
 };
 use twintail_core::{
-    config::{download_ab_config::DownloadAbConfig, fetch_config::FetchConfig},
-    fetch::{DownloadAbState, FetchState, Fetcher},
+    config::{
+        download_ab_config::{DownloadAbConfig, PruneMode},
+        fetch_config::FetchConfig,
+    },
+    fetch::{DownloadAbPlan, DownloadAbState, FetchState, Fetcher},
+    state::StateReceiver,
+    summary::Summary,
 };
 use twintail_sekai::models::AssetbundleInfo;
 
-use crate::{Error, color, progress::ProgressBar, strings};
+use super::{
+    CredentialArgs, DeviceArgs, EndpointArgs, HttpArgs, MaintenanceArgs, RetryArgs, SelectionArgs,
+    print_maintenance_wait, url_provider,
+};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+};
 
 #[derive(Debug, Args)]
 pub struct AbArgs {
@@ -44,46 +58,104 @@ pub struct AbArgs {
     #[arg(short, long, value_enum, default_value_t = Server::Japan)]
     pub server: Server,
 
+    #[command(flatten)]
+    pub endpoints: EndpointArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub device: DeviceArgs,
+
     /// Path to an assetbundle info file. If not provided, the latest one will be fetched
     #[arg(short, long)]
     pub info: Option<String>,
 
-    /// If set, the assetbundle info file provided with --info will not be updated to the most recent asset version
+    /// If set, the assetbundle info file provided with --info will not be updated to the most recent asset version,
+    /// and the manifest of a previous download into the output directory will not be used to skip unchanged assetbundles
     #[arg(long, default_value_t = false)]
     pub no_update: bool,
 
+    /// If set, a manifest of the downloaded assetbundles will not be written to the output directory
+    #[arg(long, default_value_t = false)]
+    pub no_manifest: bool,
+
+    /// If set, assetbundles that already exist in the output directory and match their expected size and CRC will not be downloaded again
+    #[arg(long, default_value_t = false)]
+    pub skip_existing: bool,
+
+    /// If set, the size and CRC of downloaded assetbundles will not be checked
+    #[arg(long, default_value_t = false)]
+    pub no_verify: bool,
+
+    /// If set, the files of assetbundles that were previously downloaded to the output directory
+    /// but no longer exist in the latest asset version will be deleted
+    #[arg(long, default_value_t = false)]
+    pub prune: bool,
+
+    /// Instead of deleting pruned files, move them into this directory. Implies --prune
+    #[arg(long)]
+    pub trash_dir: Option<String>,
+
+    /// Only list the files that would be pruned, without deleting or moving them. Implies --prune
+    #[arg(long, default_value_t = false)]
+    pub prune_dry_run: bool,
+
     /// The maximum number of files to download simultaneously
     #[arg(long, short)]
     pub concurrent: Option<usize>,
 
-    /// Only assetbundles that match this regular expression will be downloaded
-    #[arg(long, short)]
-    pub filter: Option<String>,
+    #[command(flatten)]
+    pub selection: SelectionArgs,
+
+    /// If set, the assetbundles that selected assetbundles depend on will also be downloaded
+    #[arg(long, default_value_t = false)]
+    pub with_dependencies: bool,
 
-    /// The maximum number of times to retry a download if it fails
-    #[arg(long, short, default_value_t = 3)]
-    pub retry: usize,
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub maintenance: MaintenanceArgs,
 
     /// If present, the downloaded assetbundles will not be decrypted
     #[arg(long, short, default_value_t = false)]
     pub encrypt: bool,
 
+    /// If set, nothing will be downloaded. Instead, the assetbundles that would be downloaded are output as JSON
+    #[arg(long, default_value_t = false)]
+    pub dry_run: bool,
+
+    /// When used with --dry-run, the JSON is written to this file instead of being printed
+    #[arg(long)]
+    pub plan: Option<String>,
+
+    /// Path to a plan that was saved with --dry-run --plan. Downloads the assetbundles in the plan
+    /// into its output directory, using the server, platform, encryption, prune, verify and manifest options that it was created with
+    #[arg(long, conflicts_with_all = ["dry_run", "encrypt", "out_dir"])]
+    pub from_plan: Option<String>,
+
+    #[command(flatten)]
+    pub credentials: CredentialArgs,
+
     /// Whether to output status messages
     #[arg(short, long, default_value_t = false)]
     pub quiet: bool,
 
     /// The directory to output the assetbundles to
-    pub out_dir: String,
+    #[arg(required_unless_present = "from_plan")]
+    pub out_dir: Option<String>,
 }
 
-/// Watches a [`tokio::sync::watch::Receiver`] for DownloadSuite state changes.
+/// Watches a [`StateReceiver`] for DownloadSuite state changes.
 ///
 /// Prints information related to the progress of a suite download.
-async fn watch_fetch_ab_state(mut receiver: Receiver<FetchState>) {
+pub async fn watch_fetch_ab_state(mut receiver: StateReceiver<FetchState>) {
     let mut progress_bar: Option<indicatif::ProgressBar> = None;
-    while receiver.changed().await.is_ok() {
-        let fetch_state = *receiver.borrow_and_update();
-        if let FetchState::DownloadAb(download_ab_state) = fetch_state {
+    while let Some(fetch_state) = receiver.recv().await {
+        if let FetchState::WaitForMaintenance(seconds) = fetch_state {
+            print_maintenance_wait(progress_bar.as_ref(), seconds);
+        } else if let FetchState::DownloadAb(download_ab_state) = fetch_state {
             match download_ab_state {
                 DownloadAbState::RetrieveAbInfo => {
                     println!(
@@ -94,13 +166,12 @@ async fn watch_fetch_ab_state(mut receiver: Receiver<FetchState>) {
                     );
                     progress_bar = Some(ProgressBar::spinner());
                 }
-                DownloadAbState::InvalidRegEx => {
+                DownloadAbState::CheckExisting => {
                     println!(
-                        "{}{}{}",
-                        color::ERROR.render_fg(),
-                        strings::command::INVALID_RE,
-                        color::TEXT.render_fg()
-                    )
+                        "{}{}",
+                        color::TEXT.render_fg(),
+                        strings::command::CHECKING_EXISTING,
+                    );
                 }
                 DownloadAbState::DownloadStart(total_bytes) => {
                     if let Some(spinner) = &progress_bar {
@@ -116,11 +187,24 @@ async fn watch_fetch_ab_state(mut receiver: Receiver<FetchState>) {
 
                     progress_bar = Some(ProgressBar::download(total_bytes));
                 }
-                DownloadAbState::FileDownload(file_size_bytes) => {
+                DownloadAbState::Progress(downloaded_bytes) => {
                     if let Some(progress) = &progress_bar {
-                        progress.inc(file_size_bytes);
+                        progress.set_position(downloaded_bytes);
                     }
                 }
+                DownloadAbState::Prune(file_count) => {
+                    if let Some(progress) = &progress_bar {
+                        progress.finish_and_clear();
+                    }
+
+                    println!(
+                        "{}{} {} {}",
+                        color::TEXT.render_fg(),
+                        strings::command::PRUNING,
+                        file_count,
+                        strings::command::PRUNING_FILES,
+                    );
+                }
                 DownloadAbState::Finish => {
                     if let Some(progress) = &progress_bar {
                         progress.finish_and_clear();
@@ -130,10 +214,15 @@ async fn watch_fetch_ab_state(mut receiver: Receiver<FetchState>) {
             }
         }
     }
+
+    // the fetcher may be dropped before finishing, for example when only creating a plan
+    if let Some(progress) = &progress_bar {
+        progress.finish_and_clear();
+    }
 }
 
 /// Reads and deserializes an assetbundle info from a .json file.
-async fn read_assetbundle_info(path: &str) -> Result<AssetbundleInfo, Error> {
+pub async fn read_assetbundle_info(path: &str) -> Result<AssetbundleInfo, Error> {
     // read file
     let file = File::open(path).await?;
     let mut reader = BufReader::new(file);
@@ -144,7 +233,21 @@ async fn read_assetbundle_info(path: &str) -> Result<AssetbundleInfo, Error> {
     Ok(serde_json::from_slice(&file_buf)?)
 }
 
-pub async fn fetch_ab(args: AbArgs) -> Result<(), Error> {
+pub async fn fetch_ab(args: AbArgs, output: OutputFormat) -> Result<(), Error> {
+    // a saved plan determines the server, platform and output directory
+    let saved_plan: Option<DownloadAbPlan> = match &args.from_plan {
+        Some(plan_path) => Some(serde_json::from_slice(&tokio::fs::read(plan_path).await?)?),
+        None => None,
+    };
+    let (server, platform, out_dir) = match &saved_plan {
+        Some(plan) => (plan.server, plan.platform, plan.out_dir.clone()),
+        None => (
+            args.server,
+            args.platform,
+            PathBuf::from(args.out_dir.unwrap_or_default()),
+        ),
+    };
+
     // read ab info if it was provided
     let info = if let Some(string_path) = args.info {
         let assetbundle_info_path = Path::new(&string_path);
@@ -161,9 +264,23 @@ pub async fn fetch_ab(args: AbArgs) -> Result<(), Error> {
         None
     };
 
+    let prune_mode = if args.prune_dry_run {
+        Some(PruneMode::DryRun)
+    } else if let Some(trash_dir) = args.trash_dir {
+        Some(PruneMode::Trash(PathBuf::from(trash_dir)))
+    } else if args.prune {
+        Some(PruneMode::Delete)
+    } else {
+        None
+    };
+
     // build ab_config
     let download_ab_config = DownloadAbConfig::builder()
         .update(!args.no_update)
+        .skip_existing(args.skip_existing)
+        .verify(!args.no_verify)
+        .manifest(!args.no_manifest)
+        .with_dependencies(args.with_dependencies)
         .map(info, |config, info| config.info(info))
         .map(args.asset_version, |config, asset_version| {
             config.asset_version(asset_version)
@@ -171,14 +288,27 @@ pub async fn fetch_ab(args: AbArgs) -> Result<(), Error> {
         .map(args.host_hash, |config, host_hash| {
             config.host_hash(host_hash)
         })
-        .map(args.filter, |config, filter| config.filter(filter))
+        .selection(args.selection.into_selection()?)
+        .map(prune_mode, |config, prune_mode| config.prune(prune_mode))
         .build();
 
     // build config
     let fetch_config = FetchConfig::builder()
-        .platform(args.platform)
-        .server(args.server)
-        .retry(args.retry)
+        .platform(platform)
+        .server(server)
+        .url_provider(url_provider(server, args.endpoints.load().await?.as_ref())?)
+        .http_config(args.http.into_config().await?)
+        .map(args.device.install_id_store(), |config, path| {
+            config.install_id_store(path)
+        })
+        .device_profile(args.device.into_profile(platform))
+        .map(args.credentials.store_path(), |config, path| {
+            config.credential_store(path)
+        })
+        .retry_policy(args.retry.into_policy())
+        .map(args.maintenance.into_policy(), |config, policy| {
+            config.wait_for_maintenance(policy)
+        })
         .decrypt(!args.encrypt)
         .map(args.hash, |config, hash| config.hash(hash))
         .map(args.version, |config, version| config.version(version))
@@ -191,29 +321,130 @@ pub async fn fetch_ab(args: AbArgs) -> Result<(), Error> {
     let (mut fetcher, state_recv) = Fetcher::new(fetch_config).await?;
 
     // spawn thread for watching state_recv
-    let state_watcher = if args.quiet {
+    // when printing a plan, status messages are hidden so that only JSON is printed
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet || (args.dry_run && args.plan.is_none()) {
+        drop(state_recv);
         None
     } else {
         Some(tokio::spawn(watch_fetch_ab_state(state_recv)))
     };
 
+    if args.dry_run {
+        let plan = fetcher.plan_ab(&out_dir, download_ab_config).await?;
+        drop(fetcher);
+
+        let plan_serialized = serde_json::to_string_pretty(&plan)?;
+        if output.is_json() {
+            if let Some(plan_path) = &args.plan {
+                File::create(plan_path)
+                    .await?
+                    .write_all(plan_serialized.as_bytes())
+                    .await?;
+            }
+            if let Some(watcher) = state_watcher {
+                watcher.await?;
+            }
+            output::print_data("fetch ab", &plan);
+            return Ok(());
+        }
+
+        match args.plan {
+            Some(plan_path) => {
+                File::create(&plan_path)
+                    .await?
+                    .write_all(plan_serialized.as_bytes())
+                    .await?;
+
+                if let Some(watcher) = state_watcher {
+                    watcher.await?;
+                    println!(
+                        "{}{} {} ({} / {} {}){}",
+                        color::SUCCESS.render_fg(),
+                        plan.bundles.len(),
+                        strings::command::PLAN_FILES,
+                        format_size(plan.total_size, DECIMAL),
+                        format_size(plan.available_space, DECIMAL),
+                        strings::command::PLAN_AVAILABLE,
+                        color::TEXT.render_fg(),
+                    );
+                    println!(
+                        "{}{}{}{}",
+                        color::SUCCESS.render_fg(),
+                        strings::command::PLAN_SAVED_TO,
+                        plan_path,
+                        color::TEXT.render_fg()
+                    );
+                }
+            }
+            None => println!("{}", plan_serialized),
+        }
+
+        return Ok(());
+    }
+
     // download assetbundles
+    let prune_dry_run = match &saved_plan {
+        Some(plan) => plan.prune_mode == Some(PruneMode::DryRun),
+        None => args.prune_dry_run,
+    };
     let download_start = Instant::now();
-    let (success_count, total_file_count, _) = fetcher
-        .download_ab(args.out_dir, download_ab_config)
-        .await?;
+    let download_result = match saved_plan {
+        Some(plan) => fetcher.execute_ab_plan(plan).await?,
+        None => fetcher.download_ab(&out_dir, download_ab_config).await?,
+    };
+    drop(fetcher);
 
-    if let Some(watcher) = state_watcher {
+    if output.is_json() {
+        if let Some(watcher) = state_watcher {
+            watcher.await?;
+        }
+        output::print_summary(
+            "fetch ab",
+            &Summary::from_download_ab(&download_result, download_start.elapsed())
+                .with_paths(vec![out_dir]),
+        );
+    } else if let Some(watcher) = state_watcher {
         watcher.await?;
         println!(
             "{}Successfully {} {} / {} files in {:?}{}",
             color::SUCCESS.render_fg(),
             strings::command::DOWNLOADED,
-            success_count,
-            total_file_count,
+            download_result.success_count,
+            download_result.total_count,
             Instant::now().duration_since(download_start),
             color::TEXT.render_fg(),
         );
+
+        if !download_result.corrupt.is_empty() {
+            println!(
+                "{}{}{}",
+                color::WARNING.render_fg(),
+                strings::command::CORRUPT_BUNDLES,
+                color::TEXT.render_fg(),
+            );
+            for bundle_name in &download_result.corrupt {
+                println!("   {}", bundle_name);
+            }
+        }
+
+        if !download_result.pruned.is_empty() {
+            let header = if prune_dry_run {
+                strings::command::PRUNE_DRY_RUN
+            } else {
+                strings::command::PRUNED
+            };
+            println!(
+                "{}{}{}",
+                color::WARNING.render_fg(),
+                header,
+                color::TEXT.render_fg(),
+            );
+            for path in &download_result.pruned {
+                println!("   {}", path.display());
+            }
+        }
     }
 
     Ok(())
@@ -4,14 +4,20 @@ use std::{path::Path, time::Duration};
 use tokio::{
     fs::{File, create_dir_all},
     io::AsyncWriteExt,
+    time::Instant,
 };
 use twintail_common::models::{
     OptionalBuilder,
     enums::{Platform, Server},
 };
-use twintail_core::{config::fetch_config::FetchConfig, fetch::Fetcher};
+use twintail_core::{config::fetch_config::FetchConfig, fetch::Fetcher, summary::Summary};
 
-use crate::{Error, color, strings};
+use super::{CredentialArgs, DeviceArgs, EndpointArgs, HttpArgs, url_provider};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+};
 
 #[derive(Debug, Args)]
 pub struct AbInfoArgs {
@@ -39,6 +45,18 @@ pub struct AbInfoArgs {
     #[arg(short, long, value_enum, default_value_t = Server::Japan)]
     pub server: Server,
 
+    #[command(flatten)]
+    pub endpoints: EndpointArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub device: DeviceArgs,
+
+    #[command(flatten)]
+    pub credentials: CredentialArgs,
+
     /// Whether to output status messages
     #[arg(short, long, default_value_t = false)]
     pub quiet: bool,
@@ -47,8 +65,9 @@ pub struct AbInfoArgs {
     pub out_dir: Option<String>,
 }
 
-pub async fn abinfo(args: AbInfoArgs) -> Result<(), Error> {
-    let show_progress = !args.quiet;
+pub async fn abinfo(args: AbInfoArgs, output: OutputFormat) -> Result<(), Error> {
+    let show_progress = !args.quiet && !output.is_json();
+    let fetch_start = Instant::now();
 
     // create spinner
     let communicate_spinner = if show_progress {
@@ -69,13 +88,25 @@ pub async fn abinfo(args: AbInfoArgs) -> Result<(), Error> {
     let fetch_config = FetchConfig::builder()
         .platform(args.platform)
         .server(args.server)
+        .url_provider(url_provider(
+            args.server,
+            args.endpoints.load().await?.as_ref(),
+        )?)
+        .http_config(args.http.into_config().await?)
+        .map(args.device.install_id_store(), |config, path| {
+            config.install_id_store(path)
+        })
+        .device_profile(args.device.into_profile(args.platform))
+        .map(args.credentials.store_path(), |config, path| {
+            config.credential_store(path)
+        })
         .map(args.hash, |config, hash| config.hash(hash))
         .map(args.version, |config, version| config.version(version))
         .build();
     let (mut fetcher, _) = Fetcher::new(fetch_config).await?;
 
     let assetbundle_info = fetcher
-        .get_ab_info(args.asset_version, args.host_hash)
+        .get_ab_info(args.asset_version, None, args.host_hash)
         .await?;
 
     // serialize assetbundle info
@@ -103,7 +134,14 @@ pub async fn abinfo(args: AbInfoArgs) -> Result<(), Error> {
         .await?;
     out_file.write_all(&assetbundle_info_serialized).await?;
 
-    if show_progress {
+    if output.is_json() {
+        output::print_summary(
+            "fetch ab-info",
+            &Summary::new(1, 1, fetch_start.elapsed())
+                .with_version(assetbundle_info.version)
+                .with_paths(vec![out_path]),
+        );
+    } else if show_progress {
         println!(
             "{}{}{}{}",
             color::SUCCESS.render_fg(),
@@ -3,18 +3,38 @@ pub mod abinfo;
 pub mod save;
 pub mod suite;
 
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
+
 use ab::AbArgs;
 use abinfo::AbInfoArgs;
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 use save::SaveArgs;
 use suite::SuiteArgs;
+use twintail_common::models::{
+    OptionalBuilder,
+    enums::{AssetbundleCategory, Platform, Server},
+};
+use twintail_core::{
+    config::retry_policy::{Backoff, RetryPolicy},
+    credential_store::CredentialStore,
+    install_id,
+    selection::BundleSelection,
+};
+use twintail_sekai::{
+    device_profile::DeviceProfile,
+    http_config::HttpConfig,
+    url::{server_provider::ServerUrlProvider, template_provider::TemplateUrlProvider},
+};
+
+use crate::{Error, color, output::OutputFormat, strings};
 
-use crate::Error;
+// constants
+const MAX_MAINTENANCE_POLL_INTERVAL: Duration = Duration::from_secs(600);
 
 #[derive(Debug, Subcommand)]
 enum Commands {
     /// Fetch assetbundles.
-    Ab(AbArgs),
+    Ab(Box<AbArgs>),
     /// Fetch what assetbundles are available for download.
     AbInfo(AbInfoArgs),
     /// Fetch suitemaster files.
@@ -23,17 +43,366 @@ enum Commands {
     Save(SaveArgs),
 }
 
+/// Arguments that control where anonymous game accounts are stored.
+#[derive(Debug, Args)]
+pub struct CredentialArgs {
+    /// Path to the file where anonymous accounts are stored and reused. Defaults to a file in your config directory
+    #[arg(long)]
+    pub credentials: Option<String>,
+
+    /// If set, a new anonymous account will be created instead of reusing a stored one
+    #[arg(long, default_value_t = false)]
+    pub no_credentials: bool,
+}
+
+impl CredentialArgs {
+    /// Returns the path to the credential store that should be used, if any.
+    pub fn store_path(self) -> Option<PathBuf> {
+        if self.no_credentials {
+            None
+        } else {
+            self.credentials
+                .map(PathBuf::from)
+                .or_else(CredentialStore::default_path)
+        }
+    }
+}
+
+/// Arguments that control which urls are used by the custom server.
+#[derive(Debug, Args)]
+pub struct EndpointArgs {
+    /// Path to a .toml or .json file with the hosts and path templates of the custom server
+    #[arg(long)]
+    pub endpoints: Option<String>,
+}
+
+impl EndpointArgs {
+    /// Loads the urls of the custom server, if a file was provided.
+    pub async fn load(self) -> Result<Option<TemplateUrlProvider>, Error> {
+        match self.endpoints {
+            Some(path) => Ok(Some(TemplateUrlProvider::load(path).await?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Returns the [`ServerUrlProvider`] for a server.
+///
+/// The custom server uses the provided endpoints, which are required for it and ignored by all other servers.
+pub fn url_provider(
+    server: Server,
+    endpoints: Option<&TemplateUrlProvider>,
+) -> Result<ServerUrlProvider, Error> {
+    match (server, endpoints) {
+        (Server::Custom, Some(endpoints)) => {
+            Ok(ServerUrlProvider::Custom(Box::new(endpoints.clone())))
+        }
+        (Server::Custom, None) => Err(Error::MissingEndpoints),
+        (server, _) => Ok(ServerUrlProvider::try_from(server)?),
+    }
+}
+
+/// Arguments that control which device requests appear to be sent from.
+#[derive(Debug, Args)]
+pub struct DeviceArgs {
+    /// The install id to send requests with. Defaults to an id that is generated once and saved in your config directory
+    #[arg(long)]
+    pub install_id: Option<String>,
+
+    /// The device model to send requests with. Defaults to a model for the selected platform
+    #[arg(long)]
+    pub device_model: Option<String>,
+
+    /// The operating system to send requests with. Defaults to an operating system for the selected platform
+    #[arg(long)]
+    pub operating_system: Option<String>,
+
+    /// The Unity version to send requests with
+    #[arg(long)]
+    pub unity_version: Option<String>,
+
+    /// The user agent to send requests with. Defaults to the user agent of the game's Unity player
+    #[arg(long)]
+    pub user_agent: Option<String>,
+}
+
+impl DeviceArgs {
+    /// Returns the path to the file where the install id should be saved, if any.
+    ///
+    /// No install id is saved if one was provided.
+    pub fn install_id_store(&self) -> Option<PathBuf> {
+        if self.install_id.is_some() {
+            None
+        } else {
+            install_id::default_path()
+        }
+    }
+
+    /// Builds the [`DeviceProfile`] of a platform, with any provided values replacing the preset values.
+    pub fn into_profile(self, platform: Platform) -> DeviceProfile {
+        let preset = DeviceProfile::for_platform(platform);
+        DeviceProfile {
+            install_id: self.install_id.unwrap_or(preset.install_id),
+            device_model: self.device_model.unwrap_or(preset.device_model),
+            operating_system: self.operating_system.unwrap_or(preset.operating_system),
+            unity_version: self.unity_version.unwrap_or(preset.unity_version),
+            user_agent: self.user_agent.unwrap_or(preset.user_agent),
+        }
+    }
+}
+
+/// Arguments that control how requests are sent.
+#[derive(Debug, Args)]
+pub struct HttpArgs {
+    /// The url of an HTTP, HTTPS or SOCKS5 proxy to send all requests through
+    #[arg(long)]
+    pub proxy: Option<String>,
+
+    /// The maximum number of seconds to spend connecting to a server
+    #[arg(long)]
+    pub connect_timeout: Option<u64>,
+
+    /// The maximum number of seconds to wait for data from a server
+    #[arg(long)]
+    pub read_timeout: Option<u64>,
+
+    /// Resolve a host to a fixed address instead of using DNS, in the format HOST=IP:PORT. Can be provided multiple times
+    #[arg(long)]
+    pub resolve: Vec<String>,
+
+    /// Path to a PEM encoded certificate to trust in addition to the system's certificates. Can be provided multiple times
+    #[arg(long)]
+    pub ca_cert: Vec<String>,
+}
+
+impl HttpArgs {
+    /// Builds the [`HttpConfig`] that requests are sent with.
+    pub async fn into_config(self) -> Result<HttpConfig, Error> {
+        let mut builder = HttpConfig::builder()
+            .map(self.proxy, |config, proxy| config.proxy(proxy))
+            .map(self.connect_timeout, |config, timeout| {
+                config.connect_timeout(Duration::from_secs(timeout))
+            })
+            .map(self.read_timeout, |config, timeout| {
+                config.read_timeout(Duration::from_secs(timeout))
+            });
+
+        for resolve in self.resolve {
+            let (host, address) = resolve
+                .split_once('=')
+                .and_then(|(host, address)| Some((host, address.parse::<SocketAddr>().ok()?)))
+                .ok_or_else(|| Error::InvalidResolve(resolve.clone()))?;
+            builder = builder.resolve(host.into(), address);
+        }
+
+        for path in self.ca_cert {
+            builder = builder.root_certificate(tokio::fs::read(path).await?);
+        }
+
+        Ok(builder.build())
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum BackoffMode {
+    /// Wait for the same delay before every retry
+    Fixed,
+    /// Double the delay after every retry
+    Exponential,
+}
+
+/// Arguments that control how failed downloads are retried.
+#[derive(Debug, Args)]
+pub struct RetryArgs {
+    /// The maximum number of times to retry a download if it fails
+    #[arg(long, short, default_value_t = 3)]
+    pub retry: usize,
+
+    /// How the delay between retries changes after every retry
+    #[arg(long, value_enum, default_value_t = BackoffMode::Exponential)]
+    pub backoff: BackoffMode,
+
+    /// The delay before the first retry, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    pub retry_delay: u64,
+
+    /// The longest delay between two retries, in milliseconds. Longer delays that the server asks for are still waited
+    #[arg(long, default_value_t = 30000)]
+    pub max_retry_delay: u64,
+
+    /// If set, the delays between retries will not be randomized
+    #[arg(long, default_value_t = false)]
+    pub no_jitter: bool,
+
+    /// The maximum number of seconds to spend on a download, including retries
+    #[arg(long)]
+    pub retry_deadline: Option<u64>,
+}
+
+impl RetryArgs {
+    /// Builds a [`RetryPolicy`] from the arguments.
+    pub fn into_policy(self) -> RetryPolicy {
+        let backoff = match self.backoff {
+            BackoffMode::Fixed => Backoff::Fixed,
+            BackoffMode::Exponential => Backoff::Exponential,
+        };
+
+        RetryPolicy::builder()
+            .max_retries(self.retry)
+            .backoff(backoff)
+            .delay(Duration::from_millis(self.retry_delay))
+            .max_delay(Duration::from_millis(self.max_retry_delay))
+            .jitter(!self.no_jitter)
+            .map(self.retry_deadline, |policy, deadline| {
+                policy.deadline(Duration::from_secs(deadline))
+            })
+            .build()
+    }
+}
+
+/// Arguments that control what happens when the game servers are under maintenance.
+#[derive(Debug, Args)]
+pub struct MaintenanceArgs {
+    /// If set, the game servers will be checked until maintenance ends, instead of failing immediately
+    #[arg(long, default_value_t = false)]
+    pub wait_for_maintenance: bool,
+
+    /// The delay before the game servers are checked again during maintenance, in seconds. Doubles after every check
+    #[arg(long, default_value_t = 60)]
+    pub maintenance_poll_interval: u64,
+
+    /// The maximum number of seconds to wait for maintenance to end
+    #[arg(long)]
+    pub maintenance_timeout: Option<u64>,
+}
+
+impl MaintenanceArgs {
+    /// Builds the [`RetryPolicy`] used to poll the game servers during maintenance, if waiting is enabled.
+    pub fn into_policy(self) -> Option<RetryPolicy> {
+        self.wait_for_maintenance.then(|| {
+            RetryPolicy::builder()
+                .max_retries(usize::MAX)
+                .backoff(Backoff::Exponential)
+                .delay(Duration::from_secs(self.maintenance_poll_interval))
+                .max_delay(MAX_MAINTENANCE_POLL_INTERVAL)
+                .jitter(false)
+                .map(self.maintenance_timeout, |policy, timeout| {
+                    policy.deadline(Duration::from_secs(timeout))
+                })
+                .build()
+        })
+    }
+}
+
+/// Prints that the game servers are under maintenance, above a progress bar if one is shown.
+pub fn print_maintenance_wait(progress_bar: Option<&indicatif::ProgressBar>, seconds: u64) {
+    let message = format!(
+        "{}{} {} {}{}",
+        color::WARNING.render_fg(),
+        strings::command::MAINTENANCE_WAITING,
+        seconds,
+        strings::command::MAINTENANCE_SECONDS,
+        color::TEXT.render_fg(),
+    );
+
+    match progress_bar {
+        Some(progress) => progress.println(message),
+        None => println!("{}", message),
+    }
+}
+
+/// Arguments that control which assetbundles are selected.
+#[derive(Debug, Args)]
+pub struct SelectionArgs {
+    /// Only assetbundles whose name matches this regular expression will be selected. Can be provided multiple times
+    #[arg(long, short)]
+    pub filter: Vec<String>,
+
+    /// Assetbundles whose name matches this regular expression will not be selected. Can be provided multiple times
+    #[arg(long)]
+    pub exclude: Vec<String>,
+
+    /// Only assetbundles in this category will be selected. Can be provided multiple times
+    #[arg(long, value_enum)]
+    pub category: Vec<AssetbundleCategory>,
+
+    /// Assetbundles in this category will not be selected. Can be provided multiple times
+    #[arg(long, value_enum)]
+    pub exclude_category: Vec<AssetbundleCategory>,
+
+    /// Assetbundles smaller than this number of bytes will not be selected
+    #[arg(long)]
+    pub min_size: Option<u64>,
+
+    /// Assetbundles larger than this number of bytes will not be selected
+    #[arg(long)]
+    pub max_size: Option<u64>,
+
+    /// Only assetbundles that contain an asset whose path matches this regular expression will be selected. Can be provided multiple times
+    #[arg(long)]
+    pub path_filter: Vec<String>,
+
+    /// If true, only builtin assetbundles will be selected. If false, only assetbundles that are not builtin will be selected
+    #[arg(long)]
+    pub builtin: Option<bool>,
+}
+
+impl SelectionArgs {
+    /// Builds a [`BundleSelection`] from the arguments.
+    ///
+    /// Returns an error if any of the provided patterns are not valid regular expressions.
+    pub fn into_selection(self) -> Result<BundleSelection, Error> {
+        let selection = self
+            .filter
+            .into_iter()
+            .fold(BundleSelection::builder(), |selection, pattern| {
+                selection.include(pattern)
+            });
+        let selection = self
+            .exclude
+            .into_iter()
+            .fold(selection, |selection, pattern| selection.exclude(pattern));
+        let selection = self
+            .category
+            .into_iter()
+            .fold(selection, |selection, category| {
+                selection.category(category)
+            });
+        let selection = self
+            .exclude_category
+            .into_iter()
+            .fold(selection, |selection, category| {
+                selection.exclude_category(category)
+            });
+
+        Ok(self
+            .path_filter
+            .into_iter()
+            .fold(selection, |selection, pattern| selection.path(pattern))
+            .map(self.min_size, |selection, min_size| {
+                selection.min_size(min_size)
+            })
+            .map(self.max_size, |selection, max_size| {
+                selection.max_size(max_size)
+            })
+            .map(self.builtin, |selection, builtin| {
+                selection.builtin(builtin)
+            })
+            .build()?)
+    }
+}
+
 #[derive(Debug, Args)]
 pub struct FetchArgs {
     #[command(subcommand)]
     command: Commands,
 }
 
-pub async fn fetch(fetch_args: FetchArgs) -> Result<(), Error> {
+pub async fn fetch(fetch_args: FetchArgs, output: OutputFormat) -> Result<(), Error> {
     match fetch_args.command {
-        Commands::AbInfo(args) => abinfo::abinfo(args).await,
-        Commands::Ab(args) => ab::fetch_ab(args).await,
-        Commands::Suite(args) => suite::fetch_suite(args).await,
-        Commands::Save(args) => save::fetch_save(args).await,
+        Commands::AbInfo(args) => abinfo::abinfo(args, output).await,
+        Commands::Ab(args) => ab::fetch_ab(*args, output).await,
+        Commands::Suite(args) => suite::fetch_suite(args, output).await,
+        Commands::Save(args) => save::fetch_save(args, output).await,
     }
 }
@@ -1,13 +1,24 @@
 use clap::Args;
-use std::io::{Write, stdin, stdout};
-use tokio::{sync::watch::Receiver, time::Instant};
-use twintail_common::models::{OptionalBuilder, enums::Server};
+use std::io::{Write, stderr, stdin, stdout};
+use tokio::time::Instant;
+use twintail_common::models::{
+    OptionalBuilder,
+    enums::{Platform, Server},
+};
 use twintail_core::{
     config::fetch_config::FetchConfig,
     fetch::{FetchState, Fetcher, GetUserInheritState, WriteUserSaveDataState},
+    state::StateReceiver,
+    summary::Summary,
 };
 
-use crate::{Error, color, progress::ProgressBar, strings};
+use super::{DeviceArgs, EndpointArgs, HttpArgs, url_provider};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+};
 
 #[derive(Debug, Args)]
 pub struct SaveArgs {
@@ -31,6 +42,15 @@ pub struct SaveArgs {
     #[arg(short, long, value_enum, default_value_t = Server::Japan)]
     pub server: Server,
 
+    #[command(flatten)]
+    pub endpoints: EndpointArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub device: DeviceArgs,
+
     /// Whether to output status messages
     #[arg(short, long, default_value_t = false)]
     pub quiet: bool,
@@ -47,13 +67,13 @@ pub struct SaveArgs {
     pub out_path: Option<String>,
 }
 
-/// Watches a [`tokio::sync::watch::Receiver`] for state changes.
+/// Watches a [`StateReceiver`] for state changes.
 ///
 /// Prints information related to the progress of a save fetch.
-async fn watch_fetch_save_state(mut receiver: Receiver<FetchState>) {
+async fn watch_fetch_save_state(mut receiver: StateReceiver<FetchState>) {
     let mut progress_bar: Option<indicatif::ProgressBar> = None;
-    while receiver.changed().await.is_ok() {
-        match *receiver.borrow_and_update() {
+    while let Some(fetch_state) = receiver.recv().await {
+        match fetch_state {
             FetchState::GetUserInherit(GetUserInheritState::GetInherit) => {
                 println!(
                     "{}{}{}",
@@ -105,21 +125,34 @@ async fn watch_fetch_save_state(mut receiver: Receiver<FetchState>) {
     }
 }
 
-pub async fn fetch_save(args: SaveArgs) -> Result<(), Error> {
-    let show_progress = !args.quiet;
+pub async fn fetch_save(args: SaveArgs, output: OutputFormat) -> Result<(), Error> {
+    let show_progress = !args.quiet && !output.is_json();
+    let fetch_start = Instant::now();
 
     // create fetcher
     let fetch_config = FetchConfig::builder()
         .server(args.server)
+        .url_provider(url_provider(
+            args.server,
+            args.endpoints.load().await?.as_ref(),
+        )?)
+        .http_config(args.http.into_config().await?)
+        .map(args.device.install_id_store(), |config, path| {
+            config.install_id_store(path)
+        })
+        .device_profile(args.device.into_profile(Platform::Android))
         .pretty_json(!args.compact)
         .map(args.hash, |config, hash| config.hash(hash))
         .map(args.version, |config, version| config.version(version))
         .build();
     let (mut fetcher, state_recv) = Fetcher::new(fetch_config).await?;
 
-    let state_watcher = if show_progress {
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if show_progress {
         Some(tokio::spawn(watch_fetch_save_state(state_recv)))
     } else {
+        drop(state_recv);
         None
     };
 
@@ -162,13 +195,19 @@ pub async fn fetch_save(args: SaveArgs) -> Result<(), Error> {
     }
 
     if !args.yes {
-        print!(
-            "{}{}{}",
-            color::WARNING.render_fg(),
-            strings::command::INHERIT_CONTINUE_CONFIRM,
-            color::TEXT.render_fg()
-        );
-        stdout().flush()?;
+        // the prompt is kept out of JSON output, so that every line of it is valid JSON
+        if output.is_json() {
+            eprint!("{}", strings::command::INHERIT_CONTINUE_CONFIRM);
+            stderr().flush()?;
+        } else {
+            print!(
+                "{}{}{}",
+                color::WARNING.render_fg(),
+                strings::command::INHERIT_CONTINUE_CONFIRM,
+                color::TEXT.render_fg()
+            );
+            stdout().flush()?;
+        }
 
         // read confirmation response
         let mut response = String::new();
@@ -177,12 +216,20 @@ pub async fn fetch_save(args: SaveArgs) -> Result<(), Error> {
         match response.to_lowercase().trim() {
             "y" => {}
             _ => {
-                println!(
-                    "{}{}{}",
-                    color::ERROR.render_fg(),
-                    strings::command::INHERIT_CANCELLED,
-                    color::TEXT.render_fg()
-                );
+                if output.is_json() {
+                    output::print_summary(
+                        "fetch save",
+                        &Summary::new(0, 1, fetch_start.elapsed())
+                            .with_failures(vec![strings::command::INHERIT_CANCELLED.into()]),
+                    );
+                } else {
+                    println!(
+                        "{}{}{}",
+                        color::ERROR.render_fg(),
+                        strings::command::INHERIT_CANCELLED,
+                        color::TEXT.render_fg()
+                    );
+                }
                 return Ok(());
             }
         }
@@ -197,7 +244,13 @@ pub async fn fetch_save(args: SaveArgs) -> Result<(), Error> {
 
     let credential = user_inherit.credential.unwrap_or_default();
     if credential.is_empty() {
-        if show_progress {
+        if output.is_json() {
+            output::print_summary(
+                "fetch save",
+                &Summary::new(0, 1, fetch_start.elapsed())
+                    .with_failures(vec![strings::command::INHERIT_NO_CREDENTIAL.into()]),
+            );
+        } else if show_progress {
             println!(
                 "{}{}{}",
                 color::ERROR.render_fg(),
@@ -211,15 +264,24 @@ pub async fn fetch_save(args: SaveArgs) -> Result<(), Error> {
     // write save data
     let write_start = Instant::now();
     let out_path = args.out_path.unwrap_or_default();
-    fetcher
+    let save_data_path = fetcher
         .write_user_save_data(
             user_inherit.after_user_gamedata.user_id,
             credential,
             &out_path,
         )
         .await?;
+    drop(fetcher);
 
-    if let Some(watcher) = state_watcher {
+    if output.is_json() {
+        if let Some(watcher) = state_watcher {
+            watcher.await?;
+        }
+        output::print_summary(
+            "fetch save",
+            &Summary::new(1, 1, fetch_start.elapsed()).with_paths(vec![save_data_path]),
+        );
+    } else if let Some(watcher) = state_watcher {
         watcher.await?;
         println!();
         println!(
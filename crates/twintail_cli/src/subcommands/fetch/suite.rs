@@ -1,7 +1,7 @@
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use clap::Args;
-use tokio::{sync::watch::Receiver, time::Instant};
+use tokio::time::Instant;
 use twintail_common::models::{
     OptionalBuilder,
     enums::{Platform, Server},
@@ -9,9 +9,20 @@ use twintail_common::models::{
 use twintail_core::{
     config::fetch_config::FetchConfig,
     fetch::{DownloadSuiteState, FetchState, Fetcher},
+    state::StateReceiver,
+    summary::Summary,
 };
 
-use crate::{Error, color, progress::ProgressBar, strings};
+use super::{
+    CredentialArgs, DeviceArgs, EndpointArgs, HttpArgs, MaintenanceArgs, RetryArgs,
+    print_maintenance_wait, url_provider,
+};
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+};
 
 #[derive(Debug, Args)]
 pub struct SuiteArgs {
@@ -31,18 +42,32 @@ pub struct SuiteArgs {
     #[arg(short, long, value_enum, default_value_t = Server::Japan)]
     pub server: Server,
 
+    #[command(flatten)]
+    pub endpoints: EndpointArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub device: DeviceArgs,
+
     /// The maximum number of files to download simultaneously
     #[arg(long, short)]
     pub concurrent: Option<usize>,
 
-    /// The maximum number of times to retry a download if it fails
-    #[arg(long, short, default_value_t = 3)]
-    pub retry: usize,
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub maintenance: MaintenanceArgs,
 
     /// If set, the downloaded suitemaster files will not be decrypted.
     #[arg(long, short, default_value_t = false)]
     pub encrypt: bool,
 
+    #[command(flatten)]
+    pub credentials: CredentialArgs,
+
     /// Whether to output status messages
     #[arg(short, long, default_value_t = false)]
     pub quiet: bool,
@@ -55,14 +80,15 @@ pub struct SuiteArgs {
     pub out_path: String,
 }
 
-/// Watches a [`tokio::sync::watch::Receiver`] for DownloadSuite state changes.
+/// Watches a [`StateReceiver`] for DownloadSuite state changes.
 ///
 /// Prints information related to the progress of a suite download.
-async fn watch_fetch_suite_state(mut receiver: Receiver<FetchState>) {
+async fn watch_fetch_suite_state(mut receiver: StateReceiver<FetchState>) {
     let mut progress_bar: Option<indicatif::ProgressBar> = None;
-    while receiver.changed().await.is_ok() {
-        let fetch_state = *receiver.borrow_and_update();
-        if let FetchState::DownloadSuite(download_suite_state) = fetch_state {
+    while let Some(fetch_state) = receiver.recv().await {
+        if let FetchState::WaitForMaintenance(seconds) = fetch_state {
+            print_maintenance_wait(progress_bar.as_ref(), seconds);
+        } else if let FetchState::DownloadSuite(download_suite_state) = fetch_state {
             match download_suite_state {
                 DownloadSuiteState::Communicate => {
                     println!(
@@ -105,12 +131,27 @@ async fn watch_fetch_suite_state(mut receiver: Receiver<FetchState>) {
     }
 }
 
-pub async fn fetch_suite(args: SuiteArgs) -> Result<(), Error> {
+pub async fn fetch_suite(args: SuiteArgs, output: OutputFormat) -> Result<(), Error> {
     // create fetcher
     let fetch_config = FetchConfig::builder()
         .platform(args.platform)
         .server(args.server)
-        .retry(args.retry)
+        .url_provider(url_provider(
+            args.server,
+            args.endpoints.load().await?.as_ref(),
+        )?)
+        .http_config(args.http.into_config().await?)
+        .map(args.device.install_id_store(), |config, path| {
+            config.install_id_store(path)
+        })
+        .device_profile(args.device.into_profile(args.platform))
+        .map(args.credentials.store_path(), |config, path| {
+            config.credential_store(path)
+        })
+        .retry_policy(args.retry.into_policy())
+        .map(args.maintenance.into_policy(), |config, policy| {
+            config.wait_for_maintenance(policy)
+        })
         .decrypt(!args.encrypt)
         .pretty_json(!args.compact)
         .map(args.hash, |config, hash| config.hash(hash))
@@ -121,18 +162,33 @@ pub async fn fetch_suite(args: SuiteArgs) -> Result<(), Error> {
         .build();
     let (mut fetcher, state_recv) = Fetcher::new(fetch_config).await?;
 
-    let state_watcher = if args.quiet {
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet {
+        drop(state_recv);
         None
     } else {
         Some(tokio::spawn(watch_fetch_suite_state(state_recv)))
     };
+    let out_path = PathBuf::from(&args.out_path);
 
     // download suitemaster files
     let download_start = Instant::now();
     let (downloaded_count, file_count, suite_version) =
         fetcher.download_suite(args.out_path).await?;
+    drop(fetcher);
 
-    if let Some(watcher) = state_watcher {
+    if output.is_json() {
+        if let Some(watcher) = state_watcher {
+            watcher.await?;
+        }
+        output::print_summary(
+            "fetch suite",
+            &Summary::new(downloaded_count, file_count, download_start.elapsed())
+                .with_version(suite_version)
+                .with_paths(vec![out_path]),
+        );
+    } else if let Some(watcher) = state_watcher {
         watcher.await?;
         println!(
             "{}{} {}{}",
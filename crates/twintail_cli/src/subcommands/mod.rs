@@ -1,3 +1,7 @@
+pub mod abinfo;
 pub mod app_info;
 pub mod crypt;
 pub mod fetch;
+pub mod search;
+pub mod verify;
+pub mod watch;
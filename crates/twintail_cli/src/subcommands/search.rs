@@ -0,0 +1,181 @@
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use humansize::{DECIMAL, format_size};
+use tokio::time::Instant;
+use twintail_core::{
+    search::{SearchIndex, SearchMatch, SearchQuery},
+    summary::Summary,
+};
+
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    strings,
+    subcommands::fetch::ab::read_assetbundle_info,
+};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SearchMode {
+    /// Match bundle names and asset paths that contain the query
+    Substring,
+    /// Match bundle names and asset paths that start with the query
+    Prefix,
+    /// Match bundle names and asset paths that match the query as a regular expression
+    Regex,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// How the query is matched against bundle names and asset paths
+    #[arg(long, short, value_enum, default_value_t = SearchMode::Substring)]
+    pub mode: SearchMode,
+
+    /// Assetbundle info files to add to the search index before searching.
+    /// If a directory is provided, every assetbundle info file directly inside of it is added
+    #[arg(long, short)]
+    pub add: Vec<String>,
+
+    /// Path to the search index. Uses the index in the user's data directory if not provided
+    #[arg(long)]
+    pub index: Option<String>,
+
+    /// The maximum number of bundles to output
+    #[arg(long, short)]
+    pub limit: Option<usize>,
+
+    /// The text to search for. If not provided, info files are only added to the index
+    pub query: Option<String>,
+}
+
+/// Returns the paths of the assetbundle info files to add to the index.
+///
+/// The second value is true if the path came from a directory, in which case files
+/// that are not valid assetbundle info files should be skipped.
+async fn info_paths(paths: &[String]) -> Result<Vec<(PathBuf, bool)>, Error> {
+    let mut info_paths = Vec::new();
+
+    for path in paths.iter().map(PathBuf::from) {
+        if path.is_dir() {
+            let mut read_dir = tokio::fs::read_dir(&path).await?;
+            let mut dir_paths = Vec::new();
+            while let Some(entry) = read_dir.next_entry().await? {
+                let entry_path = entry.path();
+                if entry_path.is_file() && entry_path.extension().is_some_and(|ext| ext == "json") {
+                    dir_paths.push((entry_path, true));
+                }
+            }
+            dir_paths.sort();
+            info_paths.extend(dir_paths);
+        } else {
+            info_paths.push((path, false));
+        }
+    }
+
+    Ok(info_paths)
+}
+
+/// Prints search matches in a human readable format.
+fn print_matches(matches: &[SearchMatch]) {
+    for search_match in matches {
+        println!(
+            "{}{}{} ({:?}, {}, {} {})",
+            color::SUCCESS.render_fg(),
+            search_match.bundle_name,
+            color::TEXT.render_fg(),
+            search_match.category,
+            format_size(search_match.file_size, DECIMAL),
+            strings::command::SEARCH_FIRST_SEEN,
+            search_match.first_version,
+        );
+        for path in &search_match.matched_paths {
+            println!("   {}", path);
+        }
+    }
+}
+
+/// Searches the assetbundles of indexed assetbundle infos using the provided arguments.
+pub async fn search(args: SearchArgs, output: OutputFormat) -> Result<(), Error> {
+    let search_start = Instant::now();
+    let index_path = match args.index {
+        Some(index_path) => PathBuf::from(index_path),
+        None => SearchIndex::default_path().ok_or(Error::NoSearchIndexPath)?,
+    };
+    let mut index = SearchIndex::load(&index_path).await?.unwrap_or_default();
+
+    // add infos to the index
+    if !args.add.is_empty() {
+        let mut added = 0;
+        for (info_path, from_dir) in info_paths(&args.add).await? {
+            match read_assetbundle_info(&info_path.to_string_lossy()).await {
+                Ok(info) => {
+                    index.add_info(&info);
+                    added += 1;
+                }
+                Err(_) if from_dir => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        index.save(&index_path).await?;
+
+        if output.is_json() {
+            if args.query.is_none() {
+                output::print_summary(
+                    "search",
+                    &Summary::new(added, added, search_start.elapsed())
+                        .with_paths(vec![index_path.clone()]),
+                );
+            }
+        } else {
+            println!(
+                "{}{} {} ({} {}){}",
+                color::SUCCESS.render_fg(),
+                added,
+                strings::command::SEARCH_INDEXED,
+                index.versions.len(),
+                strings::command::SEARCH_VERSIONS,
+                color::TEXT.render_fg(),
+            );
+        }
+    }
+
+    let Some(query) = args.query else {
+        return Ok(());
+    };
+
+    if index.versions.is_empty() && !output.is_json() {
+        println!(
+            "{}{}{}",
+            color::WARNING.render_fg(),
+            strings::command::SEARCH_EMPTY_INDEX,
+            color::TEXT.render_fg(),
+        );
+        return Ok(());
+    }
+
+    let query = match args.mode {
+        SearchMode::Substring => SearchQuery::substring(query),
+        SearchMode::Prefix => SearchQuery::prefix(query),
+        SearchMode::Regex => SearchQuery::regex(&query)?,
+    };
+
+    let mut matches = index.search(&query);
+    if let Some(limit) = args.limit {
+        matches.truncate(limit);
+    }
+
+    if output.is_json() {
+        output::print_data("search", &matches);
+    } else if matches.is_empty() {
+        println!(
+            "{}{}{}",
+            color::WARNING.render_fg(),
+            strings::command::SEARCH_NO_MATCHES,
+            color::TEXT.render_fg(),
+        );
+    } else {
+        print_matches(&matches);
+    }
+
+    Ok(())
+}
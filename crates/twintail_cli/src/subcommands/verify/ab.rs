@@ -0,0 +1,356 @@
+use clap::Args;
+use tokio::time::Instant;
+use twintail_common::models::{
+    OptionalBuilder,
+    enums::{Platform, Server},
+};
+use twintail_core::{
+    config::{fetch_config::FetchConfig, verify_config::VerifyConfig},
+    crypto::assetbundle::EncryptionState,
+    fetch::Fetcher,
+    integrity::BundleFileStatus,
+    state::StateReceiver,
+    summary::Summary,
+    verify::{Verifier, VerifyReport, VerifyState},
+};
+
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    progress::ProgressBar,
+    strings,
+    subcommands::fetch::{
+        CredentialArgs, DeviceArgs, EndpointArgs, HttpArgs, RetryArgs,
+        ab::{read_assetbundle_info, watch_fetch_ab_state},
+        url_provider,
+    },
+};
+
+#[derive(Debug, Args)]
+pub struct VerifyAbArgs {
+    /// The device platform that the assetbundles were downloaded for
+    #[arg(short, long, value_enum, default_value_t = Platform::Android)]
+    pub platform: Platform,
+
+    /// The server that the assetbundles were downloaded from
+    #[arg(short, long, value_enum, default_value_t = Server::Japan)]
+    pub server: Server,
+
+    #[command(flatten)]
+    pub endpoints: EndpointArgs,
+
+    /// If set, the assetbundles are expected to be encrypted
+    #[arg(long, short, default_value_t = false)]
+    pub encrypt: bool,
+
+    /// The maximum number of files to check simultaneously
+    #[arg(long, short)]
+    pub concurrent: Option<usize>,
+
+    /// If set, missing and broken assetbundles will be downloaded again
+    #[arg(long, default_value_t = false)]
+    pub repair: bool,
+
+    /// The version of the game app to repair the assetbundles with
+    #[arg(short, long)]
+    pub version: Option<String>,
+
+    /// The hash of the game app to repair the assetbundles with
+    #[arg(long)]
+    pub hash: Option<String>,
+
+    /// Part of the URL used to download the assetbundles from. Uses the one in the assetbundle info if not provided
+    #[arg(long)]
+    pub host_hash: Option<String>,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub credentials: CredentialArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub device: DeviceArgs,
+
+    /// Whether to output status messages
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+
+    /// Path to the assetbundle info file that the assetbundles were downloaded with
+    pub info: String,
+
+    /// The directory that the assetbundles were downloaded to
+    pub dir: String,
+}
+
+/// Watches a [`StateReceiver`] for state changes.
+///
+/// Prints information related to the progress of an assetbundle verification.
+async fn watch_verify_ab_state(mut receiver: StateReceiver<VerifyState>) {
+    let mut progress_bar: Option<indicatif::ProgressBar> = None;
+    while let Some(verify_state) = receiver.recv().await {
+        match verify_state {
+            VerifyState::Scan => {
+                println!(
+                    "{}[1/2] {}{}",
+                    color::TEXT_VARIANT.render_fg(),
+                    color::TEXT.render_fg(),
+                    strings::command::VERIFY_SCANNING,
+                );
+                progress_bar = Some(ProgressBar::spinner())
+            }
+            VerifyState::Check(file_count) => {
+                if let Some(spinner) = &progress_bar {
+                    spinner.finish_and_clear();
+                }
+
+                println!(
+                    "{}[2/2] {}{}",
+                    color::TEXT_VARIANT.render_fg(),
+                    color::TEXT.render_fg(),
+                    strings::command::VERIFY_CHECKING,
+                );
+                progress_bar = Some(ProgressBar::progress(file_count as u64))
+            }
+            VerifyState::CheckFile => {
+                if let Some(progress) = &progress_bar {
+                    progress.inc(1);
+                }
+            }
+            VerifyState::Finish => {
+                if let Some(progress) = &progress_bar {
+                    progress.finish_and_clear();
+                }
+                break;
+            }
+            VerifyState::NoState => {}
+        }
+    }
+}
+
+/// Prints a warning header followed by each of the provided lines.
+fn print_section(header: &str, lines: impl IntoIterator<Item = String>) {
+    println!(
+        "{}{}{}",
+        color::WARNING.render_fg(),
+        header,
+        color::TEXT.render_fg(),
+    );
+    for line in lines {
+        println!("   {}", line);
+    }
+}
+
+/// Prints every problem that was found in a [`VerifyReport`].
+fn print_report(report: &VerifyReport) {
+    let broken_count = report.broken().len();
+    println!(
+        "{}{} / {} assetbundles {}{}",
+        color::SUCCESS.render_fg(),
+        report.total_count - broken_count,
+        report.total_count,
+        strings::command::VERIFY_VALID,
+        color::TEXT.render_fg(),
+    );
+
+    if !report.missing.is_empty() {
+        print_section(
+            strings::command::VERIFY_MISSING,
+            report.missing.iter().cloned(),
+        );
+    }
+
+    if !report.mismatched.is_empty() {
+        print_section(
+            strings::command::CORRUPT_BUNDLES,
+            report
+                .mismatched
+                .iter()
+                .map(|(bundle_name, status)| match status {
+                    BundleFileStatus::SizeMismatch { expected, actual } => {
+                        format!(
+                            "{} (size: expected {}, got {})",
+                            bundle_name, expected, actual
+                        )
+                    }
+                    BundleFileStatus::CrcMismatch { expected, actual } => {
+                        format!(
+                            "{} (crc: expected {}, got {})",
+                            bundle_name, expected, actual
+                        )
+                    }
+                    _ => bundle_name.clone(),
+                }),
+        );
+    }
+
+    if !report.unexpected_encryption.is_empty() {
+        print_section(
+            strings::command::VERIFY_ENCRYPTION,
+            report
+                .unexpected_encryption
+                .iter()
+                .map(|(bundle_name, state)| {
+                    let state = match state {
+                        EncryptionState::Encrypted => "encrypted",
+                        EncryptionState::Decrypted => "decrypted",
+                        EncryptionState::Unknown => "unknown",
+                    };
+                    format!("{} ({})", bundle_name, state)
+                }),
+        );
+    }
+
+    if !report.orphans.is_empty() {
+        print_section(
+            strings::command::VERIFY_ORPHANS,
+            report.orphans.iter().map(|path| path.display().to_string()),
+        );
+    }
+}
+
+/// Verifies a directory of assetbundles using the provided arguments.
+pub async fn verify_ab(args: VerifyAbArgs, output: OutputFormat) -> Result<(), Error> {
+    let verify_start = Instant::now();
+    let quiet = args.quiet || output.is_json();
+
+    let mut info = read_assetbundle_info(&args.info).await?;
+    if args.host_hash.is_some() {
+        info.host_hash = args.host_hash;
+    }
+
+    let url_provider = url_provider(args.server, args.endpoints.load().await?.as_ref())?;
+
+    let config = VerifyConfig::builder()
+        .platform(args.platform)
+        .encrypted(args.encrypt)
+        .map(args.concurrent, |config, concurrency| {
+            config.concurrency(concurrency)
+        })
+        .build();
+
+    let (verifier, state_recv) = Verifier::new(config, url_provider.clone());
+
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet {
+        drop(state_recv);
+        None
+    } else {
+        Some(tokio::spawn(watch_verify_ab_state(state_recv)))
+    };
+
+    let report = verifier.verify_ab(&args.dir, &info).await?;
+    drop(verifier);
+
+    if output.is_json() {
+        if let Some(watcher) = state_watcher {
+            watcher.await?;
+        }
+        output::print_summary(
+            "verify ab",
+            &Summary::from_verify(&report, verify_start.elapsed())
+                .with_paths(vec![args.dir.clone().into()]),
+        );
+    } else {
+        if let Some(watcher) = state_watcher {
+            watcher.await?;
+            println!(
+                "{}Verified {} files in {:?}{}",
+                color::SUCCESS.render_fg(),
+                report.total_count,
+                Instant::now().duration_since(verify_start),
+                color::TEXT.render_fg(),
+            );
+        }
+
+        print_report(&report);
+    }
+
+    if !args.repair {
+        return Ok(());
+    }
+
+    if report.broken().is_empty() {
+        if !quiet {
+            println!("{}", strings::command::VERIFY_NOTHING_TO_REPAIR);
+        }
+        return Ok(());
+    }
+
+    if !quiet {
+        println!("{}", strings::command::VERIFY_REPAIRING);
+    }
+
+    // re-download broken bundles
+    let fetch_config = FetchConfig::builder()
+        .platform(args.platform)
+        .server(args.server)
+        .url_provider(url_provider)
+        .http_config(args.http.into_config().await?)
+        .map(args.device.install_id_store(), |config, path| {
+            config.install_id_store(path)
+        })
+        .device_profile(args.device.into_profile(args.platform))
+        .map(args.credentials.store_path(), |config, path| {
+            config.credential_store(path)
+        })
+        .retry_policy(args.retry.into_policy())
+        .decrypt(!args.encrypt)
+        .map(args.hash, |config, hash| config.hash(hash))
+        .map(args.version, |config, version| config.version(version))
+        .map(args.concurrent, |config, concurrency| {
+            config.concurrency(concurrency)
+        })
+        .build();
+
+    let (mut fetcher, state_recv) = Fetcher::new(fetch_config).await?;
+
+    let state_watcher = if output.is_json() {
+        Some(tokio::spawn(output::watch_state(state_recv)))
+    } else if args.quiet {
+        None
+    } else {
+        Some(tokio::spawn(watch_fetch_ab_state(state_recv)))
+    };
+
+    let repair_start = Instant::now();
+    let download_result = fetcher
+        .download_ab(&args.dir, report.repair_config(info))
+        .await?;
+    drop(fetcher);
+
+    if output.is_json() {
+        if let Some(watcher) = state_watcher {
+            watcher.await?;
+        }
+        output::print_summary(
+            "verify ab repair",
+            &Summary::from_download_ab(&download_result, repair_start.elapsed())
+                .with_paths(vec![args.dir.into()]),
+        );
+    } else if let Some(watcher) = state_watcher {
+        watcher.await?;
+        println!(
+            "{}Successfully {} {} / {} files in {:?}{}",
+            color::SUCCESS.render_fg(),
+            strings::command::DOWNLOADED,
+            download_result.success_count,
+            download_result.total_count,
+            Instant::now().duration_since(repair_start),
+            color::TEXT.render_fg(),
+        );
+
+        if !download_result.corrupt.is_empty() {
+            print_section(
+                strings::command::CORRUPT_BUNDLES,
+                download_result.corrupt.iter().cloned(),
+            );
+        }
+    }
+
+    Ok(())
+}
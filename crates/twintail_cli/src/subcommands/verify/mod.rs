@@ -0,0 +1,24 @@
+pub mod ab;
+
+use crate::{Error, output::OutputFormat};
+use ab::VerifyAbArgs;
+use clap::{Args, Subcommand};
+
+#[derive(Debug, Subcommand)]
+enum Commands {
+    /// Verify downloaded assetbundles against an assetbundle info file
+    Ab(VerifyAbArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct VerifyArgs {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+/// Command handler for the verify subcommand.
+pub async fn verify(args: VerifyArgs, output: OutputFormat) -> Result<(), Error> {
+    match args.command {
+        Commands::Ab(args) => ab::verify_ab(args, output).await,
+    }
+}
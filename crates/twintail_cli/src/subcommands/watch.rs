@@ -0,0 +1,444 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::Duration,
+};
+
+use clap::Args;
+use tokio::{process::Command, time::sleep};
+use twintail_common::models::{
+    OptionalBuilder,
+    enums::{Platform, Server},
+};
+use twintail_core::{
+    config::{
+        download_ab_config::DownloadAbConfig, fetch_config::FetchConfig, retry_policy::RetryPolicy,
+    },
+    fetch::{FetchState, Fetcher, GameVersions},
+    selection::BundleSelection,
+    watch::{VersionUpdate, WatchState},
+};
+use twintail_sekai::{
+    device_profile::DeviceProfile, http_config::HttpConfig,
+    url::template_provider::TemplateUrlProvider,
+};
+
+use crate::{
+    Error, color,
+    output::{self, OutputFormat},
+    print_error, strings,
+    subcommands::fetch::{
+        CredentialArgs, DeviceArgs, EndpointArgs, HttpArgs, MaintenanceArgs, RetryArgs,
+        SelectionArgs, print_maintenance_wait, url_provider,
+    },
+};
+
+// constants
+const SUITE_DIR_NAME: &str = "suite";
+const AB_DIR_NAME: &str = "ab";
+
+#[derive(Debug, Args)]
+pub struct WatchArgs {
+    /// The servers to watch for updates. Can be provided multiple times
+    #[arg(short, long, value_enum, default_values_t = [Server::Japan])]
+    pub server: Vec<Server>,
+
+    #[command(flatten)]
+    pub endpoints: EndpointArgs,
+
+    #[command(flatten)]
+    pub http: HttpArgs,
+
+    #[command(flatten)]
+    pub device: DeviceArgs,
+
+    /// The device platform to fetch files for
+    #[arg(short, long, value_enum, default_value_t = Platform::Android)]
+    pub platform: Platform,
+
+    /// The number of seconds to wait between checks for updates
+    #[arg(long, short, default_value_t = 300)]
+    pub interval: u64,
+
+    /// If set, the servers will only be checked once, exiting with an error if any check failed.
+    /// Servers under maintenance are skipped, and do not make the check fail
+    #[arg(long, default_value_t = false)]
+    pub once: bool,
+
+    /// Path to the file where the last fetched versions are stored. Defaults to a file in your data directory
+    #[arg(long)]
+    pub state: Option<String>,
+
+    /// A command to run after an update was fetched.
+    /// The old and new versions are provided in the TWINTAIL_* environment variables
+    #[arg(long)]
+    pub exec: Option<String>,
+
+    /// If set, suitemaster files will not be fetched when the data version changes
+    #[arg(long, default_value_t = false)]
+    pub no_suite: bool,
+
+    /// If set, assetbundles will not be fetched when the asset version changes
+    #[arg(long, default_value_t = false)]
+    pub no_ab: bool,
+
+    /// The maximum number of files to download simultaneously
+    #[arg(long, short)]
+    pub concurrent: Option<usize>,
+
+    #[command(flatten)]
+    pub selection: SelectionArgs,
+
+    #[command(flatten)]
+    pub retry: RetryArgs,
+
+    #[command(flatten)]
+    pub maintenance: MaintenanceArgs,
+
+    /// If set, the downloaded files will not be decrypted
+    #[arg(long, short, default_value_t = false)]
+    pub encrypt: bool,
+
+    /// Whether to save suitemaster .json files in a more compact format, reducing their file size
+    #[arg(long, default_value_t = false)]
+    pub compact: bool,
+
+    #[command(flatten)]
+    pub credentials: CredentialArgs,
+
+    /// Whether to output status messages
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
+
+    /// The directory to output files to. The files of each server are placed in a directory named after the server
+    pub out_dir: String,
+}
+
+/// The options that every check for updates is performed with.
+struct WatchOptions {
+    platform: Platform,
+    endpoints: Option<TemplateUrlProvider>,
+    http_config: HttpConfig,
+    device_profile: DeviceProfile,
+    install_id_store: Option<PathBuf>,
+    exec: Option<String>,
+    suite: bool,
+    ab: bool,
+    concurrency: Option<usize>,
+    selection: BundleSelection,
+    retry_policy: RetryPolicy,
+    maintenance_wait: Option<RetryPolicy>,
+    decrypt: bool,
+    pretty_json: bool,
+    credential_store: Option<PathBuf>,
+    quiet: bool,
+    output: OutputFormat,
+    out_dir: PathBuf,
+}
+
+/// Prints a status message about a server.
+fn print_status(server: Server, message: &str) {
+    println!(
+        "{}[{}] {}{}",
+        color::TEXT_VARIANT.render_fg(),
+        server,
+        color::TEXT.render_fg(),
+        message,
+    );
+}
+
+/// Runs a command in the system shell with environment variables describing an update.
+async fn run_command(
+    command: &str,
+    update: &VersionUpdate,
+    server_dir: &Path,
+) -> Result<ExitStatus, Error> {
+    let mut process = if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.arg("/C");
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.arg("-c");
+        process
+    };
+
+    Ok(process
+        .arg(command)
+        .envs(update.env_vars())
+        .env("TWINTAIL_OUT_DIR", server_dir)
+        .status()
+        .await?)
+}
+
+/// Checks a server for new versions, and fetches the files of any new versions.
+///
+/// Versions are recorded in the watch state as soon as their files were fetched,
+/// so that versions whose files failed to download are fetched again by the next check.
+async fn check_server(
+    server: Server,
+    options: &WatchOptions,
+    state: &mut WatchState,
+) -> Result<(), Error> {
+    let fetch_config = FetchConfig::builder()
+        .platform(options.platform)
+        .server(server)
+        .url_provider(url_provider(server, options.endpoints.as_ref())?)
+        .http_config(options.http_config.clone())
+        .map(options.install_id_store.clone(), |config, path| {
+            config.install_id_store(path)
+        })
+        .device_profile(options.device_profile.clone())
+        .map(options.credential_store.clone(), |config, path| {
+            config.credential_store(path)
+        })
+        .retry_policy(options.retry_policy.clone())
+        .map(options.maintenance_wait.clone(), |config, policy| {
+            config.wait_for_maintenance(policy)
+        })
+        .decrypt(options.decrypt)
+        .pretty_json(options.pretty_json)
+        .map(options.concurrency, |config, concurrency| {
+            config.concurrency(concurrency)
+        })
+        .build();
+    let (mut fetcher, mut receiver) = Fetcher::new(fetch_config).await?;
+
+    // report waits for maintenance to end until the fetcher is dropped
+    if options.quiet {
+        drop(receiver);
+    } else {
+        tokio::spawn(async move {
+            while let Some(fetch_state) = receiver.recv().await {
+                if let FetchState::WaitForMaintenance(seconds) = fetch_state {
+                    print_maintenance_wait(None, seconds);
+                }
+            }
+        });
+    }
+
+    let versions = fetcher.get_versions().await?;
+    let Some(update) = state.check(server, options.platform, versions) else {
+        if !options.quiet {
+            print_status(server, strings::command::WATCH_UP_TO_DATE);
+        }
+        return Ok(());
+    };
+
+    let server_dir = options.out_dir.join(server.to_string());
+    let mut data_complete = true;
+    let mut assets_complete = true;
+    let mut recorded = update.old.clone().unwrap_or_else(|| GameVersions {
+        app_version: String::new(),
+        data_version: String::new(),
+        asset_version: String::new(),
+        asset_hash: String::new(),
+    });
+
+    // a new app version has no files to fetch, so it is recorded right away
+    if update.app_changed() {
+        if !options.quiet {
+            print_status(
+                server,
+                &format!(
+                    "{} {}",
+                    strings::command::WATCH_NEW_APP_VERSION,
+                    update.new.app_version
+                ),
+            );
+        }
+
+        recorded.app_version = update.new.app_version.clone();
+        state.insert(server, options.platform, recorded.clone());
+        state.save().await?;
+    }
+
+    // fetch suitemaster files
+    if update.data_changed() {
+        if !options.quiet {
+            print_status(
+                server,
+                &format!(
+                    "{} {}",
+                    strings::command::WATCH_NEW_DATA_VERSION,
+                    update.new.data_version
+                ),
+            );
+        }
+
+        if options.suite {
+            let (downloaded_count, file_count, _) = fetcher
+                .download_suite(server_dir.join(SUITE_DIR_NAME))
+                .await?;
+            if !options.quiet {
+                print_status(
+                    server,
+                    &format!(
+                        "{} {} / {} {}",
+                        strings::command::WATCH_DOWNLOADED,
+                        downloaded_count,
+                        file_count,
+                        strings::command::WATCH_SUITE_FILES,
+                    ),
+                );
+            }
+            data_complete = downloaded_count == file_count;
+        }
+
+        // a failed download is retried by the next check
+        if data_complete {
+            recorded.data_version = update.new.data_version.clone();
+            state.insert(server, options.platform, recorded.clone());
+            state.save().await?;
+        }
+    }
+
+    // fetch the assetbundles that changed since the previous download
+    if update.assets_changed() {
+        if !options.quiet {
+            print_status(
+                server,
+                &format!(
+                    "{} {}",
+                    strings::command::WATCH_NEW_ASSET_VERSION,
+                    update.new.asset_version
+                ),
+            );
+        }
+
+        if options.ab {
+            // the versions were retrieved by logging in, so the assets are downloaded without logging in again
+            let download_ab_config = DownloadAbConfig::builder()
+                .update(true)
+                .asset_version(update.new.asset_version.clone())
+                .asset_hash(update.new.asset_hash.clone())
+                .selection(options.selection.clone())
+                .build();
+            let download_result = fetcher
+                .download_ab(server_dir.join(AB_DIR_NAME), download_ab_config)
+                .await?;
+            if !options.quiet {
+                print_status(
+                    server,
+                    &format!(
+                        "{} {} / {} {}",
+                        strings::command::WATCH_DOWNLOADED,
+                        download_result.success_count,
+                        download_result.total_count,
+                        strings::command::WATCH_AB_FILES,
+                    ),
+                );
+            }
+            assets_complete = download_result.success_count == download_result.total_count;
+        }
+
+        if assets_complete {
+            recorded.asset_version = update.new.asset_version.clone();
+            recorded.asset_hash = update.new.asset_hash.clone();
+            state.insert(server, options.platform, recorded);
+            state.save().await?;
+        }
+    }
+
+    if options.output.is_json() {
+        output::print_data("watch", &update);
+    }
+
+    // run the user's command once every file of the update was fetched
+    if data_complete
+        && assets_complete
+        && let Some(command) = &options.exec
+    {
+        let status = run_command(command, &update, &server_dir).await?;
+        if !status.success() && !options.output.is_json() {
+            println!(
+                "{}[{}] {} {}{}",
+                color::WARNING.render_fg(),
+                server,
+                strings::command::WATCH_COMMAND_FAILED,
+                status,
+                color::TEXT.render_fg(),
+            );
+        }
+    }
+
+    // files that failed to download make the check fail, even though they are fetched again later
+    if !data_complete || !assets_complete {
+        return Err(Error::WatchDownloadIncomplete);
+    }
+
+    Ok(())
+}
+
+/// Periodically checks servers for new app, data and asset versions, and fetches their files.
+pub async fn watch(args: WatchArgs, output: OutputFormat) -> Result<(), Error> {
+    let state_path = match args.state {
+        Some(state_path) => PathBuf::from(state_path),
+        None => WatchState::default_path().ok_or(Error::NoWatchStatePath)?,
+    };
+    let mut state = WatchState::load(&state_path).await?;
+
+    let options = WatchOptions {
+        platform: args.platform,
+        endpoints: args.endpoints.load().await?,
+        http_config: args.http.into_config().await?,
+        install_id_store: args.device.install_id_store(),
+        device_profile: args.device.into_profile(args.platform),
+        exec: args.exec,
+        suite: !args.no_suite,
+        ab: !args.no_ab,
+        concurrency: args.concurrent,
+        selection: args.selection.into_selection()?,
+        retry_policy: args.retry.into_policy(),
+        maintenance_wait: args.maintenance.into_policy(),
+        decrypt: !args.encrypt,
+        pretty_json: !args.compact,
+        credential_store: args.credentials.store_path(),
+        quiet: args.quiet || output.is_json(),
+        output,
+        out_dir: PathBuf::from(args.out_dir),
+    };
+
+    loop {
+        let mut failed_count = 0;
+        for &server in &args.server {
+            // a failed check should not stop the other servers from being watched
+            match check_server(server, &options, &mut state).await {
+                Ok(()) => {}
+                // a server under maintenance is checked again by the next check
+                Err(Error::TwintailCore(twintail_core::Error::Maintenance)) => {
+                    if !options.quiet {
+                        print_status(server, strings::command::WATCH_MAINTENANCE_SKIPPED);
+                    }
+                }
+                Err(err) => {
+                    if !output.is_json() {
+                        print_status(server, strings::command::WATCH_CHECK_FAILED);
+                    }
+                    print_error(&err, output);
+                    failed_count += 1;
+                }
+            }
+        }
+
+        // a single check reports its failures through the exit status
+        if args.once {
+            return match failed_count {
+                0 => Ok(()),
+                failed_count => Err(Error::WatchCheckFailed(failed_count)),
+            };
+        }
+
+        if !options.quiet {
+            println!(
+                "{}{} {} {}{}",
+                color::TEXT_VARIANT.render_fg(),
+                strings::command::WATCH_NEXT_CHECK,
+                args.interval,
+                strings::command::WATCH_SECONDS,
+                color::TEXT.render_fg(),
+            );
+        }
+        sleep(Duration::from_secs(args.interval)).await;
+    }
+}
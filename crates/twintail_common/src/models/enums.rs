@@ -1,3 +1,5 @@
+use std::fmt;
+
 use aes::cipher::KeyInit;
 use clap::ValueEnum;
 use hmac::Hmac;
@@ -6,17 +8,19 @@ use sha2::Sha256;
 
 use crate::crypto::{self, aes::AesConfig};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum, Serialize, Deserialize,
+)]
 pub enum Platform {
     Android,
     Ios,
 }
 
-impl ToString for Platform {
-    fn to_string(&self) -> String {
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Platform::Android => String::from("android"),
-            Platform::Ios => String::from("ios"),
+            Platform::Android => f.write_str("android"),
+            Platform::Ios => f.write_str("ios"),
         }
     }
 }
@@ -27,7 +31,7 @@ pub enum CryptOperation {
     Decrypt,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, ValueEnum)]
 pub enum AssetbundleCategory {
     OnDemand,
     StartApp,
@@ -37,17 +41,33 @@ pub enum AssetbundleCategory {
 }
 
 /// Represents a server for the game in a specific region
-#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Serialize, Deserialize)]
+#[derive(
+    Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum, Serialize, Deserialize,
+)]
 pub enum Server {
     Japan,
     Global,
+    /// A server whose urls are loaded from a file, such as a private or staging server.
+    ///
+    /// Uses the keys of the Japan server.
+    Custom,
+}
+
+impl fmt::Display for Server {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Server::Japan => f.write_str("japan"),
+            Server::Global => f.write_str("global"),
+            Server::Custom => f.write_str("custom"),
+        }
+    }
 }
 
 impl Server {
     /// Returns the AesConfig for a server.
     pub fn get_aes_config(&self) -> AesConfig {
         match self {
-            Self::Japan => AesConfig {
+            Self::Japan | Self::Custom => AesConfig {
                 key: *crypto::JAPAN_KEY,
                 iv: *crypto::JAPAN_IV,
             },
@@ -61,7 +81,7 @@ impl Server {
     /// Returns the JSON Web Token HMAC SHA-256 key for a server.
     pub fn get_jwt_key(&self) -> Hmac<Sha256> {
         match self {
-            Self::Japan => Hmac::new_from_slice(
+            Self::Japan | Self::Custom => Hmac::new_from_slice(
                 b"dRmS5U3jP9XJDFzoI7eeXhzT826v2qJRO9n14h9JR1phTL6so3v7YBiODRdrrfMOl3Y8FOI3pS5UTYC5",
             )
             .unwrap(),
@@ -0,0 +1,273 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use twintail_common::models::enums::AssetbundleCategory;
+use twintail_sekai::models::{Assetbundle, AssetbundleInfo};
+
+use super::bundle_prefix;
+
+/// Returns true if an assetbundle's contents differ between two asset versions.
+pub fn is_bundle_changed(old_bundle: &Assetbundle, new_bundle: &Assetbundle) -> bool {
+    old_bundle.hash != new_bundle.hash
+}
+
+/// Returns the bundles in main_bundles that do not exist in compare_bundles, or that changed.
+pub(crate) fn get_assetbundles_differences(
+    main_bundles: HashMap<String, Assetbundle>,
+    compare_bundles: &HashMap<String, Assetbundle>,
+) -> HashMap<String, Assetbundle> {
+    main_bundles
+        .into_iter()
+        .filter(|(bundle_name, bundle)| {
+            compare_bundles
+                .get(bundle_name)
+                .is_none_or(|compare_bundle| is_bundle_changed(compare_bundle, bundle))
+        })
+        .collect()
+}
+
+/// An assetbundle that was added or removed between two asset versions.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffBundle {
+    pub bundle_name: String,
+    pub category: AssetbundleCategory,
+    pub hash: String,
+    pub file_size: u64,
+}
+
+impl From<&Assetbundle> for DiffBundle {
+    fn from(bundle: &Assetbundle) -> Self {
+        Self {
+            bundle_name: bundle.bundle_name.clone(),
+            category: bundle.category,
+            hash: bundle.hash.clone(),
+            file_size: bundle.file_size,
+        }
+    }
+}
+
+/// An assetbundle whose contents changed between two asset versions.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleChange {
+    pub bundle_name: String,
+    /// the category of the assetbundle in the new asset version
+    pub category: AssetbundleCategory,
+    pub old_hash: String,
+    pub new_hash: String,
+    pub old_size: u64,
+    pub new_size: u64,
+    /// the number of bytes that the assetbundle grew by, negative if it shrunk
+    pub size_delta: i64,
+}
+
+/// A summary of the differences between two asset versions for bundles that share a prefix.
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefixSummary {
+    pub prefix: String,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    /// the total number of bytes that bundles with the prefix grew by, negative if they shrunk
+    pub size_delta: i64,
+}
+
+/// The differences between two assetbundle infos.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetbundleInfoDiff {
+    pub old_version: String,
+    pub new_version: String,
+    /// bundles that only exist in the new info, sorted by name
+    pub added: Vec<DiffBundle>,
+    /// bundles that only exist in the old info, sorted by name
+    pub removed: Vec<DiffBundle>,
+    /// bundles that exist in both infos, but whose hash changed, sorted by name
+    pub changed: Vec<BundleChange>,
+}
+
+impl AssetbundleInfoDiff {
+    /// Compares an old assetbundle info with a newer one.
+    pub fn new(old_info: &AssetbundleInfo, new_info: &AssetbundleInfo) -> Self {
+        let mut added: Vec<DiffBundle> = new_info
+            .bundles
+            .iter()
+            .filter(|(bundle_name, _)| !old_info.bundles.contains_key(*bundle_name))
+            .map(|(_, bundle)| bundle.into())
+            .collect();
+        added.sort_by(|a, b| a.bundle_name.cmp(&b.bundle_name));
+
+        let mut removed: Vec<DiffBundle> = old_info
+            .bundles
+            .iter()
+            .filter(|(bundle_name, _)| !new_info.bundles.contains_key(*bundle_name))
+            .map(|(_, bundle)| bundle.into())
+            .collect();
+        removed.sort_by(|a, b| a.bundle_name.cmp(&b.bundle_name));
+
+        let mut changed: Vec<BundleChange> = new_info
+            .bundles
+            .iter()
+            .filter_map(|(bundle_name, new_bundle)| {
+                let old_bundle = old_info.bundles.get(bundle_name)?;
+                is_bundle_changed(old_bundle, new_bundle).then(|| BundleChange {
+                    bundle_name: bundle_name.clone(),
+                    category: new_bundle.category,
+                    old_hash: old_bundle.hash.clone(),
+                    new_hash: new_bundle.hash.clone(),
+                    old_size: old_bundle.file_size,
+                    new_size: new_bundle.file_size,
+                    size_delta: new_bundle.file_size as i64 - old_bundle.file_size as i64,
+                })
+            })
+            .collect();
+        changed.sort_by(|a, b| a.bundle_name.cmp(&b.bundle_name));
+
+        Self {
+            old_version: old_info.version.clone(),
+            new_version: new_info.version.clone(),
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    /// Returns true if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Returns the total number of bytes that the assetbundles grew by, negative if they shrunk.
+    pub fn size_delta(&self) -> i64 {
+        let added: i64 = self.added.iter().map(|b| b.file_size as i64).sum();
+        let removed: i64 = self.removed.iter().map(|b| b.file_size as i64).sum();
+        let changed: i64 = self.changed.iter().map(|b| b.size_delta).sum();
+        added - removed + changed
+    }
+
+    /// Summarizes the differences of bundles grouped by their name prefix.
+    ///
+    /// See [`super::bundle_prefix`] for how prefixes are determined from ``depth``.
+    /// The summaries are sorted by prefix.
+    pub fn summarize_by_prefix(&self, depth: usize) -> Vec<PrefixSummary> {
+        // (bundle name, (added, removed, changed), size delta) of every difference
+        let differences = self
+            .added
+            .iter()
+            .map(|bundle| (&bundle.bundle_name, (1, 0, 0), bundle.file_size as i64))
+            .chain(
+                self.removed
+                    .iter()
+                    .map(|bundle| (&bundle.bundle_name, (0, 1, 0), -(bundle.file_size as i64))),
+            )
+            .chain(
+                self.changed
+                    .iter()
+                    .map(|change| (&change.bundle_name, (0, 0, 1), change.size_delta)),
+            );
+
+        let mut summaries: BTreeMap<String, PrefixSummary> = BTreeMap::new();
+        for (bundle_name, (added, removed, changed), size_delta) in differences {
+            let prefix = bundle_prefix(bundle_name, depth);
+            let summary = summaries
+                .entry(prefix.clone())
+                .or_insert_with(|| PrefixSummary {
+                    prefix,
+                    ..Default::default()
+                });
+            summary.added += added;
+            summary.removed += removed;
+            summary.changed += changed;
+            summary.size_delta += size_delta;
+        }
+
+        summaries.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+
+    fn get_bundle(bundle_name: &str, hash: &str, file_size: u64) -> (String, Assetbundle) {
+        (
+            bundle_name.into(),
+            Assetbundle {
+                hash: hash.into(),
+                file_size,
+                ..empty_bundle(bundle_name)
+            },
+        )
+    }
+
+    fn get_info(version: &str, bundles: Vec<(String, Assetbundle)>) -> AssetbundleInfo {
+        AssetbundleInfo {
+            version: version.into(),
+            os: "android".into(),
+            hash: None,
+            host_hash: None,
+            bundles: bundles.into_iter().collect(),
+        }
+    }
+
+    #[test]
+    fn test_assetbundle_info_diff() {
+        let old_info = get_info(
+            "4.0.5.10",
+            vec![
+                get_bundle("music/long/0001", "a", 100),
+                get_bundle("music/long/0002", "a", 100),
+                get_bundle("character/member/01miku", "a", 1000),
+            ],
+        );
+        let new_info = get_info(
+            "4.0.6.0",
+            vec![
+                get_bundle("music/long/0001", "a", 100),
+                get_bundle("music/long/0003", "a", 300),
+                get_bundle("character/member/01miku", "b", 800),
+            ],
+        );
+
+        let diff = AssetbundleInfoDiff::new(&old_info, &new_info);
+        assert_eq!(diff.old_version, "4.0.5.10");
+        assert_eq!(diff.new_version, "4.0.6.0");
+        assert_eq!(
+            diff.added,
+            vec![(&new_info.bundles["music/long/0003"]).into()]
+        );
+        assert_eq!(
+            diff.removed,
+            vec![(&old_info.bundles["music/long/0002"]).into()]
+        );
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].bundle_name, "character/member/01miku");
+        assert_eq!(diff.changed[0].old_hash, "a");
+        assert_eq!(diff.changed[0].new_hash, "b");
+        assert_eq!(diff.changed[0].size_delta, -200);
+        assert_eq!(diff.size_delta(), 300 - 100 - 200);
+
+        assert_eq!(
+            diff.summarize_by_prefix(2),
+            vec![
+                PrefixSummary {
+                    prefix: "character/member/".into(),
+                    added: 0,
+                    removed: 0,
+                    changed: 1,
+                    size_delta: -200,
+                },
+                PrefixSummary {
+                    prefix: "music/long/".into(),
+                    added: 1,
+                    removed: 1,
+                    changed: 0,
+                    size_delta: 200,
+                },
+            ]
+        );
+    }
+}
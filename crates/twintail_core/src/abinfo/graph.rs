@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use serde::Serialize;
+use twintail_sekai::models::AssetbundleInfo;
+
+/// The graph formed by the dependencies of the assetbundles in an assetbundle info.
+///
+/// Dependencies that are not listed in the assetbundle info are still part of the graph.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGraph {
+    /// the direct dependencies of every assetbundle, by assetbundle name
+    dependencies: BTreeMap<String, BTreeSet<String>>,
+    /// the assetbundles that directly depend on every assetbundle, by assetbundle name
+    #[serde(skip)]
+    dependents: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl DependencyGraph {
+    /// Builds the dependency graph of an assetbundle info.
+    pub fn new(info: &AssetbundleInfo) -> Self {
+        let mut graph = Self::default();
+
+        for bundle in info.bundles.values() {
+            graph
+                .dependents
+                .entry(bundle.bundle_name.clone())
+                .or_default();
+            let dependencies = graph
+                .dependencies
+                .entry(bundle.bundle_name.clone())
+                .or_default();
+
+            for dependency in &bundle.dependencies {
+                dependencies.insert(dependency.clone());
+                graph
+                    .dependents
+                    .entry(dependency.clone())
+                    .or_default()
+                    .insert(bundle.bundle_name.clone());
+            }
+        }
+
+        // make sure that dependencies which are not in the info are nodes too
+        for bundle_name in graph.dependents.keys() {
+            if !graph.dependencies.contains_key(bundle_name) {
+                graph
+                    .dependencies
+                    .insert(bundle_name.clone(), BTreeSet::new());
+            }
+        }
+
+        graph
+    }
+
+    /// Returns true if the graph contains an assetbundle with this name.
+    pub fn contains(&self, bundle_name: &str) -> bool {
+        self.dependencies.contains_key(bundle_name)
+    }
+
+    /// Returns the names of every assetbundle in the graph, sorted.
+    pub fn bundles(&self) -> impl Iterator<Item = &str> {
+        self.dependencies.keys().map(|name| name.as_str())
+    }
+
+    /// Returns the assetbundles that an assetbundle directly depends on, sorted.
+    pub fn dependencies(&self, bundle_name: &str) -> Vec<&str> {
+        Self::neighbours(&self.dependencies, bundle_name)
+    }
+
+    /// Returns the assetbundles that directly depend on an assetbundle, sorted.
+    pub fn dependents(&self, bundle_name: &str) -> Vec<&str> {
+        Self::neighbours(&self.dependents, bundle_name)
+    }
+
+    /// Returns every assetbundle that an assetbundle depends on, directly or through other assetbundles, sorted.
+    pub fn transitive_dependencies(&self, bundle_name: &str) -> Vec<&str> {
+        Self::reachable(&self.dependencies, bundle_name)
+    }
+
+    /// Returns every assetbundle that depends on an assetbundle, directly or through other assetbundles, sorted.
+    pub fn transitive_dependents(&self, bundle_name: &str) -> Vec<&str> {
+        Self::reachable(&self.dependents, bundle_name)
+    }
+
+    /// Returns the assetbundles that no other assetbundle depends on, sorted.
+    pub fn unreferenced(&self) -> Vec<&str> {
+        self.dependents
+            .iter()
+            .filter(|(_, dependents)| dependents.is_empty())
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Exports the graph in the DOT format, with an edge from every assetbundle to each of its dependencies.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n");
+        for (bundle_name, dependencies) in &self.dependencies {
+            if dependencies.is_empty() {
+                dot.push_str(&format!("    {};\n", dot_id(bundle_name)));
+            }
+            for dependency in dependencies {
+                dot.push_str(&format!(
+                    "    {} -> {};\n",
+                    dot_id(bundle_name),
+                    dot_id(dependency)
+                ));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn neighbours<'a>(
+        edges: &'a BTreeMap<String, BTreeSet<String>>,
+        bundle_name: &str,
+    ) -> Vec<&'a str> {
+        edges
+            .get(bundle_name)
+            .map(|names| names.iter().map(|name| name.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    fn reachable<'a>(
+        edges: &'a BTreeMap<String, BTreeSet<String>>,
+        bundle_name: &str,
+    ) -> Vec<&'a str> {
+        let mut visited: BTreeSet<&str> = BTreeSet::new();
+        let mut to_visit: VecDeque<&str> = Self::neighbours(edges, bundle_name).into();
+
+        while let Some(name) = to_visit.pop_front() {
+            if name != bundle_name && visited.insert(name) {
+                to_visit.extend(Self::neighbours(edges, name));
+            }
+        }
+
+        visited.into_iter().collect()
+    }
+}
+
+/// Quotes an assetbundle name so that it can be used as a DOT identifier.
+fn dot_id(bundle_name: &str) -> String {
+    format!(
+        "\"{}\"",
+        bundle_name.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+    use twintail_sekai::models::Assetbundle;
+
+    fn get_bundle(bundle_name: &str, dependencies: &[&str]) -> (String, Assetbundle) {
+        (
+            bundle_name.into(),
+            Assetbundle {
+                dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+                ..empty_bundle(bundle_name)
+            },
+        )
+    }
+
+    #[test]
+    fn test_dependency_graph() {
+        let info = AssetbundleInfo {
+            version: "4.0.5.10".into(),
+            os: "android".into(),
+            hash: None,
+            host_hash: None,
+            bundles: [
+                get_bundle("character/member/01miku", &["character/common", "shader"]),
+                get_bundle("character/member/02rin", &["character/common"]),
+                get_bundle("character/common", &["shader"]),
+                get_bundle("shader", &[]),
+                get_bundle("music/long/0001", &["missing"]),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let graph = DependencyGraph::new(&info);
+        assert!(graph.contains("missing"));
+        assert_eq!(graph.bundles().count(), 6);
+
+        assert_eq!(
+            graph.dependencies("character/member/01miku"),
+            vec!["character/common", "shader"]
+        );
+        assert_eq!(
+            graph.transitive_dependencies("character/member/02rin"),
+            vec!["character/common", "shader"]
+        );
+        assert_eq!(
+            graph.dependents("shader"),
+            vec!["character/common", "character/member/01miku"]
+        );
+        assert_eq!(
+            graph.transitive_dependents("shader"),
+            vec![
+                "character/common",
+                "character/member/01miku",
+                "character/member/02rin"
+            ]
+        );
+        assert!(graph.dependencies("not/a/bundle").is_empty());
+
+        assert_eq!(
+            graph.unreferenced(),
+            vec![
+                "character/member/01miku",
+                "character/member/02rin",
+                "music/long/0001"
+            ]
+        );
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph dependencies {\n"));
+        assert!(dot.contains("    \"character/common\" -> \"shader\";\n"));
+        assert!(dot.contains("    \"shader\";\n"));
+    }
+}
@@ -0,0 +1,40 @@
+pub mod diff;
+pub mod graph;
+pub mod stats;
+
+/// Returns the directory prefix of a bundle name, made of at most ``depth`` of its leading path segments.
+///
+/// The prefix always ends with a ``/``, unless the bundle name has no directories, where it is empty.
+/// For example, the prefix of ``character/member/01miku`` with a depth of 1 is ``character/``.
+pub fn bundle_prefix(bundle_name: &str, depth: usize) -> String {
+    let directories: Vec<&str> = match bundle_name.rsplit_once('/') {
+        Some((directories, _)) => directories.split('/').collect(),
+        None => Vec::new(),
+    };
+
+    directories
+        .iter()
+        .take(depth)
+        .map(|directory| format!("{}/", directory))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_prefix() {
+        assert_eq!(bundle_prefix("character/member/01miku", 1), "character/");
+        assert_eq!(
+            bundle_prefix("character/member/01miku", 2),
+            "character/member/"
+        );
+        assert_eq!(
+            bundle_prefix("character/member/01miku", 5),
+            "character/member/"
+        );
+        assert_eq!(bundle_prefix("character/member/01miku", 0), "");
+        assert_eq!(bundle_prefix("shader", 2), "");
+    }
+}
@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use twintail_common::models::enums::AssetbundleCategory;
+use twintail_sekai::models::{Assetbundle, AssetbundleInfo};
+
+use super::bundle_prefix;
+
+/// The number of assetbundles in a group, and their total file size.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SizeTotal {
+    pub count: usize,
+    /// the sum of the file sizes of the assetbundles, in bytes
+    pub total_size: u64,
+}
+
+impl SizeTotal {
+    fn add(&mut self, bundle: &Assetbundle) {
+        self.count += 1;
+        self.total_size += bundle.file_size;
+    }
+}
+
+/// The size of the assetbundles in a category.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CategorySize {
+    pub category: AssetbundleCategory,
+    #[serde(flatten)]
+    pub total: SizeTotal,
+}
+
+/// The size of the assetbundles that share a name prefix.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PrefixSize {
+    pub prefix: String,
+    #[serde(flatten)]
+    pub total: SizeTotal,
+}
+
+/// The size of a single assetbundle.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSize {
+    pub bundle_name: String,
+    pub category: AssetbundleCategory,
+    pub file_size: u64,
+}
+
+/// The number of assetbundles that directly depend on an assetbundle.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleFanIn {
+    pub bundle_name: String,
+    pub dependents: usize,
+}
+
+/// Size statistics of an assetbundle info.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetbundleInfoStats {
+    pub version: String,
+    pub total: SizeTotal,
+    /// sizes by category, sorted from largest to smallest
+    pub by_category: Vec<CategorySize>,
+    /// sizes by name prefix, sorted from largest to smallest
+    pub by_prefix: Vec<PrefixSize>,
+    /// the size of builtin assetbundles
+    pub builtin: SizeTotal,
+    /// the size of assetbundles that are not builtin, and are downloaded on demand
+    pub on_demand: SizeTotal,
+    /// the largest assetbundles, sorted from largest to smallest
+    pub largest: Vec<BundleSize>,
+    /// the assetbundles that the most assetbundles depend on, sorted from most to least dependents
+    pub fan_in: Vec<BundleFanIn>,
+}
+
+impl AssetbundleInfoStats {
+    /// Computes the size statistics of an assetbundle info.
+    ///
+    /// Prefixes are made of ``depth`` leading directories, see [`super::bundle_prefix`].
+    /// At most ``top`` assetbundles are listed in [`Self::largest`] and [`Self::fan_in`].
+    pub fn new(info: &AssetbundleInfo, depth: usize, top: usize) -> Self {
+        let mut total = SizeTotal::default();
+        let mut builtin = SizeTotal::default();
+        let mut on_demand = SizeTotal::default();
+        let mut categories: HashMap<AssetbundleCategory, SizeTotal> = HashMap::new();
+        let mut prefixes: HashMap<String, SizeTotal> = HashMap::new();
+        let mut dependents: HashMap<&str, usize> = HashMap::new();
+
+        for bundle in info.bundles.values() {
+            total.add(bundle);
+            if bundle.is_builtin {
+                builtin.add(bundle);
+            } else {
+                on_demand.add(bundle);
+            }
+            categories.entry(bundle.category).or_default().add(bundle);
+            prefixes
+                .entry(bundle_prefix(&bundle.bundle_name, depth))
+                .or_default()
+                .add(bundle);
+            for dependency in &bundle.dependencies {
+                *dependents.entry(dependency).or_default() += 1;
+            }
+        }
+
+        let mut by_category: Vec<CategorySize> = categories
+            .into_iter()
+            .map(|(category, total)| CategorySize { category, total })
+            .collect();
+        by_category.sort_by(|a, b| {
+            b.total
+                .total_size
+                .cmp(&a.total.total_size)
+                .then_with(|| b.total.count.cmp(&a.total.count))
+        });
+
+        let mut by_prefix: Vec<PrefixSize> = prefixes
+            .into_iter()
+            .map(|(prefix, total)| PrefixSize { prefix, total })
+            .collect();
+        by_prefix.sort_by(|a, b| {
+            b.total
+                .total_size
+                .cmp(&a.total.total_size)
+                .then_with(|| a.prefix.cmp(&b.prefix))
+        });
+
+        let mut largest: Vec<BundleSize> = info
+            .bundles
+            .values()
+            .map(|bundle| BundleSize {
+                bundle_name: bundle.bundle_name.clone(),
+                category: bundle.category,
+                file_size: bundle.file_size,
+            })
+            .collect();
+        largest.sort_by(|a, b| {
+            b.file_size
+                .cmp(&a.file_size)
+                .then_with(|| a.bundle_name.cmp(&b.bundle_name))
+        });
+        largest.truncate(top);
+
+        let mut fan_in: Vec<BundleFanIn> = dependents
+            .into_iter()
+            .map(|(bundle_name, dependents)| BundleFanIn {
+                bundle_name: bundle_name.into(),
+                dependents,
+            })
+            .collect();
+        fan_in.sort_by(|a, b| {
+            b.dependents
+                .cmp(&a.dependents)
+                .then_with(|| a.bundle_name.cmp(&b.bundle_name))
+        });
+        fan_in.truncate(top);
+
+        Self {
+            version: info.version.clone(),
+            total,
+            by_category,
+            by_prefix,
+            builtin,
+            on_demand,
+            largest,
+            fan_in,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+
+    fn get_bundle(
+        bundle_name: &str,
+        category: AssetbundleCategory,
+        file_size: u64,
+        dependencies: &[&str],
+        is_builtin: bool,
+    ) -> (String, Assetbundle) {
+        (
+            bundle_name.into(),
+            Assetbundle {
+                category,
+                file_size,
+                dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+                is_builtin,
+                ..empty_bundle(bundle_name)
+            },
+        )
+    }
+
+    #[test]
+    fn test_assetbundle_info_stats() {
+        let info = AssetbundleInfo {
+            version: "4.0.5.10".into(),
+            os: "android".into(),
+            hash: None,
+            host_hash: None,
+            bundles: [
+                get_bundle("shader", AssetbundleCategory::StartApp, 50, &[], true),
+                get_bundle(
+                    "sound/scenario/voice/01miku",
+                    AssetbundleCategory::AdditionalVoice,
+                    300,
+                    &[],
+                    false,
+                ),
+                get_bundle(
+                    "character/member/01miku",
+                    AssetbundleCategory::OnDemand,
+                    1000,
+                    &["shader"],
+                    false,
+                ),
+                get_bundle(
+                    "character/member/02rin",
+                    AssetbundleCategory::OnDemand,
+                    800,
+                    &["shader", "sound/scenario/voice/01miku"],
+                    false,
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let stats = AssetbundleInfoStats::new(&info, 1, 2);
+        assert_eq!(stats.version, "4.0.5.10");
+        assert_eq!(
+            stats.total,
+            SizeTotal {
+                count: 4,
+                total_size: 2150
+            }
+        );
+        assert_eq!(
+            stats.builtin,
+            SizeTotal {
+                count: 1,
+                total_size: 50
+            }
+        );
+        assert_eq!(
+            stats.on_demand,
+            SizeTotal {
+                count: 3,
+                total_size: 2100
+            }
+        );
+
+        assert_eq!(
+            stats
+                .by_category
+                .iter()
+                .map(|c| (c.category, c.total.total_size))
+                .collect::<Vec<_>>(),
+            vec![
+                (AssetbundleCategory::OnDemand, 1800),
+                (AssetbundleCategory::AdditionalVoice, 300),
+                (AssetbundleCategory::StartApp, 50),
+            ]
+        );
+        assert_eq!(
+            stats
+                .by_prefix
+                .iter()
+                .map(|p| (p.prefix.as_str(), p.total.count))
+                .collect::<Vec<_>>(),
+            vec![("character/", 2), ("sound/", 1), ("", 1)]
+        );
+
+        assert_eq!(
+            stats
+                .largest
+                .iter()
+                .map(|b| b.bundle_name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["character/member/01miku", "character/member/02rin"]
+        );
+        assert_eq!(
+            stats.fan_in,
+            vec![
+                BundleFanIn {
+                    bundle_name: "shader".into(),
+                    dependents: 2
+                },
+                BundleFanIn {
+                    bundle_name: "sound/scenario/voice/01miku".into(),
+                    dependents: 1
+                },
+            ]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use crate::Error;
 use regex::Regex;
+use serde::Serialize;
 use std::{
     fs::File,
     io::{BufReader, Cursor, Read, Seek},
@@ -10,7 +11,7 @@ const HASH_REGEX_PATTERN: &str =
     r".+(\d\.\d\.\d).+([0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})";
 const ANDROID_CANDIDATE: &str = r"6350e2ec327334c8a9b7f494f344a761";
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct AppInfo {
     pub version: Option<String>,
     pub hashes: Vec<String>,
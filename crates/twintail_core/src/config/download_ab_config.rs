@@ -1,26 +1,59 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
 use twintail_common::models::OptionalBuilder;
 use twintail_sekai::models::AssetbundleInfo;
 
+use crate::selection::BundleSelection;
+
 // constants
 const DEFAULT_UPDATE: bool = false;
+const DEFAULT_SKIP_EXISTING: bool = false;
+const DEFAULT_VERIFY: bool = true;
+const DEFAULT_MANIFEST: bool = true;
+const DEFAULT_WITH_DEPENDENCIES: bool = false;
+
+/// What to do with the files of assetbundles that no longer exist in the latest asset version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PruneMode {
+    /// the files are deleted
+    Delete,
+    /// the files are moved into the provided directory
+    Trash(PathBuf),
+    /// the files are only listed
+    DryRun,
+}
 
 /// Configuration for encryption and decryption.
 pub struct DownloadAbConfig {
     pub asset_version: Option<String>,
+    pub asset_hash: Option<String>,
     pub host_hash: Option<String>,
     pub info: Option<AssetbundleInfo>,
     pub update: bool,
-    pub filter: Option<String>,
+    pub selection: Option<BundleSelection>,
+    pub skip_existing: bool,
+    pub verify: bool,
+    pub manifest: bool,
+    pub prune: Option<PruneMode>,
+    pub with_dependencies: bool,
 }
 
 impl Default for DownloadAbConfig {
     fn default() -> Self {
         Self {
             asset_version: None,
+            asset_hash: None,
             host_hash: None,
             info: None,
             update: DEFAULT_UPDATE,
-            filter: None,
+            selection: None,
+            skip_existing: DEFAULT_SKIP_EXISTING,
+            verify: DEFAULT_VERIFY,
+            manifest: DEFAULT_MANIFEST,
+            prune: None,
+            with_dependencies: DEFAULT_WITH_DEPENDENCIES,
         }
     }
 }
@@ -47,6 +80,13 @@ impl DownloadAbConfigBuilder {
         self
     }
 
+    /// The hash of the assets to get. If not provided, it is retrieved by logging in,
+    /// so providing the hash of a previous login avoids logging in again
+    pub fn asset_hash(mut self, asset_hash: String) -> Self {
+        self.config.asset_hash = Some(asset_hash);
+        self
+    }
+
     /// Part of the URL used to download the assetbundles from. Uses the most recent if not provided
     pub fn host_hash(mut self, host_hash: String) -> Self {
         self.config.host_hash = Some(host_hash);
@@ -67,9 +107,49 @@ impl DownloadAbConfigBuilder {
         self
     }
 
-    /// Only assetbundles that match this regular expression will be downloaded
-    pub fn filter(mut self, filter: String) -> Self {
-        self.config.filter = Some(filter);
+    /// Only assetbundles that are selected by this selection will be downloaded
+    pub fn selection(mut self, selection: BundleSelection) -> Self {
+        self.config.selection = Some(selection);
+        self
+    }
+
+    /// If true, assetbundles that already exist in the output directory and match
+    /// their expected size and CRC will not be downloaded again
+    pub fn skip_existing(mut self, skip_existing: bool) -> Self {
+        self.config.skip_existing = skip_existing;
+        self
+    }
+
+    /// If true, the size and CRC of every downloaded assetbundle will be checked.
+    /// Assetbundles that do not match are downloaded again
+    pub fn verify(mut self, verify: bool) -> Self {
+        self.config.verify = verify;
+        self
+    }
+
+    /// If true, a manifest of the downloaded assetbundles will be written to the output directory.
+    ///
+    /// When no assetbundle info is provided and update is true, the manifest from a previous download
+    /// is used to only download the assetbundles that changed.
+    pub fn manifest(mut self, manifest: bool) -> Self {
+        self.config.manifest = manifest;
+        self
+    }
+
+    /// Prunes the files of assetbundles that were previously downloaded to the output directory,
+    /// but no longer exist in the latest asset version.
+    ///
+    /// Previously downloaded assetbundles are determined using the manifest in the output directory,
+    /// or the provided assetbundle info if update is true.
+    pub fn prune(mut self, prune_mode: PruneMode) -> Self {
+        self.config.prune = Some(prune_mode);
+        self
+    }
+
+    /// If true, the assetbundles that selected assetbundles depend on will also be downloaded,
+    /// along with the assetbundles that those depend on
+    pub fn with_dependencies(mut self, with_dependencies: bool) -> Self {
+        self.config.with_dependencies = with_dependencies;
         self
     }
 
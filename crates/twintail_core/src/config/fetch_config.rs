@@ -1,4 +1,8 @@
+use std::path::PathBuf;
+
+use super::retry_policy::RetryPolicy;
 use hmac::Hmac;
+use reqwest::Client;
 use sha2::Sha256;
 use twintail_common::{
     crypto::aes::AesConfig,
@@ -8,15 +12,16 @@ use twintail_common::{
     },
     utils::available_parallelism,
 };
-use twintail_sekai::url::{
-    UrlProvider, japan_provider::JapanUrlProvider, server_provider::ServerUrlProvider,
+use twintail_sekai::{
+    device_profile::DeviceProfile,
+    http_config::HttpConfig,
+    url::{UrlProvider, japan_provider::JapanUrlProvider, server_provider::ServerUrlProvider},
 };
 
 // constants
 const DEFAULT_SERVER: Server = Server::Japan;
 const DEFAULT_RECURSIVE: bool = false;
 const DEFAULT_PLATFORM: Platform = Platform::Android;
-const DEFAULT_RETRY: usize = 3;
 const DEFAULT_DECRYPT: bool = true;
 
 /// Configuration for encryption and decryption.
@@ -26,12 +31,19 @@ pub struct FetchConfig<P: UrlProvider> {
     pub concurrency: usize,
     pub recursive: bool,
     pub platform: Platform,
-    pub retry: usize,
+    pub retry_policy: RetryPolicy,
     pub decrypt: bool,
     pub url_provider: P,
     pub pretty_json: bool,
     pub version: Option<String>,
     pub hash: Option<String>,
+    pub server: Server,
+    pub credential_store: Option<PathBuf>,
+    pub maintenance_wait: Option<RetryPolicy>,
+    pub http_client: Option<Client>,
+    pub http_config: Option<HttpConfig>,
+    pub device_profile: Option<DeviceProfile>,
+    pub install_id_store: Option<PathBuf>,
 }
 
 impl FetchConfig<ServerUrlProvider> {
@@ -48,6 +60,12 @@ impl FetchConfig<ServerUrlProvider> {
     }
 }
 
+impl Default for FetchConfig<ServerUrlProvider> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<P: UrlProvider> FetchConfig<P> {
     /// Create a new FetchConfig with the provided version, hash, and url_provider using default values.
     pub fn new_with_provider(url_provider: P) -> Self {
@@ -58,11 +76,18 @@ impl<P: UrlProvider> FetchConfig<P> {
             concurrency: available_parallelism(),
             recursive: DEFAULT_RECURSIVE,
             platform: DEFAULT_PLATFORM,
-            retry: DEFAULT_RETRY,
+            retry_policy: RetryPolicy::default(),
             decrypt: DEFAULT_DECRYPT,
             pretty_json: false,
             version: None,
             hash: None,
+            server: DEFAULT_SERVER,
+            credential_store: None,
+            maintenance_wait: None,
+            http_client: None,
+            http_config: None,
+            device_profile: None,
+            install_id_store: None,
         }
     }
 }
@@ -83,15 +108,27 @@ impl FetchConfigBuilder<ServerUrlProvider> {
     }
 }
 
+impl Default for FetchConfigBuilder<ServerUrlProvider> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FetchConfigBuilder<ServerUrlProvider> {
     /// Sets the FetchConfig to use the
     ///  configurations required by the provided server.
     ///
+    /// The custom server has no built-in urls, so its url provider must be set with
+    /// [`FetchConfigBuilder::url_provider`], and is left unchanged by this method.
+    ///
     /// By default this will be the Japan server.
-    pub fn server(self, server: Server) -> FetchConfigBuilder<ServerUrlProvider> {
-        self.aes(server.get_aes_config())
-            .jwt(server.get_jwt_key())
-            .url_provider(ServerUrlProvider::from(server))
+    pub fn server(mut self, server: Server) -> FetchConfigBuilder<ServerUrlProvider> {
+        self.config.server = server;
+        let builder = self.aes(server.get_aes_config()).jwt(server.get_jwt_key());
+        match ServerUrlProvider::try_from(server) {
+            Ok(url_provider) => builder.url_provider(url_provider),
+            Err(_) => builder,
+        }
     }
 }
 
@@ -154,11 +191,20 @@ impl<P: UrlProvider> FetchConfigBuilder<P> {
         self
     }
 
-    /// Sets the amount of time to retry failed operations.
+    /// Sets the maximum number of times to retry failed operations.
     ///
     /// By default, this is 3 times.
     pub fn retry(mut self, retries: usize) -> Self {
-        self.config.retry = retries;
+        self.config.retry_policy.max_retries = retries;
+        self
+    }
+
+    /// Sets how failed operations are retried.
+    ///
+    /// This replaces the number of retries set with [`Self::retry`].
+    /// By default, this is [`RetryPolicy::default`].
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.config.retry_policy = retry_policy;
         self
     }
 
@@ -188,6 +234,64 @@ impl<P: UrlProvider> FetchConfigBuilder<P> {
         self
     }
 
+    /// Sets the path to a credential store where anonymous accounts will be saved.
+    ///
+    /// When set, a previously created account for the configured server and platform will be
+    /// logged into instead of signing up for a new account on every fetch.
+    ///
+    /// By default, no credential store is used.
+    pub fn credential_store(mut self, path: PathBuf) -> Self {
+        self.config.credential_store = Some(path);
+        self
+    }
+
+    /// Sets how to wait for maintenance of the game servers to end.
+    ///
+    /// When set, the game servers are polled with the delays of the policy until maintenance ends,
+    /// or until the policy's retries or deadline run out.
+    /// By default, operations fail immediately if the game servers are under maintenance.
+    pub fn wait_for_maintenance(mut self, poll_policy: RetryPolicy) -> Self {
+        self.config.maintenance_wait = Some(poll_policy);
+        self
+    }
+
+    /// Sets the HTTP client that requests are sent with.
+    ///
+    /// This takes priority over a configuration set with [`Self::http_config`].
+    /// By default, a new client is created.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.config.http_client = Some(client);
+        self
+    }
+
+    /// Sets the configuration of the HTTP client that requests are sent with,
+    /// such as a proxy, timeouts, DNS overrides and additional TLS roots.
+    ///
+    /// By default, a client without any configuration is used.
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.config.http_config = Some(http_config);
+        self
+    }
+
+    /// Sets the device that requests appear to be sent from.
+    ///
+    /// By default, this is the preset profile of the configured platform, with a new random install id.
+    pub fn device_profile(mut self, device_profile: DeviceProfile) -> Self {
+        self.config.device_profile = Some(device_profile);
+        self
+    }
+
+    /// Sets the path to a file where the install id of the device is saved.
+    ///
+    /// When set, the install id of the device profile is replaced with the saved install id,
+    /// which is generated and saved the first time that it is needed.
+    ///
+    /// By default, no install id is saved.
+    pub fn install_id_store(mut self, path: PathBuf) -> Self {
+        self.config.install_id_store = Some(path);
+        self
+    }
+
     /// Returns the FetchConfig that was constructed.
     pub fn build(self) -> FetchConfig<P> {
         self.config
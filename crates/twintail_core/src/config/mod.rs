@@ -1,3 +1,5 @@
 pub mod crypt_config;
 pub mod download_ab_config;
 pub mod fetch_config;
+pub mod retry_policy;
+pub mod verify_config;
@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+use twintail_common::models::OptionalBuilder;
+
+// constants
+const DEFAULT_MAX_RETRIES: usize = 3;
+const DEFAULT_BACKOFF: Backoff = Backoff::Exponential;
+const DEFAULT_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(30);
+const DEFAULT_JITTER: bool = true;
+
+/// How the delay between retries changes after every failed attempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backoff {
+    /// every retry waits for the same delay
+    Fixed,
+    /// the delay doubles after every retry
+    Exponential,
+}
+
+/// Configuration for retrying failed operations.
+///
+/// Operations are only retried if their error is retryable.
+/// If the server asks for a longer delay with a ``Retry-After`` header, that delay is waited instead,
+/// even if it is longer than ``max_delay``, which only limits the backoff delays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    pub backoff: Backoff,
+    /// the delay before the first retry
+    pub delay: Duration,
+    /// the longest delay between two retries
+    pub max_delay: Duration,
+    /// whether delays are randomized, so that simultaneous failures are not retried at the same time
+    pub jitter: bool,
+    /// the maximum amount of time spent on an operation, including retries
+    pub deadline: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            backoff: DEFAULT_BACKOFF,
+            delay: DEFAULT_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            jitter: DEFAULT_JITTER,
+            deadline: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Create a default builder for the RetryPolicy struct.
+    pub fn builder() -> RetryPolicyBuilder {
+        RetryPolicyBuilder::default()
+    }
+
+    /// Returns how long to wait before a retry, given the number of retries that came before it.
+    ///
+    /// With jitter, the delay is a random duration between half of the delay and the full delay.
+    pub fn delay(&self, retries: usize) -> Duration {
+        let delay = match self.backoff {
+            Backoff::Fixed => self.delay,
+            Backoff::Exponential => self
+                .delay
+                .saturating_mul(2u32.saturating_pow(retries.try_into().unwrap_or(u32::MAX))),
+        }
+        .min(self.max_delay);
+
+        if self.jitter {
+            delay.mul_f64(0.5 + fastrand::f64() * 0.5)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Builder for RetryPolicy
+#[derive(Default)]
+pub struct RetryPolicyBuilder {
+    policy: RetryPolicy,
+}
+
+impl OptionalBuilder for RetryPolicyBuilder {}
+
+impl RetryPolicyBuilder {
+    /// Sets the maximum number of times to retry a failed operation.
+    ///
+    /// By default, this is 3 times.
+    pub fn max_retries(mut self, max_retries: usize) -> Self {
+        self.policy.max_retries = max_retries;
+        self
+    }
+
+    /// Sets how the delay between retries changes after every failed attempt.
+    ///
+    /// By default, this is ``Backoff::Exponential``.
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.policy.backoff = backoff;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    ///
+    /// By default, this is 500 milliseconds.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.policy.delay = delay;
+        self
+    }
+
+    /// Sets the longest delay between two retries.
+    ///
+    /// By default, this is 30 seconds.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.policy.max_delay = max_delay;
+        self
+    }
+
+    /// Sets whether delays between retries are randomized.
+    ///
+    /// By default, this is true.
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.policy.jitter = jitter;
+        self
+    }
+
+    /// Sets the maximum amount of time spent on an operation, including retries.
+    ///
+    /// A retry that would start after the deadline is not attempted.
+    /// By default, there is no deadline.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.policy.deadline = Some(deadline);
+        self
+    }
+
+    /// Returns the RetryPolicy that was constructed.
+    pub fn build(self) -> RetryPolicy {
+        self.policy
+    }
+}
@@ -0,0 +1,72 @@
+use twintail_common::{
+    models::{OptionalBuilder, enums::Platform},
+    utils::available_parallelism,
+};
+
+// constants
+const DEFAULT_PLATFORM: Platform = Platform::Android;
+const DEFAULT_ENCRYPTED: bool = false;
+
+/// Configuration for verifying local assetbundles.
+pub struct VerifyConfig {
+    pub platform: Platform,
+    pub concurrency: usize,
+    pub encrypted: bool,
+}
+
+impl Default for VerifyConfig {
+    fn default() -> Self {
+        Self {
+            platform: DEFAULT_PLATFORM,
+            concurrency: available_parallelism(),
+            encrypted: DEFAULT_ENCRYPTED,
+        }
+    }
+}
+
+impl VerifyConfig {
+    /// Create a default builder for the VerifyConfig struct.
+    pub fn builder() -> VerifyConfigBuilder {
+        VerifyConfigBuilder::default()
+    }
+}
+
+/// Builder for VerifyConfig
+#[derive(Default)]
+pub struct VerifyConfigBuilder {
+    config: VerifyConfig,
+}
+
+impl OptionalBuilder for VerifyConfigBuilder {}
+
+impl VerifyConfigBuilder {
+    /// Sets the platform that the assetbundles were downloaded for.
+    ///
+    /// By default, this is ``Platform::Android``
+    pub fn platform(mut self, platform: Platform) -> Self {
+        self.config.platform = platform;
+        self
+    }
+
+    /// Sets the maximum number of files that will be checked simultaneously.
+    ///
+    /// By default, this is the result of [`crate::utils::available_parallelism`],
+    /// the machine's available parallelism.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.config.concurrency = concurrency;
+        self
+    }
+
+    /// Sets whether the assetbundles are expected to be encrypted.
+    ///
+    /// By default, this is false.
+    pub fn encrypted(mut self, encrypted: bool) -> Self {
+        self.config.encrypted = encrypted;
+        self
+    }
+
+    /// Returns the VerifyConfig that was constructed.
+    pub fn build(self) -> VerifyConfig {
+        self.config
+    }
+}
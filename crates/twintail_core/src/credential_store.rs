@@ -0,0 +1,280 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use twintail_common::models::enums::{Platform, Server};
+use twintail_sekai::url::UrlProvider;
+
+use crate::Error;
+#[cfg(not(unix))]
+use crate::fs::write_file;
+
+const DEFAULT_DIR_NAME: &str = "twintail";
+const DEFAULT_FILE_NAME: &str = "credentials.json";
+/// the permissions of the credential store file on unix, readable and writable only by its owner
+#[cfg(unix)]
+const FILE_MODE: u32 = 0o600;
+
+/// An anonymous account that was created on a game server.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredAccount {
+    pub user_id: usize,
+    pub credential: String,
+}
+
+/// Identifies the game server that stored accounts belong to.
+///
+/// Custom servers are told apart by the host of their API,
+/// so that every custom server keeps its own accounts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccountServer(String);
+
+impl AccountServer {
+    /// Creates the AccountServer of a server whose requests are sent to the urls of url_provider.
+    pub fn new(server: Server, url_provider: &impl UrlProvider) -> Self {
+        match server {
+            Server::Japan => Self("Japan".into()),
+            Server::Global => Self("Global".into()),
+            Server::Custom => {
+                let user_url = url_provider.user();
+                let api_host = Url::parse(&user_url)
+                    .ok()
+                    .and_then(|url| {
+                        url.host_str().map(|host| match url.port() {
+                            Some(port) => format!("{host}:{port}"),
+                            None => host.to_string(),
+                        })
+                    })
+                    .unwrap_or(user_url);
+                Self(format!("Custom:{api_host}"))
+            }
+        }
+    }
+}
+
+impl fmt::Display for AccountServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Persists anonymous accounts per server and platform,
+/// so that they can be logged into again instead of signing up for a new account every time.
+#[derive(Debug, Default)]
+pub struct CredentialStore {
+    path: PathBuf,
+    accounts: HashMap<AccountServer, HashMap<Platform, StoredAccount>>,
+}
+
+impl CredentialStore {
+    /// Returns the default location of the credential store.
+    ///
+    /// This is a file inside of the user's configuration directory.
+    /// Returns None if the configuration directory could not be determined.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(DEFAULT_DIR_NAME).join(DEFAULT_FILE_NAME))
+    }
+
+    /// Loads a credential store from the provided path.
+    ///
+    /// If no file exists at the path, an empty store is returned.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        let accounts = match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            accounts,
+        })
+    }
+
+    /// Gets the stored account for a server and platform.
+    pub fn get(&self, server: &AccountServer, platform: Platform) -> Option<&StoredAccount> {
+        self.accounts
+            .get(server)
+            .and_then(|platforms| platforms.get(&platform))
+    }
+
+    /// Stores an account for a server and platform, replacing any account that was previously stored.
+    pub fn insert(&mut self, server: AccountServer, platform: Platform, account: StoredAccount) {
+        self.accounts
+            .entry(server)
+            .or_default()
+            .insert(platform, account);
+    }
+
+    /// Removes the stored account for a server and platform, returning it if it existed.
+    pub fn remove(&mut self, server: &AccountServer, platform: Platform) -> Option<StoredAccount> {
+        self.accounts
+            .get_mut(server)
+            .and_then(|platforms| platforms.remove(&platform))
+    }
+
+    /// Writes the credential store to the path that it was loaded from.
+    ///
+    /// Any missing directories will be created.
+    /// On unix, the file is only readable and writable by its owner, since it contains account credentials.
+    pub async fn save(&self) -> Result<(), Error> {
+        let serialized = serde_json::to_vec_pretty(&self.accounts)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            use tokio::io::AsyncWriteExt;
+
+            if let Some(parent) = self.path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut file = tokio::fs::File::options()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .mode(FILE_MODE)
+                .open(&self.path)
+                .await?;
+            // the mode only applies to new files, so a file that already existed is restricted too
+            file.set_permissions(std::fs::Permissions::from_mode(FILE_MODE))
+                .await?;
+            file.write_all(&serialized).await?;
+        }
+
+        #[cfg(not(unix))]
+        write_file(&self.path, &serialized).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use twintail_sekai::url::{
+        server_provider::ServerUrlProvider, template_provider::TemplateUrlProvider,
+    };
+
+    fn get_account_server(server: Server) -> AccountServer {
+        AccountServer::new(server, &ServerUrlProvider::try_from(server).unwrap())
+    }
+
+    fn get_custom_server(game_host: &str) -> AccountServer {
+        let provider = TemplateUrlProvider::from_toml(&format!(
+            "[hosts]\ngame = \"{game_host}\"\nassetbundle = \"https://assets.example.com\""
+        ))
+        .unwrap();
+        AccountServer::new(Server::Custom, &provider)
+    }
+
+    #[tokio::test]
+    async fn test_credential_store_missing_file() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let store = CredentialStore::load(dir.path().join("credentials.json")).await?;
+
+        assert!(
+            store
+                .get(&get_account_server(Server::Japan), Platform::Android)
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_credential_store_save_load() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let store_path = dir.path().join("nested").join("credentials.json");
+
+        let account = StoredAccount {
+            user_id: 3939,
+            credential: "credential".into(),
+        };
+
+        let japan = get_account_server(Server::Japan);
+        let mut store = CredentialStore::load(&store_path).await?;
+        store.insert(japan.clone(), Platform::Android, account.clone());
+        store.save().await?;
+
+        let mut loaded = CredentialStore::load(&store_path).await?;
+        assert_eq!(loaded.get(&japan, Platform::Android), Some(&account));
+        assert!(loaded.get(&japan, Platform::Ios).is_none());
+        assert!(
+            loaded
+                .get(&get_account_server(Server::Global), Platform::Android)
+                .is_none()
+        );
+
+        assert_eq!(loaded.remove(&japan, Platform::Android), Some(account));
+        assert!(loaded.get(&japan, Platform::Android).is_none());
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_credential_store_permissions() -> Result<(), Error> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir()?;
+        let store_path = dir.path().join("credentials.json");
+
+        // a file that already exists with looser permissions is restricted when saved
+        tokio::fs::write(&store_path, "{}").await?;
+        std::fs::set_permissions(&store_path, std::fs::Permissions::from_mode(0o644))?;
+
+        let mut store = CredentialStore::load(&store_path).await?;
+        store.insert(
+            get_account_server(Server::Japan),
+            Platform::Android,
+            StoredAccount {
+                user_id: 3939,
+                credential: "credential".into(),
+            },
+        );
+        store.save().await?;
+
+        let mode = tokio::fs::metadata(&store_path).await?.permissions().mode();
+        assert_eq!(mode & 0o777, FILE_MODE);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_credential_store_custom_servers() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let store_path = dir.path().join("credentials.json");
+
+        let staging = get_custom_server("https://staging.example.com");
+        let private = get_custom_server("https://private.example.com:8443");
+        assert_ne!(staging, private);
+        // the same host is the same server, even if the paths of its urls differ
+        assert_eq!(
+            staging,
+            get_custom_server("https://staging.example.com/api")
+        );
+
+        let account = StoredAccount {
+            user_id: 3939,
+            credential: "credential".into(),
+        };
+        let mut store = CredentialStore::load(&store_path).await?;
+        store.insert(staging.clone(), Platform::Android, account.clone());
+        store.save().await?;
+
+        // every custom server keeps its own accounts
+        let loaded = CredentialStore::load(&store_path).await?;
+        assert_eq!(loaded.get(&staging, Platform::Android), Some(&account));
+        assert!(loaded.get(&private, Platform::Android).is_none());
+
+        Ok(())
+    }
+}
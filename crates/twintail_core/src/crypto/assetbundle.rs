@@ -4,23 +4,27 @@ use std::{
 };
 
 use futures::{StreamExt, stream};
+use serde::Serialize;
 use tokio::{
     fs::File,
-    io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufReader},
-    sync::watch::{self},
+    io::{
+        AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufReader,
+        BufWriter,
+    },
 };
 use twintail_common::models::enums::CryptOperation;
 
 use crate::{
     Error,
-    fs::{scan_path, write_file},
+    fs::{create_file, scan_path, write_file},
+    state::StateSender,
 };
 
 use super::{CryptAssetbundlePathState, CryptState};
 
 const UNITY_ASSETBUNDLE_MAGIC: &[u8] = b"\x55\x6e\x69\x74\x79\x46";
-const SEKAI_ASSETBUNDLE_MAGIC: &[u8] = b"\x10\x00\x00\x00";
-const HEADER_SIZE: usize = 128;
+pub(crate) const SEKAI_ASSETBUNDLE_MAGIC: &[u8] = b"\x10\x00\x00\x00";
+pub(crate) const HEADER_SIZE: usize = 128;
 const CHUNK_SIZE: usize = 65536;
 const HEADER_BLOCK_SIZE: usize = 8;
 const DECRYPT_SIZE: usize = 5;
@@ -31,6 +35,19 @@ pub struct AbCryptArgs {
     pub operation: CryptOperation,
 }
 
+/// Flips the encrypted bytes of an assetbundle header in-place.
+///
+/// If the provided header is shorter than [`HEADER_SIZE`], only the bytes that exist are flipped.
+fn flip_header(header: &mut [u8]) {
+    for i in (0..HEADER_SIZE.min(header.len())).step_by(HEADER_BLOCK_SIZE) {
+        for j in 0..DECRYPT_SIZE {
+            if i + j < header.len() {
+                header[i + j] = !header[i + j];
+            }
+        }
+    }
+}
+
 /// Flips specific bytes in the provided reader's header into the provided buffer.
 ///
 /// Writes the rest of the file to the provided buffer.
@@ -38,11 +55,7 @@ async fn crypt(reader: &mut (impl AsyncRead + Unpin), out_buf: &mut Vec<u8>) ->
     // flip header bytes
     let mut header_buf = [0u8; HEADER_SIZE];
     reader.read_exact(&mut header_buf).await?;
-    for i in (0..HEADER_SIZE).step_by(HEADER_BLOCK_SIZE) {
-        for j in 0..DECRYPT_SIZE {
-            header_buf[i + j] = !header_buf[i + j];
-        }
-    }
+    flip_header(&mut header_buf);
     out_buf.write_all(&header_buf).await?;
 
     // write the rest of the file
@@ -73,14 +86,169 @@ pub async fn decrypt_in_place(buffer: &mut Vec<u8>) -> Result<(), Error> {
     buffer.drain(..SEKAI_ASSETBUNDLE_MAGIC.len());
 
     // Flip header bytes in-place
-    for i in (0..HEADER_SIZE.min(buffer.len())).step_by(HEADER_BLOCK_SIZE) {
-        for j in 0..DECRYPT_SIZE.min(HEADER_BLOCK_SIZE) {
-            if i + j < buffer.len() {
-                buffer[i + j] = !buffer[i + j];
-            }
+    flip_header(buffer);
+
+    Ok(())
+}
+
+/// Decrypts an assetbundle that is received in chunks, writing the decrypted bytes as they become available.
+///
+/// Only the magic and the header of an assetbundle are encrypted,
+/// so at most [`HEADER_SIZE`] bytes are ever buffered.
+///
+/// Assetbundles that are not encrypted are written unchanged.
+#[derive(Default)]
+pub struct StreamDecrypter {
+    prefix: Vec<u8>,
+    prefix_written: bool,
+}
+
+impl StreamDecrypter {
+    /// Creates a new StreamDecrypter for a single assetbundle.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decrypts the next chunk of the assetbundle into the provided writer.
+    pub async fn write_chunk(
+        &mut self,
+        chunk: &[u8],
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> Result<(), Error> {
+        if self.prefix_written {
+            writer.write_all(chunk).await?;
+            return Ok(());
+        }
+
+        // buffer until the magic and header have been received
+        let prefix_size = SEKAI_ASSETBUNDLE_MAGIC.len() + HEADER_SIZE;
+        let take = (prefix_size - self.prefix.len()).min(chunk.len());
+        self.prefix.extend_from_slice(&chunk[..take]);
+
+        if self.prefix.len() == prefix_size {
+            self.write_prefix(writer).await?;
+            writer.write_all(&chunk[take..]).await?;
         }
+
+        Ok(())
     }
 
+    /// Writes any bytes that are still buffered.
+    ///
+    /// This must be called once the entire assetbundle has been passed to [`Self::write_chunk`].
+    pub async fn finish(mut self, writer: &mut (impl AsyncWrite + Unpin)) -> Result<(), Error> {
+        if !self.prefix_written {
+            self.write_prefix(writer).await?;
+        }
+        Ok(())
+    }
+
+    /// Decrypts and writes the buffered magic and header.
+    async fn write_prefix(&mut self, writer: &mut (impl AsyncWrite + Unpin)) -> Result<(), Error> {
+        self.prefix_written = true;
+
+        if self.prefix.starts_with(SEKAI_ASSETBUNDLE_MAGIC) {
+            let header = &mut self.prefix[SEKAI_ASSETBUNDLE_MAGIC.len()..];
+            flip_header(header);
+            writer.write_all(header).await?;
+        } else {
+            writer.write_all(&self.prefix).await?;
+        }
+
+        self.prefix = Vec::new();
+        Ok(())
+    }
+}
+
+/// Whether an assetbundle file is encrypted, determined by the magic that it starts with.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EncryptionState {
+    /// The file starts with the magic of an encrypted assetbundle
+    Encrypted,
+    /// The file starts with the magic of a Unity assetbundle
+    Decrypted,
+    /// The file does not start with any known magic
+    Unknown,
+}
+
+/// Reads the magic of the file at the provided path to determine its [`EncryptionState`].
+pub async fn encryption_state(path: &Path) -> Result<EncryptionState, Error> {
+    let file = File::open(path).await?;
+    let mut magic_buf = Vec::with_capacity(UNITY_ASSETBUNDLE_MAGIC.len());
+    file.take(UNITY_ASSETBUNDLE_MAGIC.len() as u64)
+        .read_to_end(&mut magic_buf)
+        .await?;
+
+    if magic_buf.starts_with(SEKAI_ASSETBUNDLE_MAGIC) {
+        Ok(EncryptionState::Encrypted)
+    } else if magic_buf == UNITY_ASSETBUNDLE_MAGIC {
+        Ok(EncryptionState::Decrypted)
+    } else {
+        Ok(EncryptionState::Unknown)
+    }
+}
+
+/// Returns true if the file at the provided path starts with the magic of an encrypted assetbundle.
+pub async fn is_encrypted(path: &Path) -> Result<bool, Error> {
+    Ok(encryption_state(path).await? == EncryptionState::Encrypted)
+}
+
+/// Computes the CRC32 of the assetbundle file at the provided path.
+///
+/// If reencrypt is true, the file is assumed to be a decrypted assetbundle,
+/// and the CRC32 is computed over the encrypted bytes that it was decrypted from.
+/// The file itself is not modified.
+pub async fn crc32(path: &Path, reencrypt: bool) -> Result<u32, Error> {
+    let mut reader = BufReader::new(File::open(path).await?);
+    let mut hasher = crc32fast::Hasher::new();
+
+    if reencrypt {
+        hasher.update(SEKAI_ASSETBUNDLE_MAGIC);
+
+        let mut header_buf = Vec::with_capacity(HEADER_SIZE);
+        (&mut reader)
+            .take(HEADER_SIZE as u64)
+            .read_to_end(&mut header_buf)
+            .await?;
+        flip_header(&mut header_buf);
+        hasher.update(&header_buf);
+    }
+
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let bytes_read = reader.read(&mut chunk).await?;
+        if bytes_read == 0 {
+            break;
+        }
+        hasher.update(&chunk[..bytes_read]);
+    }
+
+    Ok(hasher.finalize())
+}
+
+/// Decrypts the assetbundle at in_path into out_path, reading and writing it in chunks.
+///
+/// Assetbundles that are not encrypted are copied unchanged.
+/// Truncates and overwrites the file at out_path.
+pub async fn decrypt_file_chunked(in_path: &Path, out_path: &Path) -> Result<(), Error> {
+    let mut reader = BufReader::new(File::open(in_path).await?);
+    let mut writer = BufWriter::new(create_file(out_path).await?);
+    let mut decrypter = StreamDecrypter::new();
+
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let bytes_read = reader.read(&mut chunk).await?;
+        if bytes_read == 0 {
+            break;
+        }
+        decrypter
+            .write_chunk(&chunk[..bytes_read], &mut writer)
+            .await?;
+    }
+    decrypter.finish(&mut writer).await?;
+    writer.flush().await?;
+
     Ok(())
 }
 
@@ -157,14 +325,14 @@ pub async fn crypt_path(
     in_path: impl AsRef<Path>,
     out_path: Option<impl AsRef<Path>>,
     crypt_args: &AbCryptArgs,
-    state_sender: &watch::Sender<CryptState>,
+    state_sender: &StateSender<CryptState>,
 ) -> Result<(usize, usize), Error> {
     let in_path = in_path.as_ref();
     let out_path = out_path.as_ref().map(|p| p.as_ref()).unwrap_or(in_path);
     let in_place = in_path == out_path;
 
     // get the paths we need to encrypt
-    state_sender.send_replace(CryptState::AssetbundlePath(CryptAssetbundlePathState::Scan));
+    state_sender.send(CryptState::AssetbundlePath(CryptAssetbundlePathState::Scan));
 
     let in_paths = scan_path(in_path, crypt_args.recursive).await?;
 
@@ -184,14 +352,14 @@ pub async fn crypt_path(
 
     // asynchronously encrypt the files
     let total_path_count = in_out_paths.len();
-    state_sender.send_replace(CryptState::AssetbundlePath(
+    state_sender.send(CryptState::AssetbundlePath(
         CryptAssetbundlePathState::Crypt(total_path_count),
     ));
 
     let decrypt_result: Vec<Result<(), Error>> = stream::iter(&in_out_paths)
         .map(|paths| async {
             let result = crypt_file(&paths.0, &paths.1, &crypt_args.operation).await;
-            state_sender.send_replace(CryptState::AssetbundlePath(
+            state_sender.send(CryptState::AssetbundlePath(
                 CryptAssetbundlePathState::CryptFile,
             ));
             result
@@ -205,7 +373,7 @@ pub async fn crypt_path(
         .count();
 
     // stop progress bar & print the success message
-    state_sender.send_replace(CryptState::AssetbundlePath(
+    state_sender.send(CryptState::AssetbundlePath(
         CryptAssetbundlePathState::Finish,
     ));
 
@@ -228,7 +396,7 @@ mod tests {
         // Create a mock AssetBundle
         let mut mock_bundle = vec![];
         mock_bundle.extend(UNITY_ASSETBUNDLE_MAGIC);
-        mock_bundle.extend((0..CHUNK_SIZE).into_iter().map(|_| 0x0));
+        mock_bundle.extend((0..CHUNK_SIZE).map(|_| 0x0));
 
         write(&input_path, mock_bundle).await?;
 
@@ -251,7 +419,7 @@ mod tests {
         // Create a mock encrypted AssetBundle
         let mut mock_bundle = vec![];
         mock_bundle.extend(SEKAI_ASSETBUNDLE_MAGIC);
-        mock_bundle.extend((0..CHUNK_SIZE).into_iter().map(|_| 0x0));
+        mock_bundle.extend((0..CHUNK_SIZE).map(|_| 0x0));
 
         // decrypt
         decrypt_in_place(&mut mock_bundle).await?;
@@ -260,6 +428,85 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_stream_decrypter() -> Result<(), Error> {
+        // Create a mock encrypted AssetBundle
+        let mut mock_bundle = vec![];
+        mock_bundle.extend(SEKAI_ASSETBUNDLE_MAGIC);
+        mock_bundle.extend((0..CHUNK_SIZE).map(|i| i as u8));
+
+        let mut expected = mock_bundle.clone();
+        decrypt_in_place(&mut expected).await?;
+
+        // decrypt in chunks that are smaller than the header
+        let mut decrypted = Vec::new();
+        let mut decrypter = StreamDecrypter::new();
+        for chunk in mock_bundle.chunks(37) {
+            decrypter.write_chunk(chunk, &mut decrypted).await?;
+        }
+        decrypter.finish(&mut decrypted).await?;
+
+        assert_eq!(decrypted, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_decrypter_not_encrypted() -> Result<(), Error> {
+        let mock_file = vec![0x00, 0x01, 0x02, 0x03, 0x05, 0x12];
+
+        let mut output = Vec::new();
+        let mut decrypter = StreamDecrypter::new();
+        decrypter.write_chunk(&mock_file, &mut output).await?;
+        decrypter.finish(&mut output).await?;
+
+        assert_eq!(output, mock_file);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_decrypt_file_chunked() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let input_path = dir.path().join("input.bundle");
+        let decrypted_path = dir.path().join("decrypted.bundle");
+
+        // Create a mock encrypted AssetBundle that spans multiple chunks
+        let mut mock_bundle = vec![];
+        mock_bundle.extend(SEKAI_ASSETBUNDLE_MAGIC);
+        mock_bundle.extend((0..CHUNK_SIZE * 2 + 39).map(|i| i as u8));
+        write(&input_path, &mock_bundle).await?;
+
+        decrypt_file_chunked(&input_path, &decrypted_path).await?;
+
+        let mut expected = mock_bundle;
+        decrypt_in_place(&mut expected).await?;
+        assert_eq!(tokio::fs::read(&decrypted_path).await?, expected);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_crc32_reencrypt() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let encrypted_path = dir.path().join("encrypted.bundle");
+        let decrypted_path = dir.path().join("decrypted.bundle");
+
+        let mut mock_bundle = vec![];
+        mock_bundle.extend(SEKAI_ASSETBUNDLE_MAGIC);
+        mock_bundle.extend((0..CHUNK_SIZE + 39).map(|i| i as u8));
+        write(&encrypted_path, &mock_bundle).await?;
+        decrypt_file_chunked(&encrypted_path, &decrypted_path).await?;
+
+        let expected = crc32fast::hash(&mock_bundle);
+        assert!(is_encrypted(&encrypted_path).await?);
+        assert!(!is_encrypted(&decrypted_path).await?);
+        assert_eq!(crc32(&encrypted_path, false).await?, expected);
+        assert_eq!(crc32(&decrypted_path, true).await?, expected);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_decrypt_not_encrypted() -> Result<(), Error> {
         let dir = tempdir()?;
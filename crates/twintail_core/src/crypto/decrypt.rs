@@ -5,7 +5,6 @@ use serde_json::Value;
 use tokio::{
     fs::{File, read},
     io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncWrite},
-    sync::watch::{self, Receiver, Sender},
 };
 use twintail_common::{
     crypto::{aes::AesConfig, aes_msgpack},
@@ -17,6 +16,7 @@ use crate::{
     config::crypt_config::CryptConfig,
     crypto::assetbundle::{self, AbCryptArgs},
     fs::{extract_suitemaster_file, scan_path, write_file},
+    state::{self, StateReceiver, StateSender},
 };
 
 use super::{CryptState, DecryptSuitePathState};
@@ -25,13 +25,13 @@ use super::{CryptState, DecryptSuitePathState};
 #[derive(Default)]
 pub struct Decrypter {
     config: CryptConfig,
-    state_sender: Sender<CryptState>,
+    state_sender: StateSender<CryptState>,
 }
 
 impl Decrypter {
     /// Creates a new Decrypter that will use the provided configuration.
-    pub fn new(config: CryptConfig) -> (Self, Receiver<CryptState>) {
-        let (state_sender, state_receiver) = watch::channel(CryptState::default());
+    pub fn new(config: CryptConfig) -> (Self, StateReceiver<CryptState>) {
+        let (state_sender, state_receiver) = state::channel();
         (
             Self {
                 config,
@@ -117,7 +117,7 @@ impl Decrypter {
         // create decrypt progress bar
         let total_path_count = to_decrypt_paths.len();
         self.state_sender
-            .send_replace(CryptState::DecryptSuitePath(DecryptSuitePathState::Start(
+            .send(CryptState::DecryptSuitePath(DecryptSuitePathState::Start(
                 total_path_count,
             )));
 
@@ -133,7 +133,7 @@ impl Decrypter {
                 )
                 .await;
                 self.state_sender
-                    .send_replace(CryptState::DecryptSuitePath(DecryptSuitePathState::Decrypt));
+                    .send(CryptState::DecryptSuitePath(DecryptSuitePathState::Decrypt));
                 decrypt_result
             })
             .buffer_unordered(self.config.concurrency)
@@ -147,7 +147,7 @@ impl Decrypter {
 
         // print the result
         self.state_sender
-            .send_replace(CryptState::DecryptSuitePath(DecryptSuitePathState::Finish));
+            .send(CryptState::DecryptSuitePath(DecryptSuitePathState::Finish));
 
         Ok(total_path_count)
     }
@@ -195,7 +195,7 @@ mod tests {
                 "kasane": 39
             }
             "#;
-        let file_json_value: Value = serde_json::from_str(&file_json)?;
+        let file_json_value: Value = serde_json::from_str(file_json)?;
         let file_json_aes_msgpack_bytes = aes_msgpack::into_vec(&file_json_value, &aes_config)?;
 
         let in_file_path = in_dir.path().join("file");
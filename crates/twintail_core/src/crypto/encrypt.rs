@@ -1,10 +1,7 @@
 use std::{collections::HashMap, path::Path};
 
 use rayon::iter::{ParallelBridge, ParallelIterator};
-use tokio::{
-    io::{AsyncRead, AsyncSeek, AsyncWrite},
-    sync::watch,
-};
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite};
 use twintail_common::{
     crypto::{aes::AesConfig, aes_msgpack},
     models::{enums::CryptOperation, serde::ValueF32},
@@ -15,6 +12,7 @@ use crate::{
     crypto::assetbundle::{self, AbCryptArgs},
     error::Error,
     fs::{deserialize_files, scan_path, write_file},
+    state::{self, StateReceiver, StateSender},
 };
 
 use super::{CryptState, EncryptSuitePathState, EncryptSuiteValuesState};
@@ -28,13 +26,13 @@ type DeserializedSuiteFile = (String, ValueF32);
 /// A struct responsible for encryption.
 pub struct Encrypter {
     config: CryptConfig,
-    state_sender: watch::Sender<CryptState>,
+    state_sender: StateSender<CryptState>,
 }
 
 impl Encrypter {
     /// Creates a new Encrypter that will use the provided configuration.
-    pub fn new(config: CryptConfig) -> (Self, watch::Receiver<CryptState>) {
-        let (state_sender, recv) = watch::channel(CryptState::NoState);
+    pub fn new(config: CryptConfig) -> (Self, StateReceiver<CryptState>) {
+        let (state_sender, recv) = state::channel();
         (
             Self {
                 config,
@@ -86,10 +84,9 @@ impl Encrypter {
         split: usize,
     ) -> Result<usize, Error> {
         // split into chunks and serialize
-        self.state_sender
-            .send_replace(CryptState::EncryptSuiteValues(
-                EncryptSuiteValuesState::SerializeStart(values.len()),
-            ));
+        self.state_sender.send(CryptState::EncryptSuiteValues(
+            EncryptSuiteValuesState::SerializeStart(values.len()),
+        ));
 
         let deserialized_len = values.len();
         let chunk_size = {
@@ -101,10 +98,9 @@ impl Encrypter {
             .chunks(chunk_size)
             .par_bridge()
             .map(|chunk| {
-                self.state_sender
-                    .send_replace(CryptState::EncryptSuiteValues(
-                        EncryptSuiteValuesState::Serialize(chunk_size),
-                    ));
+                self.state_sender.send(CryptState::EncryptSuiteValues(
+                    EncryptSuiteValuesState::Serialize(chunk_size),
+                ));
                 match serialize_values(chunk, &self.config.aes_config) {
                     Ok(bytes) => Ok(bytes),
                     Err(err) => Err(err),
@@ -112,10 +108,9 @@ impl Encrypter {
             })
             .collect();
 
-        self.state_sender
-            .send_replace(CryptState::EncryptSuiteValues(
-                EncryptSuiteValuesState::Finish,
-            ));
+        self.state_sender.send(CryptState::EncryptSuiteValues(
+            EncryptSuiteValuesState::Finish,
+        ));
 
         // write to out directory
         for (n, result) in chunks.into_iter().enumerate() {
@@ -144,7 +139,7 @@ impl Encrypter {
         split: usize,
     ) -> Result<usize, Error> {
         self.state_sender
-            .send_replace(CryptState::EncryptSuitePath(EncryptSuitePathState::Process));
+            .send(CryptState::EncryptSuitePath(EncryptSuitePathState::Process));
 
         // deserialize all paths to [`serde_json::Value`]s.
         let deserialized_files: Vec<(_, ValueF32)> = self.deserialize_suite_path(in_path).await?;
@@ -334,7 +329,7 @@ mod tests {
         write(&in_file_path, in_file_json).await?;
 
         // generate expected value
-        let in_file_json_value: Value = serde_json::from_str(&in_file_json)?;
+        let in_file_json_value: Value = serde_json::from_str(in_file_json)?;
 
         // encrypt in_file
         let out_file_path = in_dir.path().join("file");
@@ -2,7 +2,10 @@ pub mod assetbundle;
 pub mod decrypt;
 pub mod encrypt;
 
-#[derive(Clone, Copy)]
+use serde::Serialize;
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EncryptSuiteValuesState {
     /// the provided number of encrypted/decrypted files are being serialized
     SerializeStart(usize),
@@ -12,13 +15,15 @@ pub enum EncryptSuiteValuesState {
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EncryptSuitePathState {
     /// files are being processed
     Process,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DecryptSuitePathState {
     /// The provided number of files are being decrypted
     Start(usize),
@@ -28,7 +33,8 @@ pub enum DecryptSuitePathState {
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CryptAssetbundlePathState {
     /// Files in the provided path are being scanned
     Scan,
@@ -40,17 +46,13 @@ pub enum CryptAssetbundlePathState {
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CryptState {
+    #[default]
     NoState,
     EncryptSuiteValues(EncryptSuiteValuesState),
     EncryptSuitePath(EncryptSuitePathState),
     DecryptSuitePath(DecryptSuitePathState),
     AssetbundlePath(CryptAssetbundlePathState),
 }
-
-impl Default for CryptState {
-    fn default() -> Self {
-        Self::NoState
-    }
-}
@@ -1,3 +1,6 @@
+use std::{io, time::Duration};
+
+use serde::Serializer;
 use thiserror::Error;
 use twintail_common::multi_error;
 
@@ -42,7 +45,60 @@ pub enum Error {
     #[error("not enough space: {0}")]
     NotEnoughSpace(String),
 
+    #[error("server mismatch: {0}")]
+    ServerMismatch(String),
+
+    #[error("the game servers are under maintenance")]
+    Maintenance,
+
+    #[error("corrupt assetbundle: {0}")]
+    CorruptAssetbundle(String),
+
     #[error("multiple errors: {0}")]
     Multi(String),
 }
 multi_error!(Error);
+
+impl Error {
+    /// Returns true if the operation that caused this error may succeed if it is attempted again.
+    ///
+    /// See [`twintail_sekai::Error::is_retryable`] for which request errors are retryable.
+    /// Only transient io errors are retryable, since errors such as a missing permission or a full disk would occur again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Sekai(err) => err.is_retryable(),
+            Self::Io(err) => matches!(
+                err.kind(),
+                io::ErrorKind::Interrupted | io::ErrorKind::TimedOut | io::ErrorKind::UnexpectedEof
+            ),
+            Self::CorruptAssetbundle(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the kind of request failure that caused this error, if a request to the game failed.
+    ///
+    /// Maintenance is not a request failure, and is reported as [`Error::Maintenance`] instead.
+    pub fn kind(&self) -> Option<twintail_sekai::ErrorKind> {
+        match self {
+            Self::Sekai(err) => err.kind(),
+            _ => None,
+        }
+    }
+
+    /// Returns how long a server asked to wait before attempting the operation again, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Sekai(err) => err.retry_after(),
+            _ => None,
+        }
+    }
+}
+
+/// Serializes a list of errors as their messages.
+pub(crate) fn serialize_errors<S: Serializer>(
+    errors: &[Error],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(errors.iter().map(ToString::to_string))
+}
@@ -1,28 +1,56 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
 };
 
 use futures::{StreamExt, stream};
 use humansize::{DECIMAL, format_size};
-use regex::Regex;
-use tokio::{fs::create_dir_all, sync::watch};
-use tokio_retry::{Retry, strategy::FixedInterval};
-use twintail_common::models::OptionalBuilder;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::{File, create_dir_all, metadata, remove_file, rename},
+    io::{AsyncWriteExt, BufWriter},
+    time::{Instant, sleep},
+};
+use twintail_common::models::{
+    OptionalBuilder,
+    enums::{Platform, Server},
+};
 use twintail_sekai::{
-    models::{Assetbundle, AssetbundleInfo, UserInherit},
-    sekai_client::{SekaiClient, SekaiClientBuilder},
+    ErrorKind,
+    device_profile::DeviceProfile,
+    models::{Assetbundle, AssetbundleInfo, SystemInfo, UserAuthResponse, UserInherit},
+    sekai_client::{AssetbundleStream, SekaiClient, SekaiClientBuilder},
     url::UrlProvider,
 };
 
 use crate::{
     Error,
-    config::{download_ab_config::DownloadAbConfig, fetch_config::FetchConfig},
+    abinfo::diff::get_assetbundles_differences,
+    config::{
+        download_ab_config::{DownloadAbConfig, PruneMode},
+        fetch_config::FetchConfig,
+    },
+    credential_store::{AccountServer, CredentialStore, StoredAccount},
     crypto::assetbundle,
-    fs::{extract_suitemaster_file, write_file},
+    error::serialize_errors,
+    fs::{create_file, extract_suitemaster_file, move_file, write_file},
+    install_id,
+    integrity::check_bundle_file,
+    manifest::DownloadManifest,
+    retry::retry,
+    state::{self, StateReceiver, StateSender},
 };
 
-#[derive(Clone, Copy)]
+/// The extension that is appended to assetbundles that are still being downloaded.
+const PART_EXTENSION: &str = ".part";
+/// The extension that is appended to encrypted assetbundles that are still being downloaded and decrypted.
+const DECRYPTED_PART_EXTENSION: &str = ".decrypted.part";
+/// The extension of the file that stores the hash of the assetbundle that its part files belong to.
+const PART_HASH_EXTENSION: &str = ".part.hash";
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DownloadSuiteState {
     /// The suite downloader is communicating with the game server
     Communicate,
@@ -34,21 +62,25 @@ pub enum DownloadSuiteState {
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum DownloadAbState {
     /// assetbundle info is being retrieved from the game server
     RetrieveAbInfo,
-    /// an invalid regular expression was given to the downloader
-    InvalidRegEx,
+    /// assetbundles that already exist in the output directory are being checked
+    CheckExisting,
     /// the given number of bytes are being downloaded
     DownloadStart(u64),
-    /// a file of the provided size in bytes was downloaded
-    FileDownload(u64),
+    /// the provided total number of bytes have been downloaded so far
+    Progress(u64),
+    /// the provided number of files of assetbundles that no longer exist are being pruned
+    Prune(usize),
     /// the download process finished
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum GetUserInheritState {
     /// communicating with the game server to get inherit data
     GetInherit,
@@ -56,7 +88,8 @@ pub enum GetUserInheritState {
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum WriteUserSaveDataState {
     /// logging into the user's account
     Login,
@@ -66,13 +99,133 @@ pub enum WriteUserSaveDataState {
     Finish,
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum FetchState {
     NoState,
     DownloadSuite(DownloadSuiteState),
     DownloadAb(DownloadAbState),
     GetUserInherit(GetUserInheritState),
     WriteUserSaveData(WriteUserSaveDataState),
+    /// the game servers are under maintenance, and will be checked again in the provided number of seconds
+    WaitForMaintenance(u64),
+}
+
+/// The versions of the app, game data and assets that a game server currently provides.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GameVersions {
+    /// the most recent app version that the system endpoint reports, empty if it reports none
+    #[serde(default)]
+    pub app_version: String,
+    /// the version of the suitemaster files
+    pub data_version: String,
+    pub asset_version: String,
+    pub asset_hash: String,
+}
+
+/// The outcome of downloading assetbundles.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadAbResult {
+    /// the number of assetbundles that were successfully downloaded
+    pub success_count: usize,
+    /// the number of assetbundles that were available for download
+    pub total_count: usize,
+    /// errors that occurred when downloading specific assetbundles, other than corrupt assetbundles
+    #[serde(serialize_with = "serialize_errors")]
+    pub errors: Vec<Error>,
+    /// the names of assetbundles that did not match their expected size or CRC on any attempt
+    pub corrupt: Vec<String>,
+    /// the files of assetbundles that no longer exist which were pruned, or would have been pruned with [`PruneMode::DryRun`]
+    pub pruned: Vec<PathBuf>,
+}
+
+/// An assetbundle that a [`DownloadAbPlan`] will download.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlannedBundle {
+    #[serde(flatten)]
+    pub bundle: Assetbundle,
+    /// where the assetbundle will be downloaded to
+    pub out_path: PathBuf,
+}
+
+/// Describes what downloading assetbundles with a config will do, without downloading anything.
+///
+/// Created with [`Fetcher::plan_ab`] and executed with [`Fetcher::execute_ab_plan`].
+/// A plan can be saved as JSON and executed later, by a fetcher for the same server and platform.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadAbPlan {
+    /// the server that the assetbundles will be downloaded from
+    pub server: Server,
+    /// identifies the server, including the API host of a custom server
+    pub server_id: AccountServer,
+    pub platform: Platform,
+    /// the directory that assetbundles will be downloaded to
+    pub out_dir: PathBuf,
+    pub asset_version: String,
+    pub asset_hash: Option<String>,
+    pub host_hash: Option<String>,
+    /// the assetbundles that will be downloaded, sorted by name
+    pub bundles: Vec<PlannedBundle>,
+    /// the total number of bytes that will be downloaded
+    pub total_size: u64,
+    /// the number of bytes available on the file system of out_dir when the plan was created
+    pub available_space: u64,
+    /// the files of assetbundles that no longer exist which will be pruned
+    pub prune_paths: Vec<PathBuf>,
+    /// what to do with prune_paths, if they will be pruned
+    pub prune_mode: Option<PruneMode>,
+    /// whether the size and CRC of downloaded assetbundles will be checked
+    pub verify: bool,
+    /// whether downloaded assetbundles will be decrypted
+    pub decrypt: bool,
+    /// the manifest that will be written to out_dir once the download finishes, if one will be written
+    pub manifest: Option<DownloadManifest>,
+}
+
+impl DownloadAbPlan {
+    /// Returns true if there was enough space available for the download when the plan was created.
+    pub fn has_enough_space(&self) -> bool {
+        self.total_size <= self.available_space
+    }
+}
+
+/// Tracks the total number of bytes downloaded by concurrent assetbundle downloads.
+struct DownloadProgress<'a> {
+    state_sender: &'a StateSender<FetchState>,
+    downloaded: AtomicU64,
+}
+
+impl<'a> DownloadProgress<'a> {
+    fn new(state_sender: &'a StateSender<FetchState>) -> Self {
+        Self {
+            state_sender,
+            downloaded: AtomicU64::new(0),
+        }
+    }
+
+    /// Adds the provided number of bytes to the total and reports the new total.
+    fn add(&self, bytes: u64) {
+        let downloaded = self.downloaded.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.state_sender
+            .send(FetchState::DownloadAb(DownloadAbState::Progress(
+                downloaded,
+            )));
+    }
+
+    /// Removes the provided number of bytes from the total and reports the new total.
+    ///
+    /// Used when a download fails after some of its bytes were already reported.
+    fn sub(&self, bytes: u64) {
+        let downloaded = self.downloaded.fetch_sub(bytes, Ordering::Relaxed) - bytes;
+        self.state_sender
+            .send(FetchState::DownloadAb(DownloadAbState::Progress(
+                downloaded,
+            )));
+    }
 }
 
 #[derive(Debug)]
@@ -84,14 +237,22 @@ struct AssetbundlePathArgs {
 
 /// Responsible for fetching assets or information from the game's official servers.
 pub struct Fetcher<P: UrlProvider> {
-    state_sender: watch::Sender<FetchState>,
+    state_sender: StateSender<FetchState>,
     config: FetchConfig<P>,
     client: SekaiClient<P>,
 }
 
 impl<P: UrlProvider> Fetcher<P> {
     /// Create a new Fetcher using the provided [`crate::config::fetch_config::FetchConfig`]
-    pub async fn new(config: FetchConfig<P>) -> Result<(Self, watch::Receiver<FetchState>), Error> {
+    pub async fn new(config: FetchConfig<P>) -> Result<(Self, StateReceiver<FetchState>), Error> {
+        let mut device_profile = config
+            .device_profile
+            .clone()
+            .unwrap_or_else(|| DeviceProfile::for_platform(config.platform));
+        if let Some(path) = &config.install_id_store {
+            device_profile.install_id = install_id::load_or_create(path).await?;
+        }
+
         let client = SekaiClientBuilder::new(
             config.aes_config.clone(),
             config.jwt_key.clone(),
@@ -102,10 +263,17 @@ impl<P: UrlProvider> Fetcher<P> {
         .map(config.version.clone(), |builder, hash| {
             builder.app_version(hash)
         })
+        .map(config.http_client.clone(), |builder, client| {
+            builder.client(client)
+        })
+        .map(config.http_config.clone(), |builder, http_config| {
+            builder.http_config(http_config)
+        })
+        .device_profile(device_profile)
         .build()
         .await?;
 
-        let (state_sender, recv) = watch::channel(FetchState::NoState);
+        let (state_sender, recv) = state::channel();
 
         Ok((
             Self {
@@ -117,25 +285,139 @@ impl<P: UrlProvider> Fetcher<P> {
         ))
     }
 
+    /// Makes sure that the game servers are not under maintenance.
+    ///
+    /// If the config has a maintenance wait policy, the game servers are polled until maintenance ends.
+    /// Otherwise, or if the policy runs out, [`Error::Maintenance`] is returned.
+    ///
+    /// Returns the system information that reported the game servers as open.
+    async fn wait_for_open(&self) -> Result<SystemInfo, Error> {
+        let start = Instant::now();
+        let mut polls = 0;
+
+        loop {
+            let system_info = self.client.get_system().await?;
+            if !system_info.is_maintenance() {
+                return Ok(system_info);
+            }
+
+            let Some(poll_policy) = &self.config.maintenance_wait else {
+                return Err(Error::Maintenance);
+            };
+            let delay = poll_policy.delay(polls);
+            if polls >= poll_policy.max_retries
+                || poll_policy
+                    .deadline
+                    .is_some_and(|deadline| start.elapsed() + delay > deadline)
+            {
+                return Err(Error::Maintenance);
+            }
+
+            self.state_sender
+                .send(FetchState::WaitForMaintenance(delay.as_secs()));
+            sleep(delay).await;
+            polls += 1;
+        }
+    }
+
+    /// Logs into an anonymous account on the game server.
+    ///
+    /// If a credential store was configured, the account stored for this server and platform will be reused.
+    /// Custom servers are told apart by the host of their API, see [`AccountServer`].
+    /// A new account is only signed up for if no account was stored or logging into the stored account failed.
+    async fn login(&mut self) -> Result<UserAuthResponse, Error> {
+        self.wait_for_open().await?;
+        self.login_account().await
+    }
+
+    /// Logs into an anonymous account without checking for maintenance first, see [`Self::login`].
+    async fn login_account(&mut self) -> Result<UserAuthResponse, Error> {
+        let mut credential_store = match &self.config.credential_store {
+            Some(path) => Some(CredentialStore::load(path).await?),
+            None => None,
+        };
+
+        // try to reuse a stored account
+        let account_server = AccountServer::new(self.config.server, &self.config.url_provider);
+        if let Some(account) = credential_store
+            .as_ref()
+            .and_then(|store| store.get(&account_server, self.config.platform))
+        {
+            match self
+                .client
+                .user_login(account.user_id, account.credential.clone())
+                .await
+            {
+                Ok(auth_response) => return Ok(auth_response),
+                // the stored account no longer exists or was rejected, so a new one is needed
+                Err(err)
+                    if matches!(err.kind(), Some(ErrorKind::NotFound | ErrorKind::Forbidden)) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        // sign up for a new account
+        let user_signup = self.client.user_signup().await?;
+        let user_id = user_signup.user_registration.user_id;
+        let auth_response = self
+            .client
+            .user_login(user_id, user_signup.credential.clone())
+            .await?;
+
+        if let Some(store) = &mut credential_store {
+            store.insert(
+                account_server,
+                self.config.platform,
+                StoredAccount {
+                    user_id,
+                    credential: user_signup.credential,
+                },
+            );
+            store.save().await?;
+        }
+
+        Ok(auth_response)
+    }
+
+    /// Gets the versions of the app, game data and assets that the game server currently provides.
+    ///
+    /// The app version and maintenance state come from the system endpoint,
+    /// and the other versions from logging in.
+    pub async fn get_versions(&mut self) -> Result<GameVersions, Error> {
+        let system_info = self.wait_for_open().await?;
+        let user_login = self.login_account().await?;
+
+        Ok(GameVersions {
+            app_version: system_info
+                .app_versions
+                .last()
+                .map(|app_version| app_version.app_version.clone())
+                .unwrap_or_default(),
+            data_version: user_login.data_version,
+            asset_version: user_login.asset_version,
+            asset_hash: user_login.asset_hash,
+        })
+    }
+
     /// Gets assetbundle info from the game server.
     ///
     /// If asset_version or host_hash are not provided, their most recent values will be used.
+    /// If asset_hash is not provided, it is retrieved by logging in.
+    ///
+    /// The game servers are checked for maintenance first, even if every value was provided, see [`Error::Maintenance`].
     pub async fn get_ab_info(
         &mut self,
         asset_version: Option<String>,
+        asset_hash: Option<String>,
         host_hash: Option<String>,
     ) -> Result<AssetbundleInfo, Error> {
+        // every request below fails while the game servers are under maintenance
+        let system_info = self.wait_for_open().await?;
+
         // get asset hash only if we got the most recent versions of the asset_version & host_hash
-        let asset_hash = {
-            let user_signup = self.client.user_signup().await?;
-            let user_auth_response = self
-                .client
-                .user_login(
-                    user_signup.user_registration.user_id,
-                    user_signup.credential,
-                )
-                .await?;
-            user_auth_response.asset_hash
+        let asset_hash = match asset_hash {
+            Some(asset_hash) => asset_hash,
+            None => self.login_account().await?.asset_hash,
         };
 
         // get the assetbundle host hash
@@ -145,16 +427,13 @@ impl<P: UrlProvider> Fetcher<P> {
             self.client.get_game_version().await?.assetbundle_host_hash
         };
 
-        // get system information
+        // get the most recent asset version from the system information
         let asset_version = if let Some(version) = asset_version {
             Ok(version)
+        } else if let Some(most_recent_version) = system_info.app_versions.last() {
+            Ok(most_recent_version.asset_version.clone())
         } else {
-            let system_info = self.client.get_system().await?;
-            if let Some(most_recent_version) = system_info.app_versions.last() {
-                Ok(most_recent_version.asset_version.clone())
-            } else {
-                Err(Error::NotFound("most recent game version not found".into()))
-            }
+            Err(Error::NotFound("most recent game version not found".into()))
         }?;
 
         // get the assetbundle info
@@ -183,34 +462,27 @@ impl<P: UrlProvider> Fetcher<P> {
     ) -> Result<(usize, usize, String), Error> {
         // see what suite master split files are available for download
         self.state_sender
-            .send_replace(FetchState::DownloadSuite(DownloadSuiteState::Communicate));
+            .send(FetchState::DownloadSuite(DownloadSuiteState::Communicate));
 
-        let user_signup = self.client.user_signup().await?;
-        let user_login = self
-            .client
-            .user_login(
-                user_signup.user_registration.user_id,
-                user_signup.credential,
-            )
-            .await?;
+        let user_login = self.login().await?;
 
         // create download progress bar
         let suitemaster_split_paths = user_login.suite_master_split_path;
         let split_count = suitemaster_split_paths.len();
 
-        self.state_sender.send_replace(FetchState::DownloadSuite(
+        self.state_sender.send(FetchState::DownloadSuite(
             DownloadSuiteState::DownloadStart(split_count),
         ));
 
         // download suite master split files
         let out_path = out_path.as_ref();
-        let retry_strat = FixedInterval::from_millis(200).take(self.config.retry);
+        let retry_policy = &self.config.retry_policy;
         let do_decrypt = self.config.decrypt;
         let pretty_json = self.config.pretty_json;
 
         let download_results: Vec<Result<(), Error>> = stream::iter(&suitemaster_split_paths)
             .map(|api_path| async {
-                let retry_result = Retry::spawn(retry_strat.clone(), || {
+                let retry_result = retry(retry_policy, || {
                     download_suitemasterfile(
                         &self.client,
                         api_path,
@@ -221,7 +493,7 @@ impl<P: UrlProvider> Fetcher<P> {
                 })
                 .await;
                 self.state_sender
-                    .send_replace(FetchState::DownloadSuite(DownloadSuiteState::FileDownload));
+                    .send(FetchState::DownloadSuite(DownloadSuiteState::FileDownload));
                 retry_result
             })
             .buffer_unordered(self.config.concurrency)
@@ -235,143 +507,352 @@ impl<P: UrlProvider> Fetcher<P> {
             .count();
 
         self.state_sender
-            .send_replace(FetchState::DownloadSuite(DownloadSuiteState::Finish));
+            .send(FetchState::DownloadSuite(DownloadSuiteState::Finish));
 
         Ok((success_count, split_count, user_login.data_version))
     }
 
     /// Downloads assetbundles to the provided ``out_dir`` using the provided config.
     ///
-    /// Returns:
-    /// - the number of files that were successfully downloaded
-    /// - the number of files that were available for download
-    /// - a Vec of errors that ocurred when downloading specific files
+    /// This is equivalent to creating a plan with [`Fetcher::plan_ab`] and executing it with [`Fetcher::execute_ab_plan`].
+    ///
+    /// Returns a [`DownloadAbResult`] describing which assetbundles were downloaded.
     pub async fn download_ab(
         &mut self,
         out_dir: impl AsRef<Path>,
         config: DownloadAbConfig,
-    ) -> Result<(usize, usize, Vec<Error>), Error> {
+    ) -> Result<DownloadAbResult, Error> {
+        let plan = self.plan_ab(out_dir, config).await?;
+        self.execute_ab_plan(plan).await
+    }
+
+    /// Determines which assetbundles would be downloaded to the provided ``out_dir`` using the provided config,
+    /// without downloading anything.
+    ///
+    /// Unless disabled in the config, a [`DownloadManifest`] of the downloaded assetbundles is written to ``out_dir``
+    /// when the plan is executed. If no assetbundle info is provided, the manifest of a previous download is used to
+    /// only download the assetbundles that changed.
+    ///
+    /// Returns [`Error::ServerMismatch`] if ``out_dir`` contains the manifest of a different server or platform.
+    pub async fn plan_ab(
+        &mut self,
+        out_dir: impl AsRef<Path>,
+        config: DownloadAbConfig,
+    ) -> Result<DownloadAbPlan, Error> {
+        // convert out_dir to a path.
+        let out_dir = out_dir.as_ref();
+
         // create assetbundle spinner
         self.state_sender
-            .send_replace(FetchState::DownloadAb(DownloadAbState::RetrieveAbInfo));
+            .send(FetchState::DownloadAb(DownloadAbState::RetrieveAbInfo));
 
-        // get assetbundle info
-        let assetbundle_info = match config.info {
-            None => {
-                self.get_ab_info(config.asset_version, config.host_hash)
-                    .await?
-            }
+        // load the manifest of a previous download into out_dir
+        let previous_manifest = match config.manifest {
+            true => DownloadManifest::load(out_dir).await?,
+            false => None,
+        };
+        if let Some(manifest) = &previous_manifest {
+            manifest.check_server(self.config.server, self.config.platform)?;
+        }
+
+        // get assetbundle info, along with the info of the previous download if one was provided
+        let (latest_info, previous_info) = match config.info {
+            None => (
+                self.get_ab_info(config.asset_version, config.asset_hash, config.host_hash)
+                    .await?,
+                None,
+            ),
             Some(info) => {
                 if config.update {
                     let latest_info = self
-                        .get_ab_info(config.asset_version, config.host_hash)
+                        .get_ab_info(config.asset_version, config.asset_hash, config.host_hash)
                         .await?;
-
-                    AssetbundleInfo {
-                        bundles: get_assetbundles_differences(latest_info.bundles, &info.bundles),
-                        ..latest_info
-                    }
+                    (latest_info, Some(info))
                 } else {
-                    info
+                    (info, None)
                 }
             }
         };
 
-        // extract data from assetbundle_info
-        let ab_path_args = AssetbundlePathArgs {
-            asset_version: assetbundle_info.version.clone(),
-            asset_hash: assetbundle_info.hash.clone().unwrap_or_default(),
-            host_hash: assetbundle_info.host_hash.clone().unwrap_or_default(),
+        // find the files of bundles that no longer exist in the latest info
+        let removed_paths: Vec<PathBuf> = match (&config.prune, &previous_manifest, &previous_info)
+        {
+            (None, _, _) => Vec::new(),
+            (Some(_), Some(manifest), _) => manifest
+                .bundles
+                .iter()
+                .filter(|(bundle_name, _)| !latest_info.bundles.contains_key(*bundle_name))
+                .map(|(_, bundle)| out_dir.join(&bundle.path))
+                .collect(),
+            (Some(_), None, Some(info)) => info
+                .bundles
+                .keys()
+                .filter(|bundle_name| !latest_info.bundles.contains_key(*bundle_name))
+                .map(|bundle_name| {
+                    out_dir.join(self.client.url_provider.assetbundle_path(
+                        &info.version,
+                        info.hash.as_deref().unwrap_or_default(),
+                        &self.client.platform,
+                        bundle_name,
+                    ))
+                })
+                .collect(),
+            (Some(_), None, None) => Vec::new(),
         };
+        let mut prune_paths: Vec<PathBuf> = removed_paths
+            .into_iter()
+            .filter(|path| path.try_exists().unwrap_or(false))
+            .collect();
+        prune_paths.sort();
 
-        // convert out_dir to a path.
-        let out_dir = out_dir.as_ref();
-        create_dir_all(out_dir).await?;
+        // select the bundles that match the selection
+        let selected_bundles: Option<HashSet<String>> =
+            config.selection.as_ref().map(|selection| {
+                let matched = latest_info
+                    .bundles
+                    .iter()
+                    .filter(|(_, bundle)| selection.matches(bundle))
+                    .map(|(bundle_name, _)| bundle_name.clone())
+                    .collect();
 
-        // calculate out paths
-        let mut total_bundle_size = 0;
-        let mut to_download_bundles: Vec<(Assetbundle, PathBuf)> = Vec::new();
+                if config.with_dependencies {
+                    get_assetbundles_with_dependencies(&latest_info.bundles, matched)
+                } else {
+                    matched
+                }
+            });
 
-        let bundle_name_re = config
-            .filter
-            .as_ref()
-            .and_then(|filter| Regex::new(filter).ok());
+        // only download the bundles that changed since the previous download
+        let assetbundle_info = match (&previous_info, &previous_manifest) {
+            (Some(info), _) => AssetbundleInfo {
+                bundles: get_assetbundles_differences(latest_info.bundles, &info.bundles),
+                ..latest_info
+            },
+            (None, Some(manifest)) if config.update => AssetbundleInfo {
+                bundles: manifest.changed_bundles(latest_info.bundles),
+                ..latest_info
+            },
+            _ => latest_info,
+        };
+
+        // the manifest that will be written once the download finishes
+        let mut manifest = config.manifest.then(|| {
+            let mut manifest = previous_manifest.unwrap_or_else(|| {
+                DownloadManifest::new(self.config.server, self.config.platform, &assetbundle_info)
+            });
+            manifest.set_info(&assetbundle_info);
+            manifest
+        });
+
+        // calculate out paths
+        let mut to_download_bundles: Vec<PlannedBundle> = Vec::new();
 
         for (bundle_name, bundle) in assetbundle_info.bundles {
-            if bundle_name_re
+            if selected_bundles
                 .as_ref()
-                .is_none_or(|re| re.find(&bundle_name).is_some())
+                .is_none_or(|selected| selected.contains(&bundle_name))
             {
                 let out_path = out_dir.join(self.client.url_provider.assetbundle_path(
-                    &ab_path_args.asset_version,
-                    &ab_path_args.asset_hash,
+                    &assetbundle_info.version,
+                    assetbundle_info.hash.as_deref().unwrap_or_default(),
                     &self.client.platform,
                     &bundle.bundle_name,
                 ));
 
-                total_bundle_size += bundle.file_size;
-                to_download_bundles.push((bundle, out_path));
+                to_download_bundles.push(PlannedBundle { bundle, out_path });
             }
         }
 
-        if config.filter.is_some() && bundle_name_re.is_none() {
+        // only download bundles that are missing or do not match their expected size and crc
+        if config.skip_existing {
             self.state_sender
-                .send_replace(FetchState::DownloadAb(DownloadAbState::InvalidRegEx));
+                .send(FetchState::DownloadAb(DownloadAbState::CheckExisting));
+
+            let check_results: Vec<Result<(PlannedBundle, bool), Error>> =
+                stream::iter(to_download_bundles)
+                    .map(|planned| async move {
+                        let status = check_bundle_file(&planned.out_path, &planned.bundle).await?;
+                        Ok((planned, status.is_valid()))
+                    })
+                    .buffer_unordered(self.config.concurrency)
+                    .collect()
+                    .await;
+
+            to_download_bundles = Vec::new();
+            for check_result in check_results {
+                let (planned, is_valid) = check_result?;
+                if !is_valid {
+                    to_download_bundles.push(planned);
+                } else if let Some(manifest) = &mut manifest {
+                    manifest.insert(&planned.bundle, relative_path(out_dir, &planned.out_path));
+                }
+            }
         }
+        to_download_bundles.sort_by(|a, b| a.bundle.bundle_name.cmp(&b.bundle.bundle_name));
+
+        let total_size: u64 = to_download_bundles
+            .iter()
+            .map(|planned| planned.bundle.file_size)
+            .sum();
+
+        Ok(DownloadAbPlan {
+            server: self.config.server,
+            server_id: AccountServer::new(self.config.server, &self.config.url_provider),
+            platform: self.config.platform,
+            out_dir: out_dir.to_path_buf(),
+            asset_version: assetbundle_info.version,
+            asset_hash: assetbundle_info.hash,
+            host_hash: assetbundle_info.host_hash,
+            bundles: to_download_bundles,
+            total_size,
+            available_space: available_space(out_dir)?,
+            prune_paths,
+            prune_mode: config.prune,
+            verify: config.verify,
+            decrypt: self.config.decrypt,
+            manifest,
+        })
+    }
+
+    /// Downloads the assetbundles in a plan created by [`Fetcher::plan_ab`].
+    ///
+    /// Returns [`Error::ServerMismatch`] if the plan was created for a different server or platform than this fetcher's.
+    /// Custom servers only match if their API hosts are the same.
+    ///
+    /// The assetbundles are decrypted if the plan was created by a fetcher that decrypts them,
+    /// regardless of this fetcher's configuration.
+    ///
+    /// Returns a [`DownloadAbResult`] describing which assetbundles were downloaded.
+    pub async fn execute_ab_plan(&self, plan: DownloadAbPlan) -> Result<DownloadAbResult, Error> {
+        let server_id = AccountServer::new(self.config.server, &self.config.url_provider);
+        if plan.server_id != server_id || plan.platform != self.config.platform {
+            return Err(Error::ServerMismatch(format!(
+                "the plan was created for {} ({}), not for {} ({})",
+                plan.server_id, plan.platform, server_id, self.config.platform
+            )));
+        }
+
+        let DownloadAbPlan {
+            out_dir,
+            asset_version,
+            asset_hash,
+            host_hash,
+            bundles: to_download_bundles,
+            total_size,
+            prune_paths,
+            prune_mode,
+            verify,
+            decrypt,
+            mut manifest,
+            ..
+        } = plan;
+
+        let ab_path_args = AssetbundlePathArgs {
+            asset_version,
+            asset_hash: asset_hash.unwrap_or_default(),
+            host_hash: host_hash.unwrap_or_default(),
+        };
+
+        create_dir_all(&out_dir).await?;
 
         // make sure the out_dir has enough space
-        let available_space = fs2::available_space(out_dir)?;
-        if total_bundle_size > available_space {
+        let available_space = fs2::available_space(&out_dir)?;
+        if total_size > available_space {
             return Err(Error::NotEnoughSpace(format!(
                 "this operation requires {} of free space. you only have {} available.",
-                format_size(total_bundle_size, DECIMAL),
+                format_size(total_size, DECIMAL),
                 format_size(available_space, DECIMAL)
             )));
         }
 
         // create download progress bar
         self.state_sender
-            .send_replace(FetchState::DownloadAb(DownloadAbState::DownloadStart(
-                total_bundle_size,
+            .send(FetchState::DownloadAb(DownloadAbState::DownloadStart(
+                total_size,
             )));
 
         // download bundles
-        let retry_strat = FixedInterval::from_millis(200).take(self.config.retry);
-        let do_decrypt = self.config.decrypt;
+        let retry_policy = &self.config.retry_policy;
+        let progress = &DownloadProgress::new(&self.state_sender);
+        let ab_path_args = &ab_path_args;
 
-        let download_results: Vec<Result<(), Error>> = stream::iter(&to_download_bundles)
-            .map(|(bundle, out_path)| async {
-                let download_result = Retry::spawn(retry_strat.clone(), || {
-                    download_bundle(&self.client, bundle, out_path, &ab_path_args, do_decrypt)
+        let download_results: Vec<(&PlannedBundle, Result<(), Error>)> =
+            stream::iter(&to_download_bundles)
+                .map(|planned| async move {
+                    let download_result = retry(retry_policy, || {
+                        download_bundle(
+                            &self.client,
+                            &planned.bundle,
+                            &planned.out_path,
+                            ab_path_args,
+                            decrypt,
+                            verify,
+                            progress,
+                        )
+                    })
+                    .await;
+                    (planned, download_result)
                 })
+                .buffer_unordered(self.config.concurrency)
+                .collect()
                 .await;
-                if download_result.is_ok() {
-                    self.state_sender.send_replace(FetchState::DownloadAb(
-                        DownloadAbState::FileDownload(bundle.file_size),
-                    ));
+
+        // collect errors & the bundles that were corrupt
+        let mut errors = Vec::new();
+        let mut corrupt = Vec::new();
+        for (planned, result) in download_results {
+            match result {
+                Ok(()) => {
+                    if let Some(manifest) = &mut manifest {
+                        manifest
+                            .insert(&planned.bundle, relative_path(&out_dir, &planned.out_path));
+                    }
                 }
-                download_result
-            })
-            .buffer_unordered(self.config.concurrency)
-            .collect()
-            .await;
+                Err(Error::CorruptAssetbundle(_)) => {
+                    corrupt.push(planned.bundle.bundle_name.clone());
+                }
+                Err(err) => errors.push(err),
+            }
+        }
 
-        // count successes & print errors if debug is enabled
-        let download_errors: Vec<_> = download_results
-            .into_iter()
-            .filter_map(|result| result.err())
-            .collect();
+        // remove the files of bundles that no longer exist
+        let pruned = match &prune_mode {
+            Some(prune_mode) => {
+                self.state_sender
+                    .send(FetchState::DownloadAb(DownloadAbState::Prune(
+                        prune_paths.len(),
+                    )));
+
+                let pruned = prune_files(prune_paths, &out_dir, prune_mode).await?;
+                if let Some(manifest) = &mut manifest
+                    && *prune_mode != PruneMode::DryRun
+                {
+                    manifest
+                        .bundles
+                        .retain(|_, bundle| !pruned.contains(&out_dir.join(&bundle.path)));
+                }
+                pruned
+            }
+            None => Vec::new(),
+        };
+
+        // record what was downloaded
+        if let Some(manifest) = &manifest {
+            manifest.save(&out_dir).await?;
+        }
 
         // stop progress bar & print the sucess message
         self.state_sender
-            .send_replace(FetchState::DownloadAb(DownloadAbState::Finish));
+            .send(FetchState::DownloadAb(DownloadAbState::Finish));
 
-        let total_bundle_count = to_download_bundles.len();
-        Ok((
-            total_bundle_count - download_errors.len(),
-            total_bundle_count,
-            download_errors,
-        ))
+        let total_count = to_download_bundles.len();
+        Ok(DownloadAbResult {
+            success_count: total_count - errors.len() - corrupt.len(),
+            total_count,
+            errors,
+            corrupt,
+            pruned,
+        })
     }
 
     /// Performs a request to get a user's account inherit details.
@@ -385,8 +866,9 @@ impl<P: UrlProvider> Fetcher<P> {
         password: &str,
         execute: bool,
     ) -> Result<UserInherit, Error> {
+        self.wait_for_open().await?;
         self.state_sender
-            .send_replace(FetchState::GetUserInherit(GetUserInheritState::GetInherit));
+            .send(FetchState::GetUserInherit(GetUserInheritState::GetInherit));
 
         let user_inherit = self
             .client
@@ -394,7 +876,7 @@ impl<P: UrlProvider> Fetcher<P> {
             .await?;
 
         self.state_sender
-            .send_replace(FetchState::GetUserInherit(GetUserInheritState::Finish));
+            .send(FetchState::GetUserInherit(GetUserInheritState::Finish));
 
         Ok(user_inherit)
     }
@@ -408,13 +890,13 @@ impl<P: UrlProvider> Fetcher<P> {
         credential: String,
         out_dir: impl AsRef<Path>,
     ) -> Result<PathBuf, Error> {
+        self.wait_for_open().await?;
         self.state_sender
-            .send_replace(FetchState::WriteUserSaveData(WriteUserSaveDataState::Login));
+            .send(FetchState::WriteUserSaveData(WriteUserSaveDataState::Login));
         self.client.user_login(user_id, credential).await?;
-        self.state_sender
-            .send_replace(FetchState::WriteUserSaveData(
-                WriteUserSaveDataState::GetSaveData,
-            ));
+        self.state_sender.send(FetchState::WriteUserSaveData(
+            WriteUserSaveDataState::GetSaveData,
+        ));
 
         // convert retrieve save data & convert to json
         let save_data = self.client.get_user_suite(user_id).await?;
@@ -428,10 +910,9 @@ impl<P: UrlProvider> Fetcher<P> {
         let out_path = out_dir.as_ref().join(format!("{}.json", user_id));
         write_file(&out_path, &json_save_data).await?;
 
-        self.state_sender
-            .send_replace(FetchState::WriteUserSaveData(
-                WriteUserSaveDataState::Finish,
-            ));
+        self.state_sender.send(FetchState::WriteUserSaveData(
+            WriteUserSaveDataState::Finish,
+        ));
 
         Ok(out_path)
     }
@@ -469,56 +950,990 @@ async fn download_suitemasterfile<P: UrlProvider>(
     }
 }
 
-/// Compares two HashMaps of [crate::models::api::Assetbundle].
+/// Removes the provided files according to the prune mode, returning the files that were pruned.
 ///
-/// Returns a new HashMap of [crate::models::api::Assetbundle] where
-/// a bundle in main_bundles exists in compare_bundles,
-/// but has a different hash value.
-fn get_assetbundles_differences(
-    main_bundles: HashMap<String, Assetbundle>,
-    compare_bundles: &HashMap<String, Assetbundle>,
-) -> HashMap<String, Assetbundle> {
-    main_bundles
-        .into_iter()
-        .filter(|(bundle_name, bundle)| {
-            compare_bundles
-                .get(bundle_name)
-                .is_none_or(|compare_bundle| compare_bundle.hash != bundle.hash)
-        })
-        .collect()
+/// Files that do not exist are skipped.
+/// When moving files to a trash directory, their location relative to ``out_dir`` is kept.
+async fn prune_files(
+    paths: Vec<PathBuf>,
+    out_dir: &Path,
+    prune_mode: &PruneMode,
+) -> Result<Vec<PathBuf>, Error> {
+    let mut pruned = Vec::new();
+    for path in paths {
+        if !path.try_exists().unwrap_or(false) {
+            continue;
+        }
+
+        match prune_mode {
+            PruneMode::DryRun => {}
+            PruneMode::Delete => remove_file(&path).await?,
+            PruneMode::Trash(trash_dir) => {
+                let trash_path = trash_dir.join(path.strip_prefix(out_dir).unwrap_or(&path));
+                move_file(&path, &trash_path).await?;
+            }
+        }
+        pruned.push(path);
+    }
+
+    pruned.sort();
+    Ok(pruned)
+}
+
+/// Returns the provided bundle names along with the names of every bundle that they depend on, directly or indirectly.
+///
+/// Dependencies that do not exist in bundles are ignored.
+fn get_assetbundles_with_dependencies(
+    bundles: &HashMap<String, Assetbundle>,
+    bundle_names: HashSet<String>,
+) -> HashSet<String> {
+    let mut to_visit: Vec<String> = bundle_names.iter().cloned().collect();
+    let mut with_dependencies = bundle_names;
+
+    while let Some(bundle_name) = to_visit.pop() {
+        if let Some(bundle) = bundles.get(&bundle_name) {
+            for dependency in &bundle.dependencies {
+                if bundles.contains_key(dependency) && with_dependencies.insert(dependency.clone())
+                {
+                    to_visit.push(dependency.clone());
+                }
+            }
+        }
+    }
+
+    with_dependencies
+}
+
+/// Returns out_path relative to out_dir.
+fn relative_path(out_dir: &Path, out_path: &Path) -> PathBuf {
+    out_path
+        .strip_prefix(out_dir)
+        .unwrap_or(out_path)
+        .to_path_buf()
+}
+
+/// Returns the number of bytes available on the file system that path is on.
+///
+/// If path does not exist yet, its closest existing ancestor is used.
+fn available_space(path: &Path) -> Result<u64, Error> {
+    let existing_path = path
+        .ancestors()
+        .find(|ancestor| ancestor.try_exists().unwrap_or(false))
+        .unwrap_or(Path::new("."));
+    Ok(fs2::available_space(existing_path)?)
+}
+
+/// Returns the path that an assetbundle is downloaded to before it is complete.
+///
+/// If decrypted is true, this is the path of an encrypted assetbundle that is decrypted as it is received.
+fn part_path(out_path: &Path, decrypted: bool) -> PathBuf {
+    let mut part_path = out_path.as_os_str().to_owned();
+    part_path.push(match decrypted {
+        true => DECRYPTED_PART_EXTENSION,
+        false => PART_EXTENSION,
+    });
+    PathBuf::from(part_path)
+}
+
+/// Returns the path of the file that stores the hash of the assetbundle that the part files of out_path belong to.
+///
+/// A part file can only be resumed if it belongs to the same assetbundle hash,
+/// since a part file left over from an older version of the assetbundle would be completed with different bytes.
+fn part_hash_path(out_path: &Path) -> PathBuf {
+    let mut part_hash_path = out_path.as_os_str().to_owned();
+    part_hash_path.push(PART_HASH_EXTENSION);
+    PathBuf::from(part_hash_path)
 }
 
 /// Downloads an assetbundle to a provided path.
 ///
-/// If decrypt is false, the downloaded assetbundle will remain encrypted.
+/// The assetbundle is first written to a part file next to out_path as it is received.
+/// If a part file from an interrupted download of the same assetbundle hash already exists, the download resumes from its end.
+/// Once complete, the part file is moved to out_path.
+///
+/// If verify is true, the size and CRC of the downloaded assetbundle are compared with the expected values.
+/// A mismatch is returned as [`Error::CorruptAssetbundle`] so that the download can be retried.
+///
+/// If decrypt is true, the assetbundle is decrypted as it is written to the part file.
+/// Otherwise, the downloaded assetbundle will remain encrypted.
 async fn download_bundle<P: UrlProvider>(
     client: &SekaiClient<P>,
     bundle: &Assetbundle,
     out_path: &Path,
     path_args: &AssetbundlePathArgs,
     decrypt: bool,
+    verify: bool,
+    progress: &DownloadProgress<'_>,
 ) -> Result<(), Error> {
-    // download
-    let mut ab_data = client
-        .get_assetbundle(
+    let mut downloaded = 0;
+    let result = match write_part_file(
+        client,
+        bundle,
+        out_path,
+        path_args,
+        decrypt,
+        progress,
+        &mut downloaded,
+    )
+    .await
+    {
+        Ok((part_path, crc)) if verify => verify_part_file(bundle, &part_path, downloaded, crc)
+            .await
+            .map(|_| part_path),
+        result => result.map(|(part_path, _)| part_path),
+    };
+
+    // the part hash is only needed while a part file exists
+    if matches!(result, Ok(_) | Err(Error::CorruptAssetbundle(_))) {
+        let part_hash_path = part_hash_path(out_path);
+        if part_hash_path.exists() {
+            remove_file(&part_hash_path).await?;
+        }
+    }
+
+    // undo progress made by a failed attempt, since it will be reported again
+    match result {
+        Ok(part_path) => Ok(rename(&part_path, out_path).await?),
+        Err(err) => {
+            progress.sub(downloaded);
+            Err(err)
+        }
+    }
+}
+
+/// Compares a completely downloaded part file with the expected size and CRC of its assetbundle.
+///
+/// If they do not match, the part file is removed so that the next attempt starts from the beginning.
+async fn verify_part_file(
+    bundle: &Assetbundle,
+    part_path: &Path,
+    size: u64,
+    crc: u32,
+) -> Result<(), Error> {
+    let mismatch = if size != bundle.file_size {
+        Some(format!(
+            "{}: expected {} bytes, received {} bytes",
+            bundle.bundle_name, bundle.file_size, size
+        ))
+    } else if crc != bundle.crc {
+        Some(format!(
+            "{}: expected crc {}, received crc {}",
+            bundle.bundle_name, bundle.crc, crc
+        ))
+    } else {
+        None
+    };
+
+    match mismatch {
+        Some(message) => {
+            remove_file(part_path).await?;
+            Err(Error::CorruptAssetbundle(message))
+        }
+        None => Ok(()),
+    }
+}
+
+/// The part file of an interrupted assetbundle download.
+struct PartFile {
+    path: PathBuf,
+    /// whether the file contains the decrypted bytes of an encrypted assetbundle
+    decrypted: bool,
+    /// the number of downloaded bytes that the file was written from
+    downloaded_size: u64,
+}
+
+impl PartFile {
+    /// Finds the part file of an interrupted download of the assetbundle at out_path that can be resumed.
+    ///
+    /// A part file can only be resumed if it was downloaded from the same assetbundle hash, see [`part_hash_path`].
+    /// If decrypt is true, a part file that still contains an encrypted assetbundle cannot be resumed,
+    /// since the rest of the download would be decrypted after its header.
+    async fn find(
+        out_path: &Path,
+        bundle_hash: &str,
+        decrypt: bool,
+    ) -> Result<Option<Self>, Error> {
+        match tokio::fs::read_to_string(part_hash_path(out_path)).await {
+            Ok(part_hash) if part_hash == bundle_hash => {}
+            _ => return Ok(None),
+        }
+
+        let magic_size = assetbundle::SEKAI_ASSETBUNDLE_MAGIC.len() as u64;
+
+        // the decrypted header is written all at once, so a decrypted part file always contains it
+        let decrypted_path = part_path(out_path, true);
+        if decrypt
+            && let Ok(metadata) = metadata(&decrypted_path).await
+            && metadata.len() >= assetbundle::HEADER_SIZE as u64
+        {
+            return Ok(Some(Self {
+                path: decrypted_path,
+                decrypted: true,
+                downloaded_size: magic_size + metadata.len(),
+            }));
+        }
+
+        let path = part_path(out_path, false);
+        match metadata(&path).await {
+            Ok(metadata)
+                if metadata.len() >= magic_size
+                    && !(decrypt && assetbundle::is_encrypted(&path).await?) =>
+            {
+                Ok(Some(Self {
+                    path,
+                    decrypted: false,
+                    downloaded_size: metadata.len(),
+                }))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Writes the downloaded bytes of an assetbundle into a part file.
+struct PartWriter {
+    writer: BufWriter<File>,
+    /// decrypts the downloaded bytes before they are written, if the part file is decrypted
+    decrypter: Option<assetbundle::StreamDecrypter>,
+    /// the CRC32 of the downloaded bytes
+    hasher: crc32fast::Hasher,
+}
+
+impl PartWriter {
+    /// Writes the next chunk of downloaded bytes.
+    async fn write(&mut self, chunk: &[u8]) -> Result<(), Error> {
+        self.hasher.update(chunk);
+        match &mut self.decrypter {
+            Some(decrypter) => decrypter.write_chunk(chunk, &mut self.writer).await,
+            None => Ok(self.writer.write_all(chunk).await?),
+        }
+    }
+
+    /// Writes any buffered bytes, returning the CRC32 of the downloaded bytes.
+    async fn finish(mut self) -> Result<u32, Error> {
+        if let Some(decrypter) = self.decrypter {
+            decrypter.finish(&mut self.writer).await?;
+        }
+        self.writer.flush().await?;
+        Ok(self.hasher.finalize())
+    }
+}
+
+/// Streams an assetbundle into a part file next to out_path.
+///
+/// If decrypt is true and the assetbundle is encrypted, it is decrypted as it is received.
+/// If a part file already contains the beginning of the assetbundle, only the remaining bytes are requested.
+///
+/// The number of downloaded bytes that the part file was written from is added to downloaded as each chunk arrives.
+///
+/// Returns the path of the complete part file, and the CRC32 of the downloaded bytes.
+async fn write_part_file<P: UrlProvider>(
+    client: &SekaiClient<P>,
+    bundle: &Assetbundle,
+    out_path: &Path,
+    path_args: &AssetbundlePathArgs,
+    decrypt: bool,
+    progress: &DownloadProgress<'_>,
+    downloaded: &mut u64,
+) -> Result<(PathBuf, u32), Error> {
+    // see how much of the assetbundle was downloaded previously
+    let existing = PartFile::find(out_path, &bundle.hash, decrypt)
+        .await?
+        .filter(|part| part.downloaded_size <= bundle.file_size);
+    if let Some(part) = &existing
+        && part.downloaded_size == bundle.file_size
+    {
+        *downloaded += part.downloaded_size;
+        progress.add(part.downloaded_size);
+        let crc = assetbundle::crc32(&part.path, part.decrypted).await?;
+        return Ok((part.path.clone(), crc));
+    }
+
+    let request_stream = |offset| {
+        client.get_assetbundle_stream_range(
             &path_args.asset_version,
             &path_args.asset_hash,
             &path_args.host_hash,
             &bundle.bundle_name,
+            offset,
+        )
+    };
+    let mut stream =
+        request_stream(existing.as_ref().map_or(0, |part| part.downloaded_size)).await?;
+
+    // append only if the server responded with the rest of the part file
+    if let Some(part) = existing
+        && stream.offset() > 0
+    {
+        if stream.offset() == part.downloaded_size {
+            let existing_crc = assetbundle::crc32(&part.path, part.decrypted).await?;
+            *downloaded += part.downloaded_size;
+            progress.add(part.downloaded_size);
+
+            let part_writer = PartWriter {
+                writer: BufWriter::new(File::options().append(true).open(&part.path).await?),
+                decrypter: None,
+                hasher: crc32fast::Hasher::new_with_initial_len(existing_crc, part.downloaded_size),
+            };
+            let crc = write_stream(&mut stream, part_writer, progress, downloaded).await?;
+            return Ok((part.path, crc));
+        }
+
+        // any other range cannot be appended, so the download starts over
+        stream = request_stream(0).await?;
+    }
+
+    // remove the part files of earlier attempts, since the assetbundle may be written to either
+    for decrypted in [false, true] {
+        let path = part_path(out_path, decrypted);
+        if path.exists() {
+            remove_file(&path).await?;
+        }
+    }
+
+    // the magic determines whether the assetbundle is encrypted, and so which part file it is written to
+    let magic_size = assetbundle::SEKAI_ASSETBUNDLE_MAGIC.len();
+    let mut first_chunk = Vec::new();
+    while first_chunk.len() < magic_size
+        && let Some(chunk) = stream.chunk().await?
+    {
+        first_chunk.extend_from_slice(&chunk);
+    }
+    let decrypted = decrypt && first_chunk.starts_with(assetbundle::SEKAI_ASSETBUNDLE_MAGIC);
+    let path = part_path(out_path, decrypted);
+    write_file(part_hash_path(out_path), bundle.hash.as_bytes()).await?;
+
+    let mut part_writer = PartWriter {
+        writer: BufWriter::new(create_file(&path).await?),
+        decrypter: decrypted.then(assetbundle::StreamDecrypter::new),
+        hasher: crc32fast::Hasher::new(),
+    };
+    part_writer.write(&first_chunk).await?;
+    *downloaded += first_chunk.len() as u64;
+    progress.add(first_chunk.len() as u64);
+
+    let crc = write_stream(&mut stream, part_writer, progress, downloaded).await?;
+    Ok((path, crc))
+}
+
+/// Writes the remaining chunks of an assetbundle stream with a PartWriter.
+///
+/// Returns the CRC32 of the downloaded bytes.
+async fn write_stream(
+    stream: &mut AssetbundleStream,
+    mut part_writer: PartWriter,
+    progress: &DownloadProgress<'_>,
+    downloaded: &mut u64,
+) -> Result<u32, Error> {
+    while let Some(chunk) = stream.chunk().await? {
+        part_writer.write(&chunk).await?;
+
+        let chunk_size = chunk.len() as u64;
+        *downloaded += chunk_size;
+        progress.add(chunk_size);
+    }
+
+    part_writer.finish().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::retry_policy::RetryPolicy;
+    use crate::test_utils::empty_bundle;
+    use std::{io::Cursor, time::Duration};
+    use twintail_common::{
+        crypto::aes_msgpack,
+        models::enums::{Platform, Server},
+    };
+    use twintail_sekai::{
+        models::AppInfo,
+        url::{server_provider::ServerUrlProvider, template_provider::TemplateUrlProvider},
+    };
+
+    fn get_bundle(bundle_name: &str, dependencies: &[&str]) -> (String, Assetbundle) {
+        (
+            bundle_name.into(),
+            Assetbundle {
+                dependencies: dependencies.iter().map(|dep| dep.to_string()).collect(),
+                ..empty_bundle(bundle_name)
+            },
+        )
+    }
+
+    #[test]
+    fn test_get_assetbundles_with_dependencies() {
+        let bundles = HashMap::from([
+            get_bundle("model", &["texture", "material"]),
+            get_bundle("material", &["shader", "missing"]),
+            get_bundle("texture", &[]),
+            get_bundle("shader", &["material"]),
+            get_bundle("unrelated", &["shader"]),
+        ]);
+
+        let mut with_dependencies: Vec<String> =
+            get_assetbundles_with_dependencies(&bundles, HashSet::from(["model".to_string()]))
+                .into_iter()
+                .collect();
+        with_dependencies.sort();
+
+        assert_eq!(
+            with_dependencies,
+            vec!["material", "model", "shader", "texture"]
+        );
+    }
+
+    #[test]
+    fn test_download_ab_plan_round_trip() -> Result<(), Error> {
+        let plan = get_download_ab_plan();
+
+        // a saved plan keeps every option that it is executed with
+        let serialized = serde_json::to_string(&plan)?;
+        let loaded: DownloadAbPlan = serde_json::from_str(&serialized)?;
+        assert_eq!(loaded.server, Server::Japan);
+        assert_eq!(loaded.server_id, plan.server_id);
+        assert_eq!(loaded.bundles[0].bundle, plan.bundles[0].bundle);
+        assert_eq!(loaded.bundles[0].out_path, plan.bundles[0].out_path);
+        assert_eq!(loaded.prune_paths, plan.prune_paths);
+        assert_eq!(loaded.prune_mode, plan.prune_mode);
+        assert!(!loaded.verify);
+        assert!(!loaded.decrypt);
+        assert_eq!(loaded.manifest, plan.manifest);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_ab_info_maintenance() -> Result<(), Error> {
+        let mut server = mockito::Server::new_async().await;
+        let mut fetcher = get_fetcher(&server.url()).await;
+        let system_info = SystemInfo {
+            server_date: 1730780277695,
+            timezone: "Asia/Tokyo".into(),
+            profile: "production".into(),
+            maintenance_status: SystemInfo::MAINTENANCE_IN.into(),
+            app_versions: Vec::new(),
+        };
+        let system_mock = server
+            .mock("GET", "/api/system")
+            .with_body(aes_msgpack::into_vec(&system_info, &fetcher.config.aes_config).unwrap())
+            .create_async()
+            .await;
+        let info_mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        // maintenance is reported even if no values have to be retrieved by logging in
+        let result = fetcher
+            .get_ab_info(
+                Some("4.0.5.10".into()),
+                Some("asset_hash".into()),
+                Some("host_hash".into()),
+            )
+            .await;
+        assert!(matches!(result, Err(Error::Maintenance)));
+        system_mock.assert_async().await;
+        info_mock.assert_async().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_execute_ab_plan_custom_server_mismatch() -> Result<(), Error> {
+        let fetcher = get_fetcher("http://game.example.com").await;
+        let mut plan = get_download_ab_plan();
+        plan.server = Server::Custom;
+        plan.server_id = AccountServer::new(
+            Server::Custom,
+            &TemplateUrlProvider::from_toml(
+                "[hosts]\ngame = \"http://other.example.com\"\nassetbundle = \"http://other.example.com\"\n",
+            )
+            .unwrap(),
+        );
+
+        // a plan for a different custom server is not executed
+        let result = fetcher.execute_ab_plan(plan).await;
+        assert!(matches!(result, Err(Error::ServerMismatch(_))));
+
+        Ok(())
+    }
+
+    const BUNDLE_NAME: &str = "bundle";
+    const BUNDLE_PATH: &str = "/4.0.5.10/asset_hash/android/bundle";
+
+    fn get_path_args() -> AssetbundlePathArgs {
+        AssetbundlePathArgs {
+            asset_version: "4.0.5.10".into(),
+            asset_hash: "asset_hash".into(),
+            host_hash: "host_hash".into(),
+        }
+    }
+
+    fn get_download_ab_plan() -> DownloadAbPlan {
+        let bundle = get_download_bundle(b"bundle data");
+        let info = AssetbundleInfo {
+            version: "4.0.5.10".into(),
+            os: "android".into(),
+            hash: Some("asset_hash".into()),
+            host_hash: None,
+            bundles: HashMap::from([(BUNDLE_NAME.into(), get_download_bundle(b"bundle data"))]),
+        };
+        DownloadAbPlan {
+            server: Server::Japan,
+            server_id: AccountServer::new(
+                Server::Japan,
+                &ServerUrlProvider::try_from(Server::Japan).unwrap(),
+            ),
+            platform: Platform::Android,
+            out_dir: "bundles".into(),
+            asset_version: info.version.clone(),
+            asset_hash: info.hash.clone(),
+            host_hash: None,
+            bundles: vec![PlannedBundle {
+                bundle,
+                out_path: "bundles/bundle".into(),
+            }],
+            total_size: 11,
+            available_space: 39,
+            prune_paths: vec!["bundles/removed".into()],
+            prune_mode: Some(PruneMode::Trash("trash".into())),
+            verify: false,
+            decrypt: false,
+            manifest: Some(DownloadManifest::new(
+                Server::Japan,
+                Platform::Android,
+                &info,
+            )),
+        }
+    }
+
+    /// Returns a fetcher for a custom server whose hosts are all server_url.
+    async fn get_fetcher(server_url: &str) -> Fetcher<ServerUrlProvider> {
+        let url_provider = TemplateUrlProvider::from_toml(&format!(
+            "[hosts]\ngame = \"{server_url}\"\nassetbundle = \"{server_url}\"\n"
+        ))
+        .unwrap();
+        let mut config =
+            FetchConfig::new_with_provider(ServerUrlProvider::Custom(Box::new(url_provider)));
+        config.server = Server::Custom;
+        config.hash = Some("3.9".into());
+        config.version = Some("393939".into());
+
+        Fetcher::new(config).await.unwrap().0
+    }
+
+    async fn get_client(server_url: &str) -> SekaiClient<TemplateUrlProvider> {
+        let url_provider = TemplateUrlProvider::from_toml(&format!(
+            "[hosts]\ngame = \"{server_url}\"\nassetbundle = \"{server_url}\"\n"
+        ))
+        .unwrap();
+
+        SekaiClient::new(
+            AppInfo {
+                app_hash: "3.9".into(),
+                app_version: "393939".into(),
+            },
+            Server::Japan.get_aes_config(),
+            Server::Japan.get_jwt_key(),
+            Platform::Android,
+            DeviceProfile::android(),
+            url_provider,
+            reqwest::Client::new(),
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Returns a decrypted assetbundle, and the encrypted assetbundle that the server responds with.
+    async fn get_bundle_data() -> (Vec<u8>, Vec<u8>) {
+        let mut decrypted = b"UnityFS".to_vec();
+        decrypted.extend((0..5000u32).map(|i| (i % 251) as u8));
+        let encrypted = assetbundle::encrypt(&mut Cursor::new(decrypted.clone()))
+            .await
+            .unwrap();
+        (decrypted, encrypted)
+    }
+
+    fn get_download_bundle(data: &[u8]) -> Assetbundle {
+        let (_, mut bundle) = get_bundle(BUNDLE_NAME, &[]);
+        bundle.hash = "bundle_hash".into();
+        bundle.crc = crc32fast::hash(data);
+        bundle.file_size = data.len() as u64;
+        bundle
+    }
+
+    #[tokio::test]
+    async fn test_download_bundle_decrypt() -> Result<(), Error> {
+        let (decrypted, encrypted) = get_bundle_data().await;
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", BUNDLE_PATH)
+            .with_status(200)
+            .with_body(&encrypted)
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        let dir = tempfile::tempdir()?;
+        let out_path = dir.path().join(BUNDLE_NAME);
+        let (state_sender, _) = state::channel();
+        let progress = DownloadProgress::new(&state_sender);
+
+        download_bundle(
+            &client,
+            &get_download_bundle(&encrypted),
+            &out_path,
+            &get_path_args(),
+            true,
+            true,
+            &progress,
         )
         .await?;
 
-    // decrypt if desired
-    if decrypt {
-        // if the asset bundle wasn't encrypted, just ignore the error since it's already decrypted.
-        match assetbundle::decrypt_in_place(&mut ab_data).await {
-            Ok(_) => Ok(()),
-            Err(Error::NotEncrypted) => Ok(()),
-            Err(err) => Err(err),
-        }?;
+        // the assetbundle is decrypted as it is downloaded, without leaving any part files behind
+        mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, decrypted);
+        assert!(!part_path(&out_path, false).exists());
+        assert!(!part_path(&out_path, true).exists());
+        assert!(!part_hash_path(&out_path).exists());
+        assert_eq!(
+            progress.downloaded.load(Ordering::Relaxed),
+            encrypted.len() as u64
+        );
+
+        Ok(())
     }
 
-    // write file
-    write_file(out_path, &ab_data).await?;
-    Ok(())
+    #[tokio::test]
+    async fn test_download_bundle_resume_decrypted() -> Result<(), Error> {
+        let (decrypted, encrypted) = get_bundle_data().await;
+        let magic_size = assetbundle::SEKAI_ASSETBUNDLE_MAGIC.len();
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", BUNDLE_PATH)
+            .match_header("range", "bytes=1004-")
+            .with_status(206)
+            .with_header(
+                "content-range",
+                &format!("bytes 1004-{}/{}", encrypted.len() - 1, encrypted.len()),
+            )
+            .with_body(&encrypted[1000 + magic_size..])
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        // an interrupted download that was decrypted as it was received
+        let dir = tempfile::tempdir()?;
+        let out_path = dir.path().join(BUNDLE_NAME);
+        tokio::fs::write(part_path(&out_path, true), &decrypted[..1000]).await?;
+        tokio::fs::write(part_hash_path(&out_path), "bundle_hash").await?;
+        let (state_sender, _) = state::channel();
+
+        download_bundle(
+            &client,
+            &get_download_bundle(&encrypted),
+            &out_path,
+            &get_path_args(),
+            true,
+            true,
+            &DownloadProgress::new(&state_sender),
+        )
+        .await?;
+
+        mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, decrypted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_bundle_range_mismatch() -> Result<(), Error> {
+        let (_, encrypted) = get_bundle_data().await;
+        let mut server = mockito::Server::new_async().await;
+
+        // the server responds with a different range than the one that was requested
+        let range_mock = server
+            .mock("GET", BUNDLE_PATH)
+            .match_header("range", "bytes=1000-")
+            .with_status(206)
+            .with_header(
+                "content-range",
+                &format!("bytes 500-{}/{}", encrypted.len() - 1, encrypted.len()),
+            )
+            .with_body(&encrypted[500..])
+            .create_async()
+            .await;
+        let full_mock = server
+            .mock("GET", BUNDLE_PATH)
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(&encrypted)
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        let dir = tempfile::tempdir()?;
+        let out_path = dir.path().join(BUNDLE_NAME);
+        tokio::fs::write(part_path(&out_path, false), &encrypted[..1000]).await?;
+        tokio::fs::write(part_hash_path(&out_path), "bundle_hash").await?;
+        let (state_sender, _) = state::channel();
+
+        download_bundle(
+            &client,
+            &get_download_bundle(&encrypted),
+            &out_path,
+            &get_path_args(),
+            false,
+            true,
+            &DownloadProgress::new(&state_sender),
+        )
+        .await?;
+
+        // the download starts over instead of appending the wrong range
+        range_mock.assert_async().await;
+        full_mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, encrypted);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_bundle_part_hash_mismatch() -> Result<(), Error> {
+        let (_, encrypted) = get_bundle_data().await;
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", BUNDLE_PATH)
+            .match_header("range", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(&encrypted)
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        // a part file left over from an older version of the assetbundle
+        let dir = tempfile::tempdir()?;
+        let out_path = dir.path().join(BUNDLE_NAME);
+        tokio::fs::write(part_path(&out_path, false), vec![0u8; 1000]).await?;
+        tokio::fs::write(part_hash_path(&out_path), "old_bundle_hash").await?;
+        let (state_sender, _) = state::channel();
+
+        download_bundle(
+            &client,
+            &get_download_bundle(&encrypted),
+            &out_path,
+            &get_path_args(),
+            false,
+            false,
+            &DownloadProgress::new(&state_sender),
+        )
+        .await?;
+
+        // the download starts over instead of completing the old part file
+        mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, encrypted);
+        assert!(!part_hash_path(&out_path).exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_verify_part_file() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+        let part_path = dir.path().join("bundle.part");
+        let data = b"bundle data";
+        let bundle = get_download_bundle(data);
+
+        // a matching part file is kept
+        tokio::fs::write(&part_path, data).await?;
+        verify_part_file(
+            &bundle,
+            &part_path,
+            data.len() as u64,
+            crc32fast::hash(data),
+        )
+        .await?;
+        assert!(part_path.exists());
+
+        // a part file with the wrong size is removed
+        let result = verify_part_file(&bundle, &part_path, 4, crc32fast::hash(data)).await;
+        assert!(matches!(result, Err(Error::CorruptAssetbundle(_))));
+        assert!(!part_path.exists());
+
+        // a part file with the wrong CRC is removed
+        tokio::fs::write(&part_path, data).await?;
+        let result = verify_part_file(&bundle, &part_path, data.len() as u64, 0).await;
+        assert!(matches!(result, Err(Error::CorruptAssetbundle(_))));
+        assert!(!part_path.exists());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_bundle_served_crc() -> Result<(), Error> {
+        let (decrypted, encrypted) = get_bundle_data().await;
+        let mut server = mockito::Server::new_async().await;
+        let bundle_mock = server
+            .mock("GET", BUNDLE_PATH)
+            .with_status(200)
+            .with_body(&encrypted)
+            .create_async()
+            .await;
+        let check_mock = server
+            .mock("GET", "/4.0.5.10/asset_hash/android/check")
+            .with_status(200)
+            .with_body("123456789")
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        let dir = tempfile::tempdir()?;
+        let (state_sender, _) = state::channel();
+        let progress = DownloadProgress::new(&state_sender);
+
+        // the CRC of an assetbundle info is the CRC-32 of the encrypted bytes that are served,
+        // so the expected values were computed separately from the served bytes
+        let out_path = dir.path().join(BUNDLE_NAME);
+        let bundle = Assetbundle {
+            crc: 0x74d5fc0b,
+            file_size: 5011,
+            ..empty_bundle(BUNDLE_NAME)
+        };
+        download_bundle(
+            &client,
+            &bundle,
+            &out_path,
+            &get_path_args(),
+            true,
+            true,
+            &progress,
+        )
+        .await?;
+        bundle_mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, decrypted);
+
+        // the check value of CRC-32
+        let out_path = dir.path().join("check");
+        let bundle = Assetbundle {
+            crc: 0xcbf43926,
+            file_size: 9,
+            ..empty_bundle("check")
+        };
+        download_bundle(
+            &client,
+            &bundle,
+            &out_path,
+            &get_path_args(),
+            false,
+            true,
+            &progress,
+        )
+        .await?;
+        check_mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, b"123456789");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_bundle_corrupt_retry() -> Result<(), Error> {
+        let (_, encrypted) = get_bundle_data().await;
+        let mut server = mockito::Server::new_async().await;
+
+        // the first response is truncated, and the retry receives the entire assetbundle
+        let corrupt_mock = server
+            .mock("GET", BUNDLE_PATH)
+            .with_status(200)
+            .with_body(&encrypted[..1000])
+            .expect(1)
+            .create_async()
+            .await;
+        let valid_mock = server
+            .mock("GET", BUNDLE_PATH)
+            .with_status(200)
+            .with_body(&encrypted)
+            .expect(1)
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        let dir = tempfile::tempdir()?;
+        let out_path = dir.path().join(BUNDLE_NAME);
+        let bundle = get_download_bundle(&encrypted);
+        let path_args = get_path_args();
+        let (state_sender, _) = state::channel();
+        let progress = DownloadProgress::new(&state_sender);
+        let retry_policy = RetryPolicy::builder().delay(Duration::ZERO).build();
+
+        retry(&retry_policy, || {
+            download_bundle(
+                &client, &bundle, &out_path, &path_args, false, true, &progress,
+            )
+        })
+        .await?;
+
+        // the progress of the corrupt attempt is not counted
+        corrupt_mock.assert_async().await;
+        valid_mock.assert_async().await;
+        assert_eq!(tokio::fs::read(&out_path).await?, encrypted);
+        assert_eq!(
+            progress.downloaded.load(Ordering::Relaxed),
+            encrypted.len() as u64
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_download_bundle_corrupt() -> Result<(), Error> {
+        let (_, encrypted) = get_bundle_data().await;
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", BUNDLE_PATH)
+            .with_status(200)
+            .with_body(&encrypted[..1000])
+            .expect(3)
+            .create_async()
+            .await;
+        let client = get_client(&server.url()).await;
+
+        let dir = tempfile::tempdir()?;
+        let out_path = dir.path().join(BUNDLE_NAME);
+        let bundle = get_download_bundle(&encrypted);
+        let path_args = get_path_args();
+        let (state_sender, _) = state::channel();
+        let progress = DownloadProgress::new(&state_sender);
+        let retry_policy = RetryPolicy::builder()
+            .max_retries(2)
+            .delay(Duration::ZERO)
+            .build();
+
+        let result = retry(&retry_policy, || {
+            download_bundle(
+                &client, &bundle, &out_path, &path_args, false, true, &progress,
+            )
+        })
+        .await;
+
+        // every attempt is corrupt, so nothing is written to out_path
+        mock.assert_async().await;
+        assert!(matches!(result, Err(Error::CorruptAssetbundle(_))));
+        assert!(!out_path.exists());
+        assert!(!part_path(&out_path, false).exists());
+        assert_eq!(progress.downloaded.load(Ordering::Relaxed), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_available_space_missing_dir() -> Result<(), Error> {
+        let dir = tempfile::tempdir()?;
+        let missing_dir = dir.path().join("missing").join("nested");
+
+        // the closest existing ancestor is used instead of erroring
+        assert!(available_space(&missing_dir).is_ok());
+
+        Ok(())
+    }
 }
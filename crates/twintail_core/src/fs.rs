@@ -49,16 +49,46 @@ pub async fn scan_path(path: &Path, recursive: bool) -> Result<Vec<PathBuf>, tok
 /// If a file already exists at [`out_path`], it will be truncated with the new data.
 pub async fn write_file(out_path: impl AsRef<Path>, data: &[u8]) -> Result<(), tokio::io::Error> {
     // write file
+    let mut out_file = create_file(out_path).await?;
+    out_file.write_all(data).await?;
+    Ok(())
+}
+
+/// Creates a file at the given out_path, opened for writing.
+///
+/// Any missing directories will be created.
+/// If a file already exists at [`out_path`], it will be truncated.
+pub async fn create_file(out_path: impl AsRef<Path>) -> Result<File, tokio::io::Error> {
     if let Some(parent) = out_path.as_ref().parent() {
         create_dir_all(parent).await?;
     }
-    let mut out_file = File::options()
+    File::options()
         .write(true)
         .create(true)
         .truncate(true)
         .open(out_path)
-        .await?;
-    out_file.write_all(data).await?;
+        .await
+}
+
+/// Moves a file from from_path to to_path.
+///
+/// Any missing directories will be created.
+/// If the file cannot be renamed, for example because the paths are on different file systems,
+/// it is copied and then removed.
+pub async fn move_file(
+    from_path: impl AsRef<Path>,
+    to_path: impl AsRef<Path>,
+) -> Result<(), tokio::io::Error> {
+    let (from_path, to_path) = (from_path.as_ref(), to_path.as_ref());
+    if let Some(parent) = to_path.parent() {
+        create_dir_all(parent).await?;
+    }
+
+    if fs::rename(from_path, to_path).await.is_err() {
+        fs::copy(from_path, to_path).await?;
+        fs::remove_file(from_path).await?;
+    }
+
     Ok(())
 }
 
@@ -189,4 +219,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_move_file() -> Result<(), Error> {
+        let temp_dir = tempdir()?;
+        let from_path = temp_dir.path().join("file.txt");
+        let to_path = temp_dir.path().join("nested").join("moved.txt");
+
+        tokio::fs::write(&from_path, b"content").await?;
+        move_file(&from_path, &to_path).await?;
+
+        assert!(!from_path.exists());
+        assert_eq!(tokio::fs::read(&to_path).await?, b"content");
+
+        Ok(())
+    }
 }
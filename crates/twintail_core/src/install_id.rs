@@ -0,0 +1,60 @@
+use std::path::{Path, PathBuf};
+
+use twintail_sekai::device_profile::generate_install_id;
+
+use crate::{Error, fs::write_file};
+
+const DEFAULT_DIR_NAME: &str = "twintail";
+const DEFAULT_FILE_NAME: &str = "install_id";
+
+/// Returns the default location of the saved install id.
+///
+/// This is a file inside of the user's configuration directory.
+/// Returns None if the configuration directory could not be determined.
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(DEFAULT_DIR_NAME).join(DEFAULT_FILE_NAME))
+}
+
+/// Loads the install id that was saved to the provided path.
+///
+/// If no install id was saved yet, a new random install id is generated and saved,
+/// so that every run appears to come from the same installation of the game.
+pub async fn load_or_create(path: impl AsRef<Path>) -> Result<String, Error> {
+    let path = path.as_ref();
+
+    match tokio::fs::read_to_string(path).await {
+        Ok(install_id) if !install_id.trim().is_empty() => return Ok(install_id.trim().into()),
+        Ok(_) => {}
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    let install_id = generate_install_id();
+    write_file(path, install_id.as_bytes()).await?;
+    Ok(install_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_load_or_create() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let path = dir.path().join("nested").join("install_id");
+
+        // a new install id is generated once, then reused
+        let install_id = load_or_create(&path).await?;
+        assert_eq!(install_id.len(), 36);
+        assert_eq!(load_or_create(&path).await?, install_id);
+
+        // an empty file is replaced with a new install id
+        tokio::fs::write(&path, "").await?;
+        let new_install_id = load_or_create(&path).await?;
+        assert_ne!(new_install_id, install_id);
+        assert_eq!(load_or_create(&path).await?, new_install_id);
+
+        Ok(())
+    }
+}
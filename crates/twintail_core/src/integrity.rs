@@ -0,0 +1,136 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tokio::fs::metadata;
+use twintail_sekai::models::Assetbundle;
+
+use crate::{
+    Error,
+    crypto::assetbundle::{self, SEKAI_ASSETBUNDLE_MAGIC},
+};
+
+/// The result of comparing an assetbundle file on disk with its [`Assetbundle`] entry.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BundleFileStatus {
+    /// The file matches the expected size and CRC
+    Valid,
+    /// No file exists at the path
+    Missing,
+    /// The size of the file does not match the expected size
+    SizeMismatch { expected: u64, actual: u64 },
+    /// The CRC of the file does not match the expected CRC
+    CrcMismatch { expected: u32, actual: u32 },
+}
+
+impl BundleFileStatus {
+    /// Returns true if the file matches its expected size and CRC.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Self::Valid)
+    }
+}
+
+/// Compares the assetbundle file at the provided path with the size and CRC of its [`Assetbundle`] entry.
+///
+/// The size and CRC describe the assetbundle as it is served by the game's CDN.
+/// Files that were decrypted after downloading are compared as if they were encrypted again.
+pub async fn check_bundle_file(
+    path: &Path,
+    bundle: &Assetbundle,
+) -> Result<BundleFileStatus, Error> {
+    let file_size = match metadata(path).await {
+        Ok(metadata) => metadata.len(),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(BundleFileStatus::Missing);
+        }
+        Err(err) => return Err(err.into()),
+    };
+
+    // a decrypted assetbundle is missing the magic that the served assetbundle starts with
+    let reencrypt = if file_size == bundle.file_size {
+        false
+    } else if file_size + SEKAI_ASSETBUNDLE_MAGIC.len() as u64 == bundle.file_size
+        && !assetbundle::is_encrypted(path).await?
+    {
+        true
+    } else {
+        return Ok(BundleFileStatus::SizeMismatch {
+            expected: bundle.file_size,
+            actual: file_size,
+        });
+    };
+
+    let crc = assetbundle::crc32(path, reencrypt).await?;
+    if crc == bundle.crc {
+        Ok(BundleFileStatus::Valid)
+    } else {
+        Ok(BundleFileStatus::CrcMismatch {
+            expected: bundle.crc,
+            actual: crc,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+    use tempfile::tempdir;
+
+    fn get_bundle(data: &[u8]) -> Assetbundle {
+        Assetbundle {
+            crc: crc32fast::hash(data),
+            file_size: data.len() as u64,
+            ..empty_bundle("bundle")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_bundle_file() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let encrypted_path = dir.path().join("encrypted.bundle");
+        let decrypted_path = dir.path().join("decrypted.bundle");
+        let corrupt_path = dir.path().join("corrupt.bundle");
+        let truncated_path = dir.path().join("truncated.bundle");
+
+        let mut data = SEKAI_ASSETBUNDLE_MAGIC.to_vec();
+        data.extend((0..1000).map(|i| i as u8));
+        let bundle = get_bundle(&data);
+
+        let mut corrupt = data.clone();
+        corrupt[500] = !corrupt[500];
+
+        tokio::fs::write(&encrypted_path, &data).await?;
+        tokio::fs::write(&corrupt_path, &corrupt).await?;
+        tokio::fs::write(&truncated_path, &data[..600]).await?;
+        assetbundle::decrypt_file_chunked(&encrypted_path, &decrypted_path).await?;
+
+        assert!(
+            check_bundle_file(&encrypted_path, &bundle)
+                .await?
+                .is_valid()
+        );
+        assert!(
+            check_bundle_file(&decrypted_path, &bundle)
+                .await?
+                .is_valid()
+        );
+        assert!(matches!(
+            check_bundle_file(&corrupt_path, &bundle).await?,
+            BundleFileStatus::CrcMismatch { .. }
+        ));
+        assert_eq!(
+            check_bundle_file(&truncated_path, &bundle).await?,
+            BundleFileStatus::SizeMismatch {
+                expected: data.len() as u64,
+                actual: 600
+            }
+        );
+        assert_eq!(
+            check_bundle_file(&dir.path().join("missing.bundle"), &bundle).await?,
+            BundleFileStatus::Missing
+        );
+
+        Ok(())
+    }
+}
@@ -1,9 +1,23 @@
+pub mod abinfo;
 pub mod apk_extractor;
 pub mod config;
+pub mod credential_store;
 pub mod crypto;
 pub mod fetch;
+pub mod install_id;
+pub mod integrity;
+pub mod manifest;
+pub mod search;
+pub mod selection;
+pub mod state;
+pub mod summary;
+pub mod verify;
+pub mod watch;
 
 mod error;
 mod fs;
+mod retry;
+#[cfg(test)]
+mod test_utils;
 
 pub use error::Error;
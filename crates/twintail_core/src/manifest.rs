@@ -0,0 +1,188 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use twintail_common::models::enums::{Platform, Server};
+use twintail_sekai::models::{Assetbundle, AssetbundleInfo};
+
+use crate::{Error, fs::write_file};
+
+/// The name of the manifest file that is written to the root of an assetbundle output directory.
+pub const MANIFEST_FILE_NAME: &str = "twintail_manifest.json";
+
+/// An assetbundle that was downloaded into an output directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManifestBundle {
+    /// the hash of the assetbundle when it was downloaded
+    pub hash: String,
+    /// where the assetbundle was downloaded to, relative to the output directory
+    pub path: PathBuf,
+}
+
+/// A record of the assetbundles that have been downloaded into an output directory.
+///
+/// This allows later downloads into the same directory to only download assetbundles that changed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DownloadManifest {
+    pub server: Server,
+    pub platform: Platform,
+    /// the asset version that was most recently downloaded
+    pub asset_version: String,
+    /// the asset hash of the most recently downloaded asset version
+    pub asset_hash: Option<String>,
+    /// the host hash that assetbundles were most recently downloaded with
+    pub host_hash: Option<String>,
+    /// every assetbundle that has been downloaded, keyed by bundle name
+    pub bundles: HashMap<String, ManifestBundle>,
+}
+
+impl DownloadManifest {
+    /// Creates an empty manifest for an assetbundle info.
+    pub fn new(server: Server, platform: Platform, info: &AssetbundleInfo) -> Self {
+        Self {
+            server,
+            platform,
+            asset_version: info.version.clone(),
+            asset_hash: info.hash.clone(),
+            host_hash: info.host_hash.clone(),
+            bundles: HashMap::new(),
+        }
+    }
+
+    /// Returns the location of the manifest inside of an output directory.
+    pub fn path(dir: impl AsRef<Path>) -> PathBuf {
+        dir.as_ref().join(MANIFEST_FILE_NAME)
+    }
+
+    /// Loads the manifest from an output directory.
+    ///
+    /// Returns None if the directory does not contain a manifest.
+    pub async fn load(dir: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        match tokio::fs::read(Self::path(dir)).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the manifest to an output directory.
+    pub async fn save(&self, dir: impl AsRef<Path>) -> Result<(), Error> {
+        let serialized = serde_json::to_vec_pretty(self)?;
+        write_file(Self::path(dir), &serialized).await?;
+        Ok(())
+    }
+
+    /// Returns [`Error::ServerMismatch`] if the manifest was written for a different server or platform.
+    ///
+    /// The bundles of a manifest for another server or platform cannot be compared with the bundles of this one,
+    /// and writing over it would lose the record of that download.
+    pub fn check_server(&self, server: Server, platform: Platform) -> Result<(), Error> {
+        if self.server == server && self.platform == platform {
+            return Ok(());
+        }
+
+        Err(Error::ServerMismatch(format!(
+            "the manifest in the output directory was written for {} ({}), not for {} ({})",
+            self.server, self.platform, server, platform
+        )))
+    }
+
+    /// Returns the assetbundles that have not been downloaded, or whose hash changed since they were downloaded.
+    pub fn changed_bundles(
+        &self,
+        bundles: HashMap<String, Assetbundle>,
+    ) -> HashMap<String, Assetbundle> {
+        bundles
+            .into_iter()
+            .filter(|(bundle_name, bundle)| {
+                self.bundles
+                    .get(bundle_name)
+                    .is_none_or(|downloaded| downloaded.hash != bundle.hash)
+            })
+            .collect()
+    }
+
+    /// Sets the asset version, asset hash, and host hash of the manifest to those of an assetbundle info.
+    pub fn set_info(&mut self, info: &AssetbundleInfo) {
+        self.asset_version = info.version.clone();
+        self.asset_hash = info.hash.clone();
+        self.host_hash = info.host_hash.clone();
+    }
+
+    /// Records that an assetbundle was downloaded to a path relative to the output directory.
+    pub fn insert(&mut self, bundle: &Assetbundle, path: PathBuf) {
+        self.bundles.insert(
+            bundle.bundle_name.clone(),
+            ManifestBundle {
+                hash: bundle.hash.clone(),
+                path,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+    use tempfile::tempdir;
+
+    fn get_bundle(bundle_name: &str, hash: &str) -> Assetbundle {
+        Assetbundle {
+            hash: hash.into(),
+            ..empty_bundle(bundle_name)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manifest() -> Result<(), Error> {
+        let dir = tempdir()?;
+        assert!(DownloadManifest::load(dir.path()).await?.is_none());
+
+        let info = AssetbundleInfo {
+            version: "4.0.5.10".into(),
+            os: "android".into(),
+            hash: Some("asset_hash".into()),
+            host_hash: Some("host_hash".into()),
+            bundles: HashMap::new(),
+        };
+        let mut manifest = DownloadManifest::new(Server::Japan, Platform::Android, &info);
+        manifest.insert(&get_bundle("same", "a"), PathBuf::from("4.0.5.10/same"));
+        manifest.insert(
+            &get_bundle("changed", "a"),
+            PathBuf::from("4.0.5.10/changed"),
+        );
+        manifest.save(dir.path()).await?;
+
+        let loaded = DownloadManifest::load(dir.path()).await?.unwrap();
+        assert_eq!(loaded, manifest);
+
+        let bundles = HashMap::from([
+            ("same".to_string(), get_bundle("same", "a")),
+            ("changed".to_string(), get_bundle("changed", "b")),
+            ("new".to_string(), get_bundle("new", "a")),
+        ]);
+        let mut changed: Vec<_> = loaded.changed_bundles(bundles).into_keys().collect();
+        changed.sort();
+        assert_eq!(changed, vec!["changed", "new"]);
+
+        // a manifest of another server or platform cannot be used
+        assert!(
+            loaded
+                .check_server(Server::Japan, Platform::Android)
+                .is_ok()
+        );
+        assert!(matches!(
+            loaded.check_server(Server::Global, Platform::Android),
+            Err(Error::ServerMismatch(_))
+        ));
+        assert!(matches!(
+            loaded.check_server(Server::Japan, Platform::Ios),
+            Err(Error::ServerMismatch(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+use tokio::time::{Instant, sleep};
+
+use crate::{Error, config::retry_policy::RetryPolicy};
+
+/// Performs an operation, retrying it according to a [`RetryPolicy`] while it fails with a retryable error.
+///
+/// Returns the result of the last attempt.
+pub async fn retry<T, F, Fut>(policy: &RetryPolicy, mut operation: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let start = Instant::now();
+    let mut retries = 0;
+
+    loop {
+        let err = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(err) => err,
+        };
+
+        if retries >= policy.max_retries || !err.is_retryable() {
+            return Err(err);
+        }
+
+        // a retry never starts before the server asked for, even if that is longer than max_delay
+        let delay = err
+            .retry_after()
+            .map_or(policy.delay(retries), |retry_after| {
+                retry_after.max(policy.delay(retries))
+            });
+        if policy
+            .deadline
+            .is_some_and(|deadline| start.elapsed() + delay > deadline)
+        {
+            return Err(err);
+        }
+
+        sleep(delay).await;
+        retries += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::retry_policy::Backoff;
+    use std::{io, sync::atomic::AtomicUsize, sync::atomic::Ordering, time::Duration};
+
+    fn get_policy() -> RetryPolicy {
+        RetryPolicy::builder()
+            .max_retries(3)
+            .backoff(Backoff::Fixed)
+            .delay(Duration::from_millis(1))
+            .jitter(false)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        let policy = get_policy();
+
+        // retryable errors are retried until the operation succeeds
+        let attempts = AtomicUsize::new(0);
+        let result = retry(&policy, || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(Error::CorruptAssetbundle("bundle".into())),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // retryable errors are retried at most max_retries times
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), Error> = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::CorruptAssetbundle("bundle".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 4);
+
+        // errors that are not retryable are returned immediately
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), Error> = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::NotFound("bundle".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_io() {
+        let policy = get_policy();
+
+        // transient io errors are retried
+        let attempts = AtomicUsize::new(0);
+        let result = retry(&policy, || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(Error::Io(io::ErrorKind::TimedOut.into())),
+                1 => Err(Error::Io(io::ErrorKind::UnexpectedEof.into())),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // other io errors would occur again, so they are returned immediately
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), Error> = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::Io(io::ErrorKind::PermissionDenied.into()))
+        })
+        .await;
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    fn get_unavailable_error(retry_after: Duration) -> Error {
+        Error::Sekai(twintail_sekai::Error::Request(
+            twintail_sekai::RequestError::from_status(
+                twintail_sekai::Endpoint::Assetbundle,
+                reqwest::StatusCode::SERVICE_UNAVAILABLE,
+                Some(retry_after),
+                "unavailable".into(),
+            ),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_retry_after() {
+        let policy = RetryPolicy {
+            max_delay: Duration::from_millis(10),
+            ..get_policy()
+        };
+
+        // the delay that the server asks for is waited, even if it is longer than max_delay
+        let attempts = AtomicUsize::new(0);
+        let start = Instant::now();
+        let result = retry(&policy, || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 => Err(get_unavailable_error(Duration::from_millis(100))),
+                _ => Ok(()),
+            }
+        })
+        .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // a delay that would pass the deadline stops retrying instead of retrying early
+        let policy = RetryPolicy {
+            deadline: Some(Duration::from_secs(1)),
+            ..policy
+        };
+        let attempts = AtomicUsize::new(0);
+        let start = Instant::now();
+        let result: Result<(), Error> = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(get_unavailable_error(Duration::from_secs(86400)))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_retry_deadline() {
+        let policy = RetryPolicy {
+            delay: Duration::from_secs(60),
+            deadline: Some(Duration::from_secs(1)),
+            ..get_policy()
+        };
+
+        // a retry that would start after the deadline is not attempted
+        let attempts = AtomicUsize::new(0);
+        let result: Result<(), Error> = retry(&policy, || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::CorruptAssetbundle("bundle".into()))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_retry_policy_delay() {
+        let policy = RetryPolicy::builder()
+            .backoff(Backoff::Exponential)
+            .delay(Duration::from_millis(100))
+            .max_delay(Duration::from_millis(500))
+            .jitter(false)
+            .build();
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        assert_eq!(policy.delay(3), Duration::from_millis(500));
+        assert_eq!(policy.delay(100), Duration::from_millis(500));
+
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        let delay = policy.delay(0);
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+    }
+}
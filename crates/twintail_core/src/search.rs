@@ -0,0 +1,281 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use twintail_common::models::enums::AssetbundleCategory;
+use twintail_sekai::models::AssetbundleInfo;
+
+use crate::{Error, fs::write_file};
+
+const DEFAULT_DIR_NAME: &str = "twintail";
+const DEFAULT_FILE_NAME: &str = "search_index.json";
+
+/// An assetbundle in a search index.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexedBundle {
+    /// the earliest indexed asset version that contains the assetbundle
+    pub first_version: String,
+    /// the latest indexed asset version that contains the assetbundle
+    pub latest_version: String,
+    /// the category of the assetbundle in the latest version
+    pub category: AssetbundleCategory,
+    /// the file size of the assetbundle in the latest version
+    pub file_size: u64,
+    /// the asset paths contained in the assetbundle in the latest version
+    pub paths: Vec<String>,
+}
+
+/// How a search query is matched against assetbundle names and asset paths.
+#[derive(Debug, Clone)]
+pub enum SearchQuery {
+    /// matches text that contains the query
+    Substring(String),
+    /// matches text that starts with the query
+    Prefix(String),
+    /// matches text that matches the regular expression
+    Regex(Regex),
+}
+
+impl SearchQuery {
+    /// Creates a query that matches text containing the query.
+    pub fn substring(query: String) -> Self {
+        Self::Substring(query)
+    }
+
+    /// Creates a query that matches text starting with the query.
+    pub fn prefix(query: String) -> Self {
+        Self::Prefix(query)
+    }
+
+    /// Creates a query that matches text matching a regular expression.
+    ///
+    /// Returns an error if the query is not a valid regular expression.
+    pub fn regex(query: &str) -> Result<Self, Error> {
+        Ok(Self::Regex(Regex::new(query)?))
+    }
+
+    /// Returns true if the text matches the query.
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Substring(query) => text.contains(query.as_str()),
+            Self::Prefix(query) => text.starts_with(query.as_str()),
+            Self::Regex(re) => re.is_match(text),
+        }
+    }
+}
+
+/// An assetbundle whose name or asset paths matched a search query.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub bundle_name: String,
+    /// the earliest indexed asset version that contains the assetbundle
+    pub first_version: String,
+    pub category: AssetbundleCategory,
+    pub file_size: u64,
+    /// the asset paths of the assetbundle that matched, empty if only the name matched
+    pub matched_paths: Vec<String>,
+}
+
+/// A local index of the assetbundle names and asset paths of one or more assetbundle infos.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchIndex {
+    /// the asset versions that have been indexed
+    pub versions: BTreeSet<String>,
+    /// every indexed assetbundle, by assetbundle name
+    pub bundles: BTreeMap<String, IndexedBundle>,
+}
+
+impl SearchIndex {
+    /// Returns the default location of the search index, inside of the user's data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(DEFAULT_DIR_NAME).join(DEFAULT_FILE_NAME))
+    }
+
+    /// Loads a search index from a file.
+    ///
+    /// Returns None if the file does not exist.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Option<Self>, Error> {
+        match tokio::fs::read(path).await {
+            Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the search index to a file.
+    pub async fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        let serialized = serde_json::to_vec(self)?;
+        write_file(path, &serialized).await?;
+        Ok(())
+    }
+
+    /// Adds the assetbundles of an assetbundle info to the index.
+    ///
+    /// Infos can be added in any order. The details of an assetbundle are taken from the latest
+    /// asset version that contains it, and its first version from the earliest.
+    pub fn add_info(&mut self, info: &AssetbundleInfo) {
+        self.versions.insert(info.version.clone());
+
+        for (bundle_name, bundle) in &info.bundles {
+            let indexed =
+                self.bundles
+                    .entry(bundle_name.clone())
+                    .or_insert_with(|| IndexedBundle {
+                        first_version: info.version.clone(),
+                        latest_version: info.version.clone(),
+                        category: bundle.category,
+                        file_size: bundle.file_size,
+                        paths: bundle.paths.clone(),
+                    });
+
+            if compare_versions(&info.version, &indexed.first_version) == Ordering::Less {
+                indexed.first_version = info.version.clone();
+            }
+            if compare_versions(&info.version, &indexed.latest_version) != Ordering::Less {
+                indexed.latest_version = info.version.clone();
+                indexed.category = bundle.category;
+                indexed.file_size = bundle.file_size;
+                indexed.paths = bundle.paths.clone();
+            }
+        }
+    }
+
+    /// Returns the assetbundles whose name, or one of whose asset paths, matches the query, sorted by name.
+    pub fn search(&self, query: &SearchQuery) -> Vec<SearchMatch> {
+        self.bundles
+            .iter()
+            .filter_map(|(bundle_name, indexed)| {
+                let matched_paths: Vec<String> = indexed
+                    .paths
+                    .iter()
+                    .filter(|path| query.matches(path))
+                    .cloned()
+                    .collect();
+
+                (query.matches(bundle_name) || !matched_paths.is_empty()).then(|| SearchMatch {
+                    bundle_name: bundle_name.clone(),
+                    first_version: indexed.first_version.clone(),
+                    category: indexed.category,
+                    file_size: indexed.file_size,
+                    matched_paths,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Compares two asset versions, such as ``4.0.5.10``, by each of their numeric components.
+///
+/// Components that are not numbers are compared as text.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut a_parts = a.split('.');
+    let mut b_parts = b.split('.');
+
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_part), Some(b_part)) => {
+                let ordering = match (a_part.parse::<u64>(), b_part.parse::<u64>()) {
+                    (Ok(a_num), Ok(b_num)) => a_num.cmp(&b_num),
+                    _ => a_part.cmp(b_part),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+    use tempfile::tempdir;
+    use twintail_sekai::models::Assetbundle;
+
+    fn get_info(version: &str, bundles: &[(&str, u64, &[&str])]) -> AssetbundleInfo {
+        AssetbundleInfo {
+            version: version.into(),
+            os: "android".into(),
+            hash: None,
+            host_hash: None,
+            bundles: bundles
+                .iter()
+                .map(|(bundle_name, file_size, paths)| {
+                    (
+                        bundle_name.to_string(),
+                        Assetbundle {
+                            file_size: *file_size,
+                            paths: paths.iter().map(|path| path.to_string()).collect(),
+                            ..empty_bundle(bundle_name)
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("4.0.10.0", "4.0.9.1"), Ordering::Greater);
+        assert_eq!(compare_versions("4.0.5", "4.0.5.0"), Ordering::Less);
+        assert_eq!(compare_versions("4.0.5.10", "4.0.5.10"), Ordering::Equal);
+    }
+
+    #[tokio::test]
+    async fn test_search_index() -> Result<(), Error> {
+        let mut index = SearchIndex::default();
+        // infos are added out of order
+        index.add_info(&get_info(
+            "4.0.10.0",
+            &[
+                ("character/member/01miku", 200, &["assets/01miku.prefab"]),
+                ("music/long/0001", 50, &["assets/0001.acb"]),
+            ],
+        ));
+        index.add_info(&get_info(
+            "4.0.9.0",
+            &[("character/member/01miku", 100, &["assets/01miku.prefab"])],
+        ));
+
+        let matches = index.search(&SearchQuery::substring("01miku".into()));
+        assert_eq!(
+            matches,
+            vec![SearchMatch {
+                bundle_name: "character/member/01miku".into(),
+                first_version: "4.0.9.0".into(),
+                category: AssetbundleCategory::OnDemand,
+                file_size: 200,
+                matched_paths: vec!["assets/01miku.prefab".into()],
+            }]
+        );
+
+        let matches = index.search(&SearchQuery::prefix("music/".into()));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].first_version, "4.0.10.0");
+        assert!(matches[0].matched_paths.is_empty());
+
+        let matches = index.search(&SearchQuery::regex(r"\.(acb|prefab)$")?);
+        assert_eq!(matches.len(), 2);
+        assert!(SearchQuery::regex("(").is_err());
+
+        // the index can be saved and loaded
+        let temp_dir = tempdir()?;
+        let index_path = temp_dir.path().join("index.json");
+        assert_eq!(SearchIndex::load(&index_path).await?, None);
+        index.save(&index_path).await?;
+        assert_eq!(SearchIndex::load(&index_path).await?, Some(index));
+
+        Ok(())
+    }
+}
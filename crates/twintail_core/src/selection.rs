@@ -0,0 +1,232 @@
+use regex::Regex;
+use twintail_common::models::{OptionalBuilder, enums::AssetbundleCategory};
+use twintail_sekai::models::Assetbundle;
+
+use crate::Error;
+
+/// Decides which assetbundles are selected for an operation.
+///
+/// An empty selection selects every assetbundle.
+#[derive(Debug, Clone, Default)]
+pub struct BundleSelection {
+    /// if not empty, an assetbundle's name must match at least one of these patterns
+    pub include: Vec<Regex>,
+    /// an assetbundle's name must not match any of these patterns
+    pub exclude: Vec<Regex>,
+    /// if not empty, an assetbundle must be in one of these categories
+    pub categories: Vec<AssetbundleCategory>,
+    /// an assetbundle must not be in any of these categories
+    pub exclude_categories: Vec<AssetbundleCategory>,
+    /// the minimum file size of an assetbundle, in bytes
+    pub min_size: Option<u64>,
+    /// the maximum file size of an assetbundle, in bytes
+    pub max_size: Option<u64>,
+    /// if not empty, at least one of an assetbundle's asset paths must match at least one of these patterns
+    pub paths: Vec<Regex>,
+    /// if set, an assetbundle's is_builtin value must be equal to this
+    pub builtin: Option<bool>,
+}
+
+impl BundleSelection {
+    /// Create a default builder for the BundleSelection struct.
+    pub fn builder() -> BundleSelectionBuilder {
+        BundleSelectionBuilder::default()
+    }
+
+    /// Returns true if the assetbundle is selected.
+    pub fn matches(&self, bundle: &Assetbundle) -> bool {
+        let name = bundle.bundle_name.as_str();
+
+        (self.include.is_empty() || self.include.iter().any(|re| re.is_match(name)))
+            && !self.exclude.iter().any(|re| re.is_match(name))
+            && (self.categories.is_empty() || self.categories.contains(&bundle.category))
+            && !self.exclude_categories.contains(&bundle.category)
+            && self.min_size.is_none_or(|min| bundle.file_size >= min)
+            && self.max_size.is_none_or(|max| bundle.file_size <= max)
+            && (self.paths.is_empty()
+                || bundle
+                    .paths
+                    .iter()
+                    .any(|path| self.paths.iter().any(|re| re.is_match(path))))
+            && self
+                .builtin
+                .is_none_or(|builtin| bundle.is_builtin == builtin)
+    }
+}
+
+/// Builder for BundleSelection
+///
+/// Patterns are compiled when [`BundleSelectionBuilder::build`] is called.
+#[derive(Default)]
+pub struct BundleSelectionBuilder {
+    include: Vec<String>,
+    exclude: Vec<String>,
+    paths: Vec<String>,
+    selection: BundleSelection,
+}
+
+impl OptionalBuilder for BundleSelectionBuilder {}
+
+impl BundleSelectionBuilder {
+    /// Only assetbundles whose name matches this regular expression, or another include pattern, will be selected.
+    pub fn include(mut self, pattern: String) -> Self {
+        self.include.push(pattern);
+        self
+    }
+
+    /// Assetbundles whose name matches this regular expression will not be selected.
+    pub fn exclude(mut self, pattern: String) -> Self {
+        self.exclude.push(pattern);
+        self
+    }
+
+    /// Only assetbundles in this category, or another included category, will be selected.
+    pub fn category(mut self, category: AssetbundleCategory) -> Self {
+        self.selection.categories.push(category);
+        self
+    }
+
+    /// Assetbundles in this category will not be selected.
+    pub fn exclude_category(mut self, category: AssetbundleCategory) -> Self {
+        self.selection.exclude_categories.push(category);
+        self
+    }
+
+    /// Assetbundles smaller than this number of bytes will not be selected.
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.selection.min_size = Some(min_size);
+        self
+    }
+
+    /// Assetbundles larger than this number of bytes will not be selected.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.selection.max_size = Some(max_size);
+        self
+    }
+
+    /// Only assetbundles that contain an asset whose path matches this regular expression,
+    /// or another path pattern, will be selected.
+    pub fn path(mut self, pattern: String) -> Self {
+        self.paths.push(pattern);
+        self
+    }
+
+    /// If true, only builtin assetbundles will be selected. If false, only assetbundles that are not builtin will be selected.
+    pub fn builtin(mut self, builtin: bool) -> Self {
+        self.selection.builtin = Some(builtin);
+        self
+    }
+
+    /// Returns the BundleSelection that was constructed.
+    ///
+    /// Returns an error if any of the provided patterns are not valid regular expressions.
+    pub fn build(self) -> Result<BundleSelection, Error> {
+        let compile = |patterns: Vec<String>| -> Result<Vec<Regex>, Error> {
+            Ok(patterns
+                .iter()
+                .map(|pattern| Regex::new(pattern))
+                .collect::<Result<_, _>>()?)
+        };
+
+        Ok(BundleSelection {
+            include: compile(self.include)?,
+            exclude: compile(self.exclude)?,
+            paths: compile(self.paths)?,
+            ..self.selection
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+
+    fn get_bundle(
+        bundle_name: &str,
+        category: AssetbundleCategory,
+        file_size: u64,
+        paths: &[&str],
+        is_builtin: bool,
+    ) -> Assetbundle {
+        Assetbundle {
+            category,
+            file_size,
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            is_builtin,
+            ..empty_bundle(bundle_name)
+        }
+    }
+
+    #[test]
+    fn test_bundle_selection() -> Result<(), Error> {
+        let model = get_bundle(
+            "live_pv/model/character/01miku",
+            AssetbundleCategory::OnDemand,
+            1000,
+            &["assets/sekai/character/01miku.prefab"],
+            false,
+        );
+        let voice = get_bundle(
+            "sound/scenario/voice/01miku",
+            AssetbundleCategory::AdditionalVoice,
+            100,
+            &[],
+            false,
+        );
+        let builtin = get_bundle(
+            "live_pv/model/character/common",
+            AssetbundleCategory::StartApp,
+            10,
+            &[],
+            true,
+        );
+
+        // an empty selection selects everything
+        let selection = BundleSelection::builder().build()?;
+        assert!(
+            [&model, &voice, &builtin]
+                .iter()
+                .all(|b| selection.matches(b))
+        );
+
+        let selection = BundleSelection::builder()
+            .include("^live_pv/".into())
+            .exclude("common$".into())
+            .build()?;
+        assert!(selection.matches(&model));
+        assert!(!selection.matches(&voice));
+        assert!(!selection.matches(&builtin));
+
+        let selection = BundleSelection::builder()
+            .exclude_category(AssetbundleCategory::AdditionalVoice)
+            .min_size(50)
+            .build()?;
+        assert!(selection.matches(&model));
+        assert!(!selection.matches(&voice));
+        assert!(!selection.matches(&builtin));
+
+        let selection = BundleSelection::builder()
+            .category(AssetbundleCategory::StartApp)
+            .max_size(100)
+            .builtin(true)
+            .build()?;
+        assert!(!selection.matches(&model));
+        assert!(!selection.matches(&voice));
+        assert!(selection.matches(&builtin));
+
+        let selection = BundleSelection::builder()
+            .path(r"\.prefab$".into())
+            .build()?;
+        assert!(selection.matches(&model));
+        assert!(!selection.matches(&voice));
+
+        // invalid patterns are an error
+        assert!(matches!(
+            BundleSelection::builder().include("(".into()).build(),
+            Err(Error::Regex(_))
+        ));
+
+        Ok(())
+    }
+}
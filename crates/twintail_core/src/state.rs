@@ -0,0 +1,62 @@
+use tokio::sync::mpsc;
+
+/// Receives every state that an operation reports, in the order that they were sent.
+///
+/// States are kept until they are received, so a receiver that is not read should be dropped.
+pub type StateReceiver<S> = mpsc::UnboundedReceiver<S>;
+
+/// Reports the states of an operation to a [`StateReceiver`].
+///
+/// Unlike a [`tokio::sync::watch`] channel, no state is dropped if the receiver falls behind,
+/// so that receivers can count states such as the files that were processed.
+#[derive(Debug, Clone)]
+pub struct StateSender<S>(mpsc::UnboundedSender<S>);
+
+impl<S> StateSender<S> {
+    /// Sends a state to the receiver.
+    ///
+    /// Operations do not depend on their states being received, so nothing happens if the receiver was dropped.
+    pub fn send(&self, state: S) {
+        let _ = self.0.send(state);
+    }
+}
+
+impl<S> Default for StateSender<S> {
+    /// Creates a sender whose receiver was dropped.
+    fn default() -> Self {
+        channel().0
+    }
+}
+
+/// Creates a channel that an operation reports its states through.
+pub fn channel<S>() -> (StateSender<S>, StateReceiver<S>) {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    (StateSender(sender), receiver)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_state_channel() {
+        let (sender, mut receiver) = channel();
+
+        // states sent faster than they are received are all kept, in order
+        for state in 0..100 {
+            sender.send(state);
+        }
+        drop(sender);
+
+        let mut received = Vec::new();
+        while let Some(state) = receiver.recv().await {
+            received.push(state);
+        }
+        assert_eq!(received, (0..100).collect::<Vec<_>>());
+
+        // sending to a dropped receiver is ignored
+        let (sender, receiver) = channel();
+        drop(receiver);
+        sender.send(0);
+    }
+}
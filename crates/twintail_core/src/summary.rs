@@ -0,0 +1,143 @@
+use std::{path::PathBuf, time::Duration};
+
+use serde::{Serialize, Serializer};
+
+use crate::{fetch::DownloadAbResult, verify::VerifyReport};
+
+/// A machine-readable summary of a finished operation.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Summary {
+    /// the number of files or items that were processed successfully
+    pub success_count: usize,
+    /// the number of files or items that were available to process
+    pub total_count: usize,
+    /// how long the operation took, serialized in milliseconds
+    #[serde(rename = "durationMs", serialize_with = "serialize_millis")]
+    pub duration: Duration,
+    /// the version of the data that was processed, if it has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// descriptions of the files or items that could not be processed
+    pub failures: Vec<String>,
+    /// the files or directories that were written to
+    pub paths: Vec<PathBuf>,
+}
+
+impl Summary {
+    /// Creates a summary of an operation without failures or output paths.
+    pub fn new(success_count: usize, total_count: usize, duration: Duration) -> Self {
+        Self {
+            success_count,
+            total_count,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    /// Returns this summary with the version of the data that was processed.
+    pub fn with_version(self, version: String) -> Self {
+        Self {
+            version: Some(version),
+            ..self
+        }
+    }
+
+    /// Returns this summary with descriptions of the files or items that could not be processed.
+    pub fn with_failures(self, failures: Vec<String>) -> Self {
+        Self { failures, ..self }
+    }
+
+    /// Returns this summary with the files or directories that were written to.
+    pub fn with_paths(self, paths: Vec<PathBuf>) -> Self {
+        Self { paths, ..self }
+    }
+
+    /// Creates a summary of an assetbundle download.
+    ///
+    /// Download errors and corrupt assetbundles are listed as failures.
+    pub fn from_download_ab(result: &DownloadAbResult, duration: Duration) -> Self {
+        let failures = result
+            .errors
+            .iter()
+            .map(ToString::to_string)
+            .chain(
+                result
+                    .corrupt
+                    .iter()
+                    .map(|bundle_name| format!("corrupt assetbundle: {}", bundle_name)),
+            )
+            .collect();
+
+        Self::new(result.success_count, result.total_count, duration).with_failures(failures)
+    }
+
+    /// Creates a summary of an assetbundle verification.
+    ///
+    /// Every problem that the verification found is listed as a failure.
+    pub fn from_verify(report: &VerifyReport, duration: Duration) -> Self {
+        let failures: Vec<String> = report
+            .missing
+            .iter()
+            .map(|bundle_name| format!("missing: {}", bundle_name))
+            .chain(
+                report
+                    .mismatched
+                    .iter()
+                    .map(|(bundle_name, status)| format!("{:?}: {}", status, bundle_name)),
+            )
+            .chain(
+                report
+                    .unexpected_encryption
+                    .iter()
+                    .map(|(bundle_name, state)| format!("{:?}: {}", state, bundle_name)),
+            )
+            .chain(
+                report
+                    .orphans
+                    .iter()
+                    .map(|path| format!("orphan: {}", path.display())),
+            )
+            .collect();
+        let success_count = report.total_count.saturating_sub(report.broken().len());
+
+        Self::new(success_count, report.total_count, duration).with_failures(failures)
+    }
+}
+
+/// Serializes a duration as a whole number of milliseconds.
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis().try_into().unwrap_or(u64::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    #[test]
+    fn test_summary_serialize() -> Result<(), serde_json::Error> {
+        let result = DownloadAbResult {
+            success_count: 1,
+            total_count: 3,
+            errors: vec![Error::NotFound("other_bundle".into())],
+            corrupt: vec!["bundle".into()],
+            pruned: Vec::new(),
+        };
+        let summary = Summary::from_download_ab(&result, Duration::from_millis(1500))
+            .with_paths(vec![PathBuf::from("out")]);
+
+        assert_eq!(
+            serde_json::to_value(&summary)?,
+            serde_json::json!({
+                "successCount": 1,
+                "totalCount": 3,
+                "durationMs": 1500,
+                "failures": ["not found: other_bundle", "corrupt assetbundle: bundle"],
+                "paths": ["out"],
+            })
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use twintail_common::models::enums::AssetbundleCategory;
+use twintail_sekai::models::Assetbundle;
+
+/// Returns an on demand assetbundle with the provided name, whose other fields are empty.
+///
+/// Tests set the fields that they depend on with struct update syntax.
+pub(crate) fn empty_bundle(bundle_name: &str) -> Assetbundle {
+    Assetbundle {
+        bundle_name: bundle_name.into(),
+        cache_file_name: String::new(),
+        cache_directory_name: String::new(),
+        hash: String::new(),
+        category: AssetbundleCategory::OnDemand,
+        crc: 0,
+        file_size: 0,
+        dependencies: Vec::new(),
+        paths: Vec::new(),
+        is_builtin: false,
+    }
+}
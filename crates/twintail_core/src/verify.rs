@@ -0,0 +1,313 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use futures::{StreamExt, stream};
+use serde::Serialize;
+use twintail_sekai::{models::AssetbundleInfo, url::UrlProvider};
+
+use crate::{
+    Error,
+    config::{download_ab_config::DownloadAbConfig, verify_config::VerifyConfig},
+    crypto::assetbundle::{self, EncryptionState},
+    fs::scan_path,
+    integrity::{BundleFileStatus, check_bundle_file},
+    state::{self, StateReceiver, StateSender},
+};
+
+#[derive(Clone, Copy, Default, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyState {
+    #[default]
+    NoState,
+    /// files in the assetbundle directory are being scanned
+    Scan,
+    /// the provided number of assetbundles are being checked
+    Check(usize),
+    /// an assetbundle was checked
+    CheckFile,
+    /// the verification finished
+    Finish,
+}
+
+/// The result of comparing a local assetbundle directory with an [`AssetbundleInfo`].
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    /// the number of assetbundles that were checked
+    pub total_count: usize,
+    /// the names of assetbundles that do not exist locally
+    pub missing: Vec<String>,
+    /// assetbundles that do not match their expected size or CRC
+    pub mismatched: Vec<(String, BundleFileStatus)>,
+    /// assetbundles that are not in the expected encryption state, along with the state they are in
+    pub unexpected_encryption: Vec<(String, EncryptionState)>,
+    /// files in the assetbundle directory that are not listed in the info
+    pub orphans: Vec<PathBuf>,
+}
+
+impl VerifyReport {
+    /// Returns true if every assetbundle exists, is valid, and there are no orphan files.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+            && self.mismatched.is_empty()
+            && self.unexpected_encryption.is_empty()
+            && self.orphans.is_empty()
+    }
+
+    /// Returns the names of every assetbundle that has to be downloaded again.
+    pub fn broken(&self) -> HashSet<&str> {
+        self.missing
+            .iter()
+            .chain(self.mismatched.iter().map(|(name, _)| name))
+            .chain(self.unexpected_encryption.iter().map(|(name, _)| name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Creates a [`DownloadAbConfig`] that downloads only the broken assetbundles of the provided info.
+    ///
+    /// The info should be the same info that this report was created from.
+    pub fn repair_config(&self, info: AssetbundleInfo) -> DownloadAbConfig {
+        let broken = self.broken();
+        let bundles = info
+            .bundles
+            .into_iter()
+            .filter(|(bundle_name, _)| broken.contains(bundle_name.as_str()))
+            .collect();
+
+        DownloadAbConfig::builder()
+            .info(AssetbundleInfo { bundles, ..info })
+            .update(false)
+            .verify(true)
+            .build()
+    }
+}
+
+/// The state of a single assetbundle in the assetbundle directory.
+struct BundleCheck {
+    bundle_name: String,
+    status: BundleFileStatus,
+    encryption: Option<EncryptionState>,
+}
+
+/// Responsible for auditing a local assetbundle directory.
+pub struct Verifier<P: UrlProvider> {
+    config: VerifyConfig,
+    url_provider: P,
+    state_sender: StateSender<VerifyState>,
+}
+
+impl<P: UrlProvider> Verifier<P> {
+    /// Creates a new Verifier that will use the provided configuration.
+    ///
+    /// The url provider of the server that the assetbundles were downloaded from
+    /// determines where each assetbundle is expected to be located.
+    pub fn new(config: VerifyConfig, url_provider: P) -> (Self, StateReceiver<VerifyState>) {
+        let (state_sender, state_receiver) = state::channel();
+        (
+            Self {
+                url_provider,
+                config,
+                state_sender,
+            },
+            state_receiver,
+        )
+    }
+
+    /// Returns where the provided assetbundle is located inside of ``dir``.
+    fn bundle_path(&self, dir: &Path, info: &AssetbundleInfo, bundle_name: &str) -> PathBuf {
+        dir.join(self.url_provider.assetbundle_path(
+            &info.version,
+            info.hash.as_deref().unwrap_or_default(),
+            &self.config.platform,
+            bundle_name,
+        ))
+    }
+
+    /// Compares the assetbundles inside of ``dir`` with the provided info.
+    ///
+    /// ``dir`` should be the directory that assetbundles were downloaded to with [`crate::fetch::Fetcher::download_ab`].
+    ///
+    /// Reports assetbundles that are missing, that do not match their expected size or CRC,
+    /// that are not in the expected encryption state, and files that are not listed in the info.
+    pub async fn verify_ab(
+        &self,
+        dir: impl AsRef<Path>,
+        info: &AssetbundleInfo,
+    ) -> Result<VerifyReport, Error> {
+        let dir = dir.as_ref();
+        self.state_sender.send(VerifyState::Scan);
+
+        // compute where each bundle should be
+        let bundle_paths: HashMap<PathBuf, &str> = info
+            .bundles
+            .keys()
+            .map(|bundle_name| {
+                (
+                    self.bundle_path(dir, info, bundle_name),
+                    bundle_name.as_str(),
+                )
+            })
+            .collect();
+
+        // every file in the asset version's directory that is not a bundle is an orphan
+        let version_dir = self.bundle_path(dir, info, "");
+        let mut orphans: Vec<PathBuf> = if version_dir.is_dir() {
+            scan_path(&version_dir, true)
+                .await?
+                .into_iter()
+                .filter(|path| !bundle_paths.contains_key(path))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        orphans.sort();
+
+        // check bundles
+        self.state_sender
+            .send(VerifyState::Check(bundle_paths.len()));
+
+        let check_results: Vec<Result<BundleCheck, Error>> = stream::iter(&bundle_paths)
+            .map(|(path, bundle_name)| async move {
+                let result = self.check_bundle(path, info, bundle_name).await;
+                self.state_sender.send(VerifyState::CheckFile);
+                result
+            })
+            .buffer_unordered(self.config.concurrency)
+            .collect()
+            .await;
+
+        let expected_encryption = if self.config.encrypted {
+            EncryptionState::Encrypted
+        } else {
+            EncryptionState::Decrypted
+        };
+
+        let mut report = VerifyReport {
+            total_count: bundle_paths.len(),
+            orphans,
+            ..Default::default()
+        };
+        for check in check_results {
+            let check = check?;
+            match check.status {
+                BundleFileStatus::Valid => {}
+                BundleFileStatus::Missing => report.missing.push(check.bundle_name.clone()),
+                status => report.mismatched.push((check.bundle_name.clone(), status)),
+            }
+            if let Some(encryption) = check.encryption
+                && encryption != expected_encryption
+            {
+                report
+                    .unexpected_encryption
+                    .push((check.bundle_name, encryption));
+            }
+        }
+        report.missing.sort();
+        report.mismatched.sort_by(|a, b| a.0.cmp(&b.0));
+        report.unexpected_encryption.sort_by(|a, b| a.0.cmp(&b.0));
+
+        self.state_sender.send(VerifyState::Finish);
+
+        Ok(report)
+    }
+
+    /// Checks the integrity and encryption state of a single assetbundle.
+    async fn check_bundle(
+        &self,
+        path: &Path,
+        info: &AssetbundleInfo,
+        bundle_name: &str,
+    ) -> Result<BundleCheck, Error> {
+        let bundle = info
+            .bundles
+            .get(bundle_name)
+            .ok_or(Error::NotFound(bundle_name.into()))?;
+
+        let status = check_bundle_file(path, bundle).await?;
+        let encryption = if status == BundleFileStatus::Missing {
+            None
+        } else {
+            Some(assetbundle::encryption_state(path).await?)
+        };
+
+        Ok(BundleCheck {
+            bundle_name: bundle_name.into(),
+            status,
+            encryption,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::empty_bundle;
+    use tempfile::tempdir;
+    use twintail_sekai::{models::Assetbundle, url::server_provider::ServerUrlProvider};
+
+    fn get_bundle(bundle_name: &str, data: &[u8]) -> Assetbundle {
+        Assetbundle {
+            crc: crc32fast::hash(data),
+            file_size: data.len() as u64,
+            ..empty_bundle(bundle_name)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_ab() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let bundle_dir = dir.path().join("4.0.5.10/asset_hash/android");
+
+        // an encrypted unity assetbundle
+        let mut plain = b"UnityFS".to_vec();
+        plain.extend((0..1000).map(|i| i as u8));
+        let data = assetbundle::encrypt(&mut std::io::Cursor::new(plain)).await?;
+
+        let mut bundles = HashMap::new();
+        for name in ["valid", "missing", "corrupt", "encrypted"] {
+            bundles.insert(name.to_string(), get_bundle(name, &data));
+        }
+        let info = AssetbundleInfo {
+            version: "4.0.5.10".into(),
+            os: "android".into(),
+            hash: Some("asset_hash".into()),
+            host_hash: None,
+            bundles,
+        };
+
+        // write files
+        tokio::fs::create_dir_all(&bundle_dir).await?;
+        tokio::fs::write(bundle_dir.join("encrypted"), &data).await?;
+        assetbundle::decrypt_file_chunked(&bundle_dir.join("encrypted"), &bundle_dir.join("valid"))
+            .await?;
+        let mut corrupt = tokio::fs::read(bundle_dir.join("valid")).await?;
+        corrupt[500] = !corrupt[500];
+        tokio::fs::write(bundle_dir.join("corrupt"), &corrupt).await?;
+        tokio::fs::write(bundle_dir.join("orphan"), b"orphan").await?;
+
+        let (verifier, _) = Verifier::new(VerifyConfig::default(), ServerUrlProvider::default());
+        let report = verifier.verify_ab(dir.path(), &info).await?;
+
+        assert_eq!(report.total_count, 4);
+        assert_eq!(report.missing, vec!["missing".to_string()]);
+        assert_eq!(report.mismatched.len(), 1);
+        assert_eq!(report.mismatched[0].0, "corrupt");
+        assert_eq!(
+            report.unexpected_encryption,
+            vec![("encrypted".to_string(), EncryptionState::Encrypted)]
+        );
+        assert_eq!(report.orphans, vec![bundle_dir.join("orphan")]);
+        assert!(!report.is_ok());
+
+        // only broken bundles are repaired
+        let repair_config = report.repair_config(info);
+        let mut repair_bundles: Vec<_> = repair_config.info.unwrap().bundles.into_keys().collect();
+        repair_bundles.sort();
+        assert_eq!(repair_bundles, vec!["corrupt", "encrypted", "missing"]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use twintail_common::models::enums::{Platform, Server};
+
+use crate::{Error, fetch::GameVersions, fs::write_file};
+
+const DEFAULT_DIR_NAME: &str = "twintail";
+const DEFAULT_FILE_NAME: &str = "watch_state.json";
+
+/// A change in the versions that a game server provides, compared to the versions recorded in a [`WatchState`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionUpdate {
+    pub server: Server,
+    pub platform: Platform,
+    /// the versions that were recorded before, or None if the server had never been checked
+    pub old: Option<GameVersions>,
+    pub new: GameVersions,
+}
+
+impl VersionUpdate {
+    /// Returns true if the most recent app version changed.
+    pub fn app_changed(&self) -> bool {
+        self.old
+            .as_ref()
+            .is_none_or(|old| old.app_version != self.new.app_version)
+    }
+
+    /// Returns true if the version of the suitemaster files changed.
+    pub fn data_changed(&self) -> bool {
+        self.old
+            .as_ref()
+            .is_none_or(|old| old.data_version != self.new.data_version)
+    }
+
+    /// Returns true if the asset version or asset hash changed.
+    pub fn assets_changed(&self) -> bool {
+        self.old.as_ref().is_none_or(|old| {
+            old.asset_version != self.new.asset_version || old.asset_hash != self.new.asset_hash
+        })
+    }
+
+    /// Returns environment variables that describe the update, for commands that are run when it is detected.
+    ///
+    /// Old versions are empty if the server had never been checked.
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let old = self.old.as_ref();
+        let old_value =
+            |get: fn(&GameVersions) -> &String| old.map(get).cloned().unwrap_or_default();
+
+        vec![
+            ("TWINTAIL_SERVER", self.server.to_string()),
+            ("TWINTAIL_PLATFORM", self.platform.to_string()),
+            (
+                "TWINTAIL_OLD_APP_VERSION",
+                old_value(|versions| &versions.app_version),
+            ),
+            ("TWINTAIL_NEW_APP_VERSION", self.new.app_version.clone()),
+            (
+                "TWINTAIL_OLD_DATA_VERSION",
+                old_value(|versions| &versions.data_version),
+            ),
+            ("TWINTAIL_NEW_DATA_VERSION", self.new.data_version.clone()),
+            (
+                "TWINTAIL_OLD_ASSET_VERSION",
+                old_value(|versions| &versions.asset_version),
+            ),
+            ("TWINTAIL_NEW_ASSET_VERSION", self.new.asset_version.clone()),
+            (
+                "TWINTAIL_OLD_ASSET_HASH",
+                old_value(|versions| &versions.asset_hash),
+            ),
+            ("TWINTAIL_NEW_ASSET_HASH", self.new.asset_hash.clone()),
+        ]
+    }
+}
+
+/// Persists the versions that were last fetched from each server and platform,
+/// so that a watcher that is restarted does not fetch the same versions again.
+#[derive(Debug, Default)]
+pub struct WatchState {
+    path: PathBuf,
+    servers: HashMap<Server, HashMap<Platform, GameVersions>>,
+}
+
+impl WatchState {
+    /// Returns the default location of the watch state.
+    ///
+    /// This is a file inside of the user's data directory.
+    /// Returns None if the data directory could not be determined.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(DEFAULT_DIR_NAME).join(DEFAULT_FILE_NAME))
+    }
+
+    /// Loads a watch state from the provided path.
+    ///
+    /// If no file exists at the path, an empty state is returned.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        let servers = match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            servers,
+        })
+    }
+
+    /// Gets the versions that were recorded for a server and platform.
+    pub fn get(&self, server: Server, platform: Platform) -> Option<&GameVersions> {
+        self.servers
+            .get(&server)
+            .and_then(|platforms| platforms.get(&platform))
+    }
+
+    /// Records the versions for a server and platform, replacing any versions that were previously recorded.
+    pub fn insert(&mut self, server: Server, platform: Platform, versions: GameVersions) {
+        self.servers
+            .entry(server)
+            .or_default()
+            .insert(platform, versions);
+    }
+
+    /// Compares the versions that a server currently provides to the recorded versions.
+    ///
+    /// Returns None if nothing changed.
+    pub fn check(
+        &self,
+        server: Server,
+        platform: Platform,
+        versions: GameVersions,
+    ) -> Option<VersionUpdate> {
+        let old = self.get(server, platform);
+        if old == Some(&versions) {
+            return None;
+        }
+
+        Some(VersionUpdate {
+            server,
+            platform,
+            old: old.cloned(),
+            new: versions,
+        })
+    }
+
+    /// Writes the watch state to the path that it was loaded from.
+    ///
+    /// Any missing directories will be created.
+    pub async fn save(&self) -> Result<(), Error> {
+        let serialized = serde_json::to_vec_pretty(&self.servers)?;
+        write_file(&self.path, &serialized).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn get_versions(data_version: &str, asset_version: &str) -> GameVersions {
+        GameVersions {
+            app_version: "4.0.5".into(),
+            data_version: data_version.into(),
+            asset_version: asset_version.into(),
+            asset_hash: "hash".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_state() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let state_path = dir.path().join("nested").join("watch_state.json");
+
+        // a server that was never checked is always updated
+        let mut state = WatchState::load(&state_path).await?;
+        let update = state
+            .check(Server::Japan, Platform::Android, get_versions("1.0", "1.0"))
+            .unwrap();
+        assert!(update.old.is_none());
+        assert!(update.data_changed() && update.assets_changed());
+
+        // recorded versions persist between loads
+        state.insert(Server::Japan, Platform::Android, update.new);
+        state.save().await?;
+        let state = WatchState::load(&state_path).await?;
+        assert_eq!(
+            state.get(Server::Japan, Platform::Android),
+            Some(&get_versions("1.0", "1.0"))
+        );
+        assert!(
+            state
+                .check(Server::Japan, Platform::Android, get_versions("1.0", "1.0"))
+                .is_none()
+        );
+
+        // only the versions that changed are reported as changed
+        let update = state
+            .check(Server::Japan, Platform::Android, get_versions("1.1", "1.0"))
+            .unwrap();
+        assert!(update.data_changed() && !update.assets_changed() && !update.app_changed());
+        let env_vars: HashMap<_, _> = update.env_vars().into_iter().collect();
+        assert_eq!(env_vars["TWINTAIL_SERVER"], "japan");
+        assert_eq!(env_vars["TWINTAIL_OLD_DATA_VERSION"], "1.0");
+        assert_eq!(env_vars["TWINTAIL_NEW_DATA_VERSION"], "1.1");
+
+        // a new app version is an update, even if no files changed
+        let update = state
+            .check(
+                Server::Japan,
+                Platform::Android,
+                GameVersions {
+                    app_version: "4.1.0".into(),
+                    ..get_versions("1.0", "1.0")
+                },
+            )
+            .unwrap();
+        assert!(update.app_changed() && !update.data_changed() && !update.assets_changed());
+        let env_vars: HashMap<_, _> = update.env_vars().into_iter().collect();
+        assert_eq!(env_vars["TWINTAIL_OLD_APP_VERSION"], "4.0.5");
+        assert_eq!(env_vars["TWINTAIL_NEW_APP_VERSION"], "4.1.0");
+
+        // state files that were saved before app versions were recorded can still be loaded
+        tokio::fs::write(
+            &state_path,
+            r#"{"Japan":{"Android":{"dataVersion":"1.0","assetVersion":"1.0","assetHash":"hash"}}}"#,
+        )
+        .await?;
+        let state = WatchState::load(&state_path).await?;
+        assert_eq!(
+            state
+                .get(Server::Japan, Platform::Android)
+                .map(|versions| versions.app_version.as_str()),
+            Some("")
+        );
+
+        assert!(
+            state
+                .check(
+                    Server::Global,
+                    Platform::Android,
+                    get_versions("1.0", "1.0")
+                )
+                .is_some()
+        );
+
+        Ok(())
+    }
+}
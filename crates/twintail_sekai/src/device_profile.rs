@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use twintail_common::models::enums::Platform;
+use uuid::Uuid;
+
+// preset values
+mod preset {
+    pub const UNITY_VERSION: &str = "2022.3.21f1";
+
+    pub const ANDROID_DEVICE_MODEL: &str = "Google Pixel 7";
+    pub const ANDROID_OPERATING_SYSTEM: &str = "Android OS 14 / API-34 (AP2A.240805.005/12025142)";
+    pub const ANDROID_USER_AGENT: &str =
+        "UnityPlayer/2022.3.21f1 (UnityWebRequest/1.0, libcurl/8.5.0-DEV)";
+
+    pub const IOS_DEVICE_MODEL: &str = "iPhone15,2";
+    pub const IOS_OPERATING_SYSTEM: &str = "iOS 17.5.1";
+    pub const IOS_USER_AGENT: &str = "ProductName/1 CFNetwork/1496.0.7 Darwin/23.5.0";
+}
+
+/// The device that requests to the game servers appear to be sent from.
+///
+/// Its values are sent in the headers of every request, and when signing up for a new account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfile {
+    /// a UUID that identifies an installation of the game
+    pub install_id: String,
+    pub device_model: String,
+    pub operating_system: String,
+    pub unity_version: String,
+    pub user_agent: String,
+}
+
+impl DeviceProfile {
+    /// Creates a profile of an Android phone, with a new random install id.
+    pub fn android() -> Self {
+        Self {
+            install_id: generate_install_id(),
+            device_model: preset::ANDROID_DEVICE_MODEL.into(),
+            operating_system: preset::ANDROID_OPERATING_SYSTEM.into(),
+            unity_version: preset::UNITY_VERSION.into(),
+            user_agent: preset::ANDROID_USER_AGENT.into(),
+        }
+    }
+
+    /// Creates a profile of an iPhone, with a new random install id.
+    pub fn ios() -> Self {
+        Self {
+            install_id: generate_install_id(),
+            device_model: preset::IOS_DEVICE_MODEL.into(),
+            operating_system: preset::IOS_OPERATING_SYSTEM.into(),
+            unity_version: preset::UNITY_VERSION.into(),
+            user_agent: preset::IOS_USER_AGENT.into(),
+        }
+    }
+
+    /// Creates the preset profile of a platform, with a new random install id.
+    pub fn for_platform(platform: Platform) -> Self {
+        match platform {
+            Platform::Android => Self::android(),
+            Platform::Ios => Self::ios(),
+        }
+    }
+}
+
+/// Generates a random version 4 UUID to use as an install id.
+pub fn generate_install_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_install_id() {
+        let install_id = generate_install_id();
+
+        let groups: Vec<&str> = install_id.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|group| group.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(groups[3].as_bytes()[0], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(install_id, generate_install_id());
+    }
+
+    #[test]
+    fn test_device_profile_presets() {
+        let android = DeviceProfile::for_platform(Platform::Android);
+        let ios = DeviceProfile::for_platform(Platform::Ios);
+
+        assert_eq!(android.device_model, preset::ANDROID_DEVICE_MODEL);
+        assert_eq!(ios.device_model, preset::IOS_DEVICE_MODEL);
+        assert_ne!(android.operating_system, ios.operating_system);
+        assert_eq!(android.user_agent, preset::ANDROID_USER_AGENT);
+        assert_eq!(ios.user_agent, preset::IOS_USER_AGENT);
+        assert_ne!(android.install_id, ios.install_id);
+    }
+}
@@ -1,3 +1,6 @@
+use std::{fmt, time::Duration};
+
+use reqwest::StatusCode;
 use thiserror::Error;
 use twintail_common::multi_error;
 
@@ -12,6 +15,12 @@ pub enum Error {
     #[error("serde_json error: {0}")]
     SerdeJson(#[from] serde_json::Error),
 
+    #[error("toml error: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+
     #[error("rmp_serde decode error: {0}")]
     RmpSerdeDecode(#[from] rmp_serde::decode::Error),
 
@@ -27,10 +36,237 @@ pub enum Error {
     #[error("invalid request: {0}")]
     InvalidRequest(String),
 
+    #[error("{0}")]
+    Request(#[from] RequestError),
+
     #[error("missing url: {0}")]
     MissingUrl(String),
 
+    #[error("invalid url template: {0}")]
+    InvalidTemplate(String),
+
     #[error("multiple errors: {0}")]
     Multi(String),
 }
 multi_error!(Error);
+
+impl Error {
+    /// Returns the kind of request failure that caused this error, if a request failed.
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Self::Request(err) => Some(err.kind),
+            _ => None,
+        }
+    }
+
+    /// Returns the endpoint of the request that failed, if a request failed.
+    pub fn endpoint(&self) -> Option<Endpoint> {
+        match self {
+            Self::Request(err) => Some(err.endpoint),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the request that caused this error may succeed if it is sent again.
+    ///
+    /// Network failures, timeouts, rate limiting and server errors are retryable.
+    /// Errors caused by the request itself, such as an outdated app or asset version, are not.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Reqwest(err) => match err.status() {
+                Some(status) => ErrorKind::from_status(status).is_retryable(),
+                None => is_transport_error(err),
+            },
+            Self::Request(err) => err.kind.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Returns the status code of the response that caused this error, if there was one.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Reqwest(err) => err.status(),
+            Self::Request(err) => err.status,
+            _ => None,
+        }
+    }
+
+    /// Returns how long the server asked to wait before retrying the request, if it did.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::Request(err) => err.retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// The endpoints that a [`crate::sekai_client::SekaiClient`] sends requests to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    IssueSignature,
+    GameVersion,
+    UserSignup,
+    UserAuth,
+    AssetbundleInfo,
+    Assetbundle,
+    System,
+    Suitemasterfile,
+    UserInherit,
+    UserSuite,
+    AppHash,
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::IssueSignature => "issue_signature",
+            Self::GameVersion => "game_version",
+            Self::UserSignup => "user_signup",
+            Self::UserAuth => "user_auth",
+            Self::AssetbundleInfo => "assetbundle_info",
+            Self::Assetbundle => "assetbundle",
+            Self::System => "system",
+            Self::Suitemasterfile => "suitemasterfile",
+            Self::UserInherit => "user_inherit",
+            Self::UserSuite => "user_suite",
+            Self::AppHash => "apphash",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The reason that a request failed.
+///
+/// Maintenance is not a request failure, since the system endpoint reports it in a successful response,
+/// see [`crate::models::SystemInfo::is_maintenance`]. twintail_core reports it as its own error instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// the app version is too old, and must be upgraded (426)
+    UpgradeRequired,
+    /// the request was refused, usually because of an invalid hash or version, or invalid credentials (403)
+    Forbidden,
+    /// the requested resource or account does not exist (404)
+    NotFound,
+    /// too many requests were sent (429)
+    RateLimited,
+    /// the server failed to handle the request (5xx)
+    Server,
+    /// the request could not be sent, or the response could not be received
+    Transport,
+    /// any other unsuccessful status code
+    Other,
+}
+
+impl ErrorKind {
+    /// Classifies an unsuccessful status code.
+    pub fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UPGRADE_REQUIRED => Self::UpgradeRequired,
+            StatusCode::FORBIDDEN => Self::Forbidden,
+            StatusCode::NOT_FOUND => Self::NotFound,
+            StatusCode::REQUEST_TIMEOUT => Self::Transport,
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited,
+            status if status.is_server_error() => Self::Server,
+            _ => Self::Other,
+        }
+    }
+
+    /// Returns true if a request that failed for this reason may succeed if it is sent again.
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::RateLimited | Self::Server | Self::Transport)
+    }
+}
+
+/// A request to an endpoint that failed.
+#[derive(Debug)]
+pub struct RequestError {
+    pub endpoint: Endpoint,
+    /// the status code of the response, if one was received
+    pub status: Option<StatusCode>,
+    pub kind: ErrorKind,
+    /// how long the server asked to wait before retrying the request
+    pub retry_after: Option<Duration>,
+    message: String,
+}
+
+impl RequestError {
+    /// Creates an error for a response with an unsuccessful status code.
+    ///
+    /// Maintenance cannot be told apart from other unavailable responses by the status code alone,
+    /// so it is detected from the body of the system endpoint instead, see [`crate::models::SystemInfo`].
+    pub fn from_status(
+        endpoint: Endpoint,
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        message: String,
+    ) -> Self {
+        Self {
+            endpoint,
+            status: Some(status),
+            kind: ErrorKind::from_status(status),
+            retry_after,
+            message,
+        }
+    }
+
+    /// Creates an error for a request that could not be sent, or whose response could not be received.
+    pub fn from_reqwest(endpoint: Endpoint, err: reqwest::Error) -> Self {
+        let kind = match err.status() {
+            Some(status) => ErrorKind::from_status(status),
+            None if is_transport_error(&err) => ErrorKind::Transport,
+            None => ErrorKind::Other,
+        };
+
+        Self {
+            endpoint,
+            status: err.status(),
+            kind,
+            retry_after: None,
+            message: err.to_string(),
+        }
+    }
+
+    /// Creates an error for a response whose body could not be received,
+    /// for example because the connection was closed before the body ended.
+    pub fn from_body(endpoint: Endpoint, err: reqwest::Error) -> Self {
+        Self {
+            endpoint,
+            status: None,
+            kind: ErrorKind::Transport,
+            retry_after: None,
+            message: err.to_string(),
+        }
+    }
+
+    /// Returns this error with a different message.
+    pub fn with_message(self, message: &str) -> Self {
+        Self {
+            message: message.into(),
+            ..self
+        }
+    }
+
+    /// Returns this error with context added to the start of its message.
+    pub fn with_context(self, context: &str) -> Self {
+        Self {
+            message: format!("{}: {}", context, self.message),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.status {
+            Some(_) => write!(f, "invalid request: {}", self.message),
+            None => write!(f, "reqwest error: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {}
+
+/// Returns true if a reqwest error was caused by the network rather than by the request itself.
+fn is_transport_error(err: &reqwest::Error) -> bool {
+    !err.is_builder() && !err.is_decode() && !err.is_redirect()
+}
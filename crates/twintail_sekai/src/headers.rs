@@ -1,7 +1,7 @@
 use reqwest::header::{HeaderMap, HeaderValue, InvalidHeaderValue};
 use twintail_common::models::enums::Platform;
 
-use crate::Error;
+use crate::{Error, device_profile::DeviceProfile};
 
 // header names
 pub mod header_name {
@@ -21,43 +21,39 @@ pub mod header_name {
     pub const INHERIT_TOKEN: &str = "x-inherit-id-verify-token";
     pub const DATA_VERSION: &str = "x-data-version";
     pub const ASSET_VERSION: &str = "x-asset-version";
+    pub const RANGE: &str = "range";
 }
 
 // header values
 pub mod header_value {
     pub const CONTENT_TYPE: &str = "application/octet-stream";
     pub const ACCEPT: &str = "application/octet-stream";
-    pub const USER_AGENT: &str = "UnityPlayer/2022.3.21f1 (UnityWebRequest/1.0, libcurl/8.5.0-DEV)";
-    pub const INSTALL_ID: &str = "3efd7166-11b2-4b3e-2f08-94b0e16f76e8";
-    pub const DEVICE_MODEL: &str = "39phone";
-    pub const OPERATING_SYSTEM: &str = "39os";
-    pub const UNITY_VERSION: &str = "2022.3.21f1";
 }
 
 pub struct Headers(pub HeaderMap<HeaderValue>);
 
 impl Headers {
-    /// Get a new SekaiHeaders object with some default headers.
-    fn new() -> Result<Self, InvalidHeaderValue> {
+    /// Get a new SekaiHeaders object with some default headers, and the headers that describe a device.
+    fn new(device_profile: &DeviceProfile) -> Result<Self, InvalidHeaderValue> {
         let mut headers = Self::default();
 
         headers.insert_str(header_name::CONTENT_TYPE, header_value::CONTENT_TYPE)?;
         headers.insert_str(header_name::ACCEPT, header_value::ACCEPT)?;
-        headers.insert_str(header_name::USER_AGENT, header_value::USER_AGENT)?;
-        headers.insert_str(header_name::INSTALL_ID, header_value::INSTALL_ID)?;
-        headers.insert_str(header_name::DEVICE_MODEL, header_value::DEVICE_MODEL)?;
+        headers.insert_str(header_name::USER_AGENT, &device_profile.user_agent)?;
+        headers.insert_str(header_name::INSTALL_ID, &device_profile.install_id)?;
+        headers.insert_str(header_name::DEVICE_MODEL, &device_profile.device_model)?;
         headers.insert_str(
             header_name::OPERATING_SYSTEM,
-            header_value::OPERATING_SYSTEM,
+            &device_profile.operating_system,
         )?;
-        headers.insert_str(header_name::UNITY_VERSION, header_value::UNITY_VERSION)?;
+        headers.insert_str(header_name::UNITY_VERSION, &device_profile.unity_version)?;
 
         Ok(headers)
     }
 
-    /// Get a builder for SekaiHeaders
-    pub fn builder() -> Result<HeadersBuilder, InvalidHeaderValue> {
-        Ok(HeadersBuilder::new(Self::new()?))
+    /// Get a builder for SekaiHeaders that describe a device.
+    pub fn builder(device_profile: &DeviceProfile) -> Result<HeadersBuilder, InvalidHeaderValue> {
+        Ok(HeadersBuilder::new(Self::new(device_profile)?))
     }
 
     pub fn insert(&mut self, name: &'static str, value: HeaderValue) {
@@ -163,17 +159,31 @@ mod tests {
 
     #[test]
     fn test_new_sekai_headers() {
-        assert!(Headers::new().is_ok())
+        let device_profile = DeviceProfile::ios();
+        let headers = Headers::new(&device_profile).unwrap();
+
+        assert_eq!(
+            headers.0.get(header_name::INSTALL_ID).unwrap(),
+            &device_profile.install_id
+        );
+        assert_eq!(
+            headers.0.get(header_name::DEVICE_MODEL).unwrap(),
+            &device_profile.device_model
+        );
+        assert_eq!(
+            headers.0.get(header_name::USER_AGENT).unwrap(),
+            &device_profile.user_agent
+        );
     }
 
     #[test]
     fn test_new_sekai_headers_builder() {
-        assert!(Headers::builder().is_ok())
+        assert!(Headers::builder(&DeviceProfile::android()).is_ok())
     }
 
     #[test]
     fn test_sekai_headers_builder_version() {
-        let builder = Headers::builder().unwrap();
+        let builder = Headers::builder(&DeviceProfile::android()).unwrap();
 
         let version = "4.0.5";
         let headers = builder.version(version).build().unwrap();
@@ -183,7 +193,7 @@ mod tests {
 
     #[test]
     fn test_sekai_headers_builder_hash() {
-        let builder = Headers::builder().unwrap();
+        let builder = Headers::builder(&DeviceProfile::android()).unwrap();
 
         let hash = "2179da72-9de5-23a6-f388-9e5835098ce1";
         let headers = builder.hash(hash).build().unwrap();
@@ -193,7 +203,7 @@ mod tests {
 
     #[test]
     fn test_sekai_headers_builder_platform() {
-        let builder = Headers::builder().unwrap();
+        let builder = Headers::builder(&DeviceProfile::android()).unwrap();
 
         let platform = Platform::Ios;
         let headers = builder.platform(&platform).build().unwrap();
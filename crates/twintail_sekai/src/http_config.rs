@@ -0,0 +1,149 @@
+use std::{net::SocketAddr, time::Duration};
+
+use reqwest::{Certificate, Client, Proxy};
+use twintail_common::models::OptionalBuilder;
+
+use crate::Error;
+
+/// Configuration for the HTTP client that a [`crate::sekai_client::SekaiClient`] sends requests with.
+///
+/// By default, requests are sent directly without timeouts, using the system's DNS resolver and TLS roots.
+#[derive(Debug, Clone, Default)]
+pub struct HttpConfig {
+    /// the url of an HTTP, HTTPS or SOCKS proxy that all requests are sent through
+    pub proxy: Option<String>,
+    /// the maximum amount of time spent connecting to a host
+    pub connect_timeout: Option<Duration>,
+    /// the maximum amount of time spent waiting for data from a connection
+    pub read_timeout: Option<Duration>,
+    /// hosts that are resolved to a fixed address instead of using DNS
+    pub resolve: Vec<(String, SocketAddr)>,
+    /// additional PEM encoded certificates that are trusted as TLS roots
+    pub root_certificates: Vec<Vec<u8>>,
+}
+
+impl HttpConfig {
+    /// Create a default builder for the HttpConfig struct.
+    pub fn builder() -> HttpConfigBuilder {
+        HttpConfigBuilder::default()
+    }
+
+    /// Builds a [`reqwest::Client`] with this configuration.
+    pub fn build_client(&self) -> Result<Client, Error> {
+        let mut builder = Client::builder();
+
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(Proxy::all(proxy)?);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        for (host, address) in &self.resolve {
+            builder = builder.resolve(host, *address);
+        }
+        for pem in &self.root_certificates {
+            builder = builder.add_root_certificate(Certificate::from_pem(pem)?);
+        }
+
+        Ok(builder.build()?)
+    }
+}
+
+/// Builder for HttpConfig
+#[derive(Default)]
+pub struct HttpConfigBuilder {
+    config: HttpConfig,
+}
+
+impl OptionalBuilder for HttpConfigBuilder {}
+
+impl HttpConfigBuilder {
+    /// Sends all requests through a proxy.
+    ///
+    /// Supports ``http://``, ``https://``, ``socks5://`` and ``socks5h://`` urls.
+    pub fn proxy(mut self, proxy: String) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets the maximum amount of time spent connecting to a host.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.config.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum amount of time spent waiting for data from a connection.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.config.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Resolves a host to a fixed address instead of using DNS.
+    ///
+    /// The port of the address is ignored if the url of a request has a port.
+    pub fn resolve(mut self, host: String, address: SocketAddr) -> Self {
+        self.config.resolve.push((host, address));
+        self
+    }
+
+    /// Trusts a PEM encoded certificate as a TLS root, in addition to the system's roots.
+    pub fn root_certificate(mut self, pem: Vec<u8>) -> Self {
+        self.config.root_certificates.push(pem);
+        self
+    }
+
+    /// Returns the HttpConfig that was constructed.
+    pub fn build(self) -> HttpConfig {
+        self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_build_client() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/api/system")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        // a host that does not exist is resolved to the mock server
+        let client = HttpConfig::builder()
+            .connect_timeout(Duration::from_secs(5))
+            .read_timeout(Duration::from_secs(5))
+            .resolve("game-api.example.invalid".into(), server.socket_address())
+            .build()
+            .build_client()
+            .unwrap();
+        let response = client
+            .get(format!(
+                "http://game-api.example.invalid:{}/api/system",
+                server.socket_address().port()
+            ))
+            .send()
+            .await
+            .unwrap();
+
+        mock.assert();
+        assert!(response.status().is_success());
+
+        // invalid configuration is reported when the client is built
+        let result = HttpConfig::builder()
+            .proxy("not a proxy url".into())
+            .build()
+            .build_client();
+        assert!(result.is_err());
+        let result = HttpConfig::builder()
+            .root_certificate(b"not a certificate".to_vec())
+            .build()
+            .build_client();
+        assert!(result.is_err());
+    }
+}
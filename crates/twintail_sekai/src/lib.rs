@@ -1,8 +1,11 @@
+pub mod device_profile;
 pub mod headers;
+pub mod http_config;
 pub mod models;
 pub mod sekai_client;
 pub mod url;
 
 mod error;
+mod signature;
 
-pub use error::Error;
+pub use error::{Endpoint, Error, ErrorKind, RequestError};
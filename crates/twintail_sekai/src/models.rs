@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use twintail_common::models::enums::{AssetbundleCategory, Platform};
 
+use crate::device_profile::DeviceProfile;
+
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct GameVersion {
@@ -30,6 +32,17 @@ pub struct UserRequest {
     pub operating_system: String,
 }
 
+impl UserRequest {
+    /// Creates a request to sign up for an account on a device.
+    pub fn new(platform: Platform, device_profile: &DeviceProfile) -> Self {
+        Self {
+            platform,
+            device_model: device_profile.device_model.clone(),
+            operating_system: device_profile.operating_system.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UserSignup {
@@ -104,6 +117,16 @@ pub struct SystemInfo {
     pub app_versions: Vec<AppVersion>,
 }
 
+impl SystemInfo {
+    /// The maintenance status that the game servers report while they are under maintenance.
+    pub const MAINTENANCE_IN: &str = "maintenance_in";
+
+    /// Returns true if the game servers are under maintenance.
+    pub fn is_maintenance(&self) -> bool {
+        self.maintenance_status == Self::MAINTENANCE_IN
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UserInheritGamedata {
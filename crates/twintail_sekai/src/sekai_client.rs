@@ -1,17 +1,26 @@
-use super::{headers::Headers, url::UrlProvider};
+use super::{headers::Headers, http_config::HttpConfig, url::UrlProvider};
 use crate::{
     Error,
-    headers::{header_name, header_value},
+    device_profile::DeviceProfile,
+    error::{Endpoint, ErrorKind, RequestError},
+    headers::header_name,
     models::{
         AppInfo, AssetbundleInfo, GameVersion, SystemInfo, UserAuthRequest, UserAuthResponse,
         UserInherit, UserInheritJWT, UserRequest, UserSignup,
     },
+    signature::Signature,
 };
+use bytes::Bytes;
 use hmac::Hmac;
 use jwt::SignWithKey;
-use reqwest::{Client, StatusCode, header::HeaderValue};
+use reqwest::{
+    Client, RequestBuilder, Response, StatusCode,
+    header::{CONTENT_RANGE, HeaderMap, HeaderValue, RETRY_AFTER},
+};
 use serde_json::Value;
 use sha2::Sha256;
+use std::time::{Duration, SystemTime};
+use tokio::sync::RwLock;
 use twintail_common::{
     crypto::{aes::AesConfig, aes_msgpack},
     models::{OptionalBuilder, enums::Platform},
@@ -26,8 +35,13 @@ mod error_string {
     pub const INVALID_INHERIT_CREDENTIALS: &str = "could not find any account with the provided transfer id or password. ensure that both values are correct";
     pub const NOT_FOUND_USER_AUTH: &str = "(404: not found) error when logging in to an account. ensure that the app version and hash values are correct";
     pub const GET_APP_INFO: &str = "error when attempting to retrieve the latest app info";
+    pub const INVALID_CONTENT_RANGE: &str =
+        "partial assetbundle response did not include a valid content-range header";
 }
 
+/// How long before its expiry a cloudfront signature is replaced with a new one.
+const SIGNATURE_EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
 /// An API client that interfaces with the game's servers, providing various functions to query endpoints.
 pub struct SekaiClient<T: UrlProvider> {
     aes_config: AesConfig,
@@ -35,33 +49,39 @@ pub struct SekaiClient<T: UrlProvider> {
     app_version: String,
     client: Client,
     headers: Headers,
+    signature: RwLock<Option<Signature>>,
     jwt_key: Hmac<Sha256>,
     pub platform: Platform,
+    pub device_profile: DeviceProfile,
     pub url_provider: T,
 }
 
 impl<T: UrlProvider> SekaiClient<T> {
-    /// Creates a new SekaiClient that uses a specific url provider.
+    /// Creates a new SekaiClient that uses a specific url provider, and sends requests with the provided HTTP client
+    /// as if they were sent from the provided device.
     pub async fn new(
-        app_hash: String,
-        app_version: String,
+        app_info: AppInfo,
         aes_config: AesConfig,
         jwt_key: Hmac<Sha256>,
         platform: Platform,
+        device_profile: DeviceProfile,
         url_provider: T,
+        client: Client,
     ) -> Result<Self, Error> {
-        let headers = Headers::builder()?
-            .version(&app_version)
-            .hash(&app_hash)
+        let headers = Headers::builder(&device_profile)?
+            .version(&app_info.app_version)
+            .hash(&app_info.app_hash)
             .platform(&platform)
             .build()?;
 
-        let mut client = Self {
+        let client = Self {
             headers,
-            client: Client::new(),
+            signature: RwLock::new(None),
+            client,
             platform,
-            app_version,
-            app_hash,
+            device_profile,
+            app_version: app_info.app_version,
+            app_hash: app_info.app_hash,
             aes_config,
             jwt_key,
             url_provider,
@@ -69,7 +89,7 @@ impl<T: UrlProvider> SekaiClient<T> {
 
         // save the cloudfront signature only if required
         if client.url_provider.issue_signature().is_some() {
-            client.issue_signature().await?;
+            client.refresh_signature(None).await?;
         }
 
         Ok(client)
@@ -77,11 +97,11 @@ impl<T: UrlProvider> SekaiClient<T> {
 
     /// Performs a request to [`constants::url::sekai::ISSUE_SIGNATURE`].
     ///
-    /// This endpoint responds with a CloudFront cookie value,
+    /// This endpoint responds with CloudFront cookies,
     /// which we need in order to communicate with the CDN.
     ///
-    /// The function will automatically assign this cookie value to its Headers.
-    async fn issue_signature(&mut self) -> Result<(), Error> {
+    /// Returns the signature made up of these cookies.
+    async fn issue_signature(&self, generation: u64) -> Result<Signature, Error> {
         let url = self
             .url_provider
             .issue_signature()
@@ -93,19 +113,73 @@ impl<T: UrlProvider> SekaiClient<T> {
             .body(b"ffa3bd6214f33fe73cb72fee2262bedb".to_vec())
             .headers(self.headers.get_map());
 
-        match request.send().await?.error_for_status() {
-            Ok(mut response) => {
-                // set the cookie that is inside of issue_signature_response
-                let set_cookie_header = response
-                    .headers_mut()
-                    .remove(header_name::SET_COOKIE)
-                    .ok_or(Error::InvalidRequest(
-                        error_string::SET_COOKIE_NOT_FOUND.into(),
-                    ))?;
-                self.headers.insert(header_name::COOKIE, set_cookie_header);
-                Ok(())
+        match send(Endpoint::IssueSignature, request).await {
+            Ok(response) => Signature::from_headers(response.headers(), generation)?.ok_or(
+                Error::InvalidRequest(error_string::SET_COOKIE_NOT_FOUND.into()),
+            ),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Issues a new cloudfront signature to replace the one with the provided generation.
+    ///
+    /// Requests wait for the new signature while it is being issued.
+    /// If the signature was already replaced by another request in the meantime, nothing is issued.
+    async fn refresh_signature(&self, replaced_generation: Option<u64>) -> Result<(), Error> {
+        let mut signature = self.signature.write().await;
+        if signature.as_ref().map(|signature| signature.generation) != replaced_generation {
+            return Ok(());
+        }
+
+        let generation = replaced_generation.map_or(0, |generation| generation + 1);
+        *signature = Some(self.issue_signature(generation).await?);
+        Ok(())
+    }
+
+    /// Returns the headers to send a request with, including the current cloudfront signature,
+    /// along with the generation of that signature.
+    ///
+    /// If the signature is about to expire, a new one is issued first.
+    async fn request_headers(&self) -> Result<(HeaderMap, Option<u64>), Error> {
+        let expiring_generation = self
+            .signature
+            .read()
+            .await
+            .as_ref()
+            .filter(|signature| signature.expires_within(SIGNATURE_EXPIRY_MARGIN))
+            .map(|signature| signature.generation);
+        if expiring_generation.is_some() {
+            self.refresh_signature(expiring_generation).await?;
+        }
+
+        let mut headers = self.headers.get_map();
+        let signature = self.signature.read().await;
+        if let Some(signature) = signature.as_ref() {
+            headers.insert(header_name::COOKIE, signature.cookie.clone());
+        }
+        Ok((
+            headers,
+            signature.as_ref().map(|signature| signature.generation),
+        ))
+    }
+
+    /// Sends a request to the CDN, which requires a valid cloudfront signature.
+    ///
+    /// If the CDN refuses the signature, a new one is issued and the request is sent again.
+    async fn send_cdn(
+        &self,
+        endpoint: Endpoint,
+        request: impl Fn(HeaderMap) -> RequestBuilder,
+    ) -> Result<Response, Error> {
+        let (headers, generation) = self.request_headers().await?;
+
+        match send(endpoint, request(headers)).await {
+            Err(err) if err.kind == ErrorKind::Forbidden && generation.is_some() => {
+                self.refresh_signature(generation).await?;
+                let (headers, _) = self.request_headers().await?;
+                Ok(send(endpoint, request(headers)).await?)
             }
-            Err(err) => Err(Error::InvalidRequest(err.to_string())),
+            result => Ok(result?),
         }
     }
 
@@ -122,18 +196,18 @@ impl<T: UrlProvider> SekaiClient<T> {
                 self.url_provider
                     .game_version(&self.app_version, &self.app_hash),
             )
-            .headers(self.headers.get_map());
+            .headers(self.request_headers().await?.0);
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::GameVersion, request).await {
             Ok(response) => {
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::GameVersion, response).await?;
                 Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
             }
-            Err(err) => match err.status() {
-                Some(StatusCode::FORBIDDEN) => Err(Error::InvalidRequest(
-                    error_string::INVALID_HASH_VERSION.into(),
-                )),
-                _ => Err(Error::InvalidRequest(err.to_string())),
+            Err(err) => match err.kind {
+                ErrorKind::Forbidden => {
+                    Err(err.with_message(error_string::INVALID_HASH_VERSION).into())
+                }
+                _ => Err(err.into()),
             },
         }
     }
@@ -147,30 +221,26 @@ impl<T: UrlProvider> SekaiClient<T> {
     /// and the credential.
     pub async fn user_signup(&self) -> Result<UserSignup, Error> {
         let request_body = aes_msgpack::into_vec(
-            &UserRequest {
-                platform: self.platform,
-                device_model: header_value::DEVICE_MODEL.into(),
-                operating_system: header_value::OPERATING_SYSTEM.into(),
-            },
+            &UserRequest::new(self.platform, &self.device_profile),
             &self.aes_config,
         )?;
 
         let request = self
             .client
             .post(self.url_provider.user())
-            .headers(self.headers.get_map())
+            .headers(self.request_headers().await?.0)
             .body(request_body);
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::UserSignup, request).await {
             Ok(response) => {
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::UserSignup, response).await?;
                 Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
             }
-            Err(err) => match err.status() {
-                Some(StatusCode::UPGRADE_REQUIRED) => {
-                    Err(Error::InvalidRequest(error_string::UPGRADE_REQUIRED.into()))
+            Err(err) => match err.kind {
+                ErrorKind::UpgradeRequired => {
+                    Err(err.with_message(error_string::UPGRADE_REQUIRED).into())
                 }
-                _ => Err(Error::InvalidRequest(err.to_string())),
+                _ => Err(err.into()),
             },
         }
     }
@@ -201,13 +271,13 @@ impl<T: UrlProvider> SekaiClient<T> {
         let request = self
             .client
             .put(self.url_provider.user_auth(user_id))
-            .headers(self.headers.get_map())
+            .headers(self.request_headers().await?.0)
             .body(request_body);
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::UserAuth, request).await {
             Ok(response) => {
                 // parse body
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::UserAuth, response).await?;
                 let auth_response: UserAuthResponse =
                     aes_msgpack::from_slice(&bytes, &self.aes_config)?;
 
@@ -221,11 +291,11 @@ impl<T: UrlProvider> SekaiClient<T> {
 
                 Ok(auth_response)
             }
-            Err(err) => match err.status() {
-                Some(StatusCode::NOT_FOUND) => Err(Error::InvalidRequest(
-                    error_string::NOT_FOUND_USER_AUTH.into(),
-                )),
-                _ => Err(Error::InvalidRequest(err.to_string())),
+            Err(err) => match err.kind {
+                ErrorKind::NotFound => {
+                    Err(err.with_message(error_string::NOT_FOUND_USER_AUTH).into())
+                }
+                _ => Err(err.into()),
             },
         }
     }
@@ -244,31 +314,50 @@ impl<T: UrlProvider> SekaiClient<T> {
         asset_hash: &str,
         asstbundle_host_hash: &str,
     ) -> Result<AssetbundleInfo, Error> {
-        let request = self
-            .client
-            .get(self.url_provider.assetbundle_info(
-                asstbundle_host_hash,
-                asset_version,
-                asset_hash,
-                &self.platform,
-            ))
-            .headers(self.headers.get_map());
+        let url = self.url_provider.assetbundle_info(
+            asstbundle_host_hash,
+            asset_version,
+            asset_hash,
+            &self.platform,
+        );
 
-        match request.send().await?.error_for_status() {
+        match self
+            .send_cdn(Endpoint::AssetbundleInfo, |headers| {
+                self.client.get(&url).headers(headers)
+            })
+            .await
+        {
             Ok(response) => {
                 // parse body
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::AssetbundleInfo, response).await?;
                 Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
             }
-            Err(err) => match err.status() {
-                Some(StatusCode::FORBIDDEN) => Err(Error::InvalidRequest(
-                    error_string::FORBIDDEN_ASSETBUNDLE_INFO.into(),
-                )),
-                _ => Err(Error::InvalidRequest(err.to_string())),
-            },
+            Err(Error::Request(err)) if err.kind == ErrorKind::Forbidden => Err(err
+                .with_message(error_string::FORBIDDEN_ASSETBUNDLE_INFO)
+                .into()),
+            Err(err) => Err(err),
         }
     }
 
+    /// Returns the URL that an assetbundle can be downloaded from.
+    fn assetbundle_url(
+        &self,
+        asset_version: &str,
+        asset_hash: &str,
+        assetbundle_host_hash: &str,
+        bundle_name: &str,
+    ) -> String {
+        self.url_provider.assetbundle(
+            assetbundle_host_hash,
+            &self.url_provider.assetbundle_path(
+                asset_version,
+                asset_hash,
+                &self.platform,
+                bundle_name,
+            ),
+        )
+    }
+
     /// Performs a request to download an assetbundle.
     ///
     ///
@@ -282,26 +371,91 @@ impl<T: UrlProvider> SekaiClient<T> {
         assetbundle_host_hash: &str,
         bundle_name: &str,
     ) -> Result<Vec<u8>, Error> {
-        let request = self
-            .client
-            .get(self.url_provider.assetbundle(
+        let mut stream = self
+            .get_assetbundle_stream(
+                asset_version,
+                asset_hash,
                 assetbundle_host_hash,
-                &self.url_provider.assetbundle_path(
-                    asset_version,
-                    asset_hash,
-                    &self.platform,
-                    bundle_name,
-                ),
-            ))
-            .headers(self.headers.get_map());
+                bundle_name,
+            )
+            .await?;
 
-        match request.send().await?.error_for_status() {
-            Ok(response) => {
-                // parse body
-                Ok(response.bytes().await?.to_vec())
-            }
-            Err(err) => Err(Error::InvalidRequest(err.to_string())),
+        let mut data = Vec::with_capacity(stream.content_length().unwrap_or_default() as usize);
+        while let Some(chunk) = stream.chunk().await? {
+            data.extend_from_slice(&chunk);
         }
+        Ok(data)
+    }
+
+    /// Performs a request to download an assetbundle without buffering its body.
+    ///
+    /// This endpoint requires that the cloudfront cookies have been set.
+    ///
+    /// Returns an [`AssetbundleStream`] that yields the assetbundle data in chunks as it is received.
+    pub async fn get_assetbundle_stream(
+        &self,
+        asset_version: &str,
+        asset_hash: &str,
+        assetbundle_host_hash: &str,
+        bundle_name: &str,
+    ) -> Result<AssetbundleStream, Error> {
+        self.get_assetbundle_stream_range(
+            asset_version,
+            asset_hash,
+            assetbundle_host_hash,
+            bundle_name,
+            0,
+        )
+        .await
+    }
+
+    /// Performs a request to download an assetbundle starting at the provided byte offset.
+    ///
+    /// This endpoint requires that the cloudfront cookies have been set.
+    ///
+    /// The server may ignore the requested range and respond with the entire assetbundle.
+    /// [`AssetbundleStream::offset`] reports the offset that the returned body actually starts at.
+    pub async fn get_assetbundle_stream_range(
+        &self,
+        asset_version: &str,
+        asset_hash: &str,
+        assetbundle_host_hash: &str,
+        bundle_name: &str,
+        offset: u64,
+    ) -> Result<AssetbundleStream, Error> {
+        let range = match offset {
+            0 => None,
+            offset => Some(HeaderValue::from_str(&format!("bytes={}-", offset))?),
+        };
+        let url = self.assetbundle_url(
+            asset_version,
+            asset_hash,
+            assetbundle_host_hash,
+            bundle_name,
+        );
+
+        let response = self
+            .send_cdn(Endpoint::Assetbundle, |mut headers| {
+                if let Some(range) = &range {
+                    headers.insert(header_name::RANGE, range.clone());
+                }
+                self.client.get(&url).headers(headers)
+            })
+            .await?;
+
+        // the server decides which range it responds with, so the body starts where it says it does
+        let offset = if response.status() == StatusCode::PARTIAL_CONTENT {
+            response
+                .headers()
+                .get(CONTENT_RANGE)
+                .and_then(parse_content_range_start)
+                .ok_or(Error::InvalidRequest(
+                    error_string::INVALID_CONTENT_RANGE.into(),
+                ))?
+        } else {
+            0
+        };
+        Ok(AssetbundleStream { response, offset })
     }
 
     /// Performs a request to [`constants::url::sekai::SYSTEM`]
@@ -313,19 +467,34 @@ impl<T: UrlProvider> SekaiClient<T> {
     ///
     /// This function responds with this information
     pub async fn get_system(&self) -> Result<SystemInfo, Error> {
-        let request = self
+        let response = self
             .client
             .get(self.url_provider.system())
-            .headers(self.headers.get_map());
-
-        match request.send().await?.error_for_status() {
-            Ok(response) => {
-                // parse body
-                let bytes = response.bytes().await?;
-                Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
-            }
-            Err(err) => Err(Error::InvalidRequest(err.to_string())),
+            .headers(self.request_headers().await?.0)
+            .send()
+            .await
+            .map_err(|err| RequestError::from_reqwest(Endpoint::System, err))?;
+
+        // while under maintenance, the game servers may be unavailable but still describe the maintenance
+        let status = response.status();
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            let retry_after = retry_after(&response);
+            let message = format!("system unavailable ({})", status);
+            let bytes = read_body(Endpoint::System, response).await?;
+            return match aes_msgpack::from_slice::<SystemInfo>(&bytes, &self.aes_config) {
+                Ok(system_info) if system_info.is_maintenance() => Ok(system_info),
+                _ => Err(
+                    RequestError::from_status(Endpoint::System, status, retry_after, message)
+                        .into(),
+                ),
+            };
         }
+
+        let response = response
+            .error_for_status()
+            .map_err(|err| RequestError::from_reqwest(Endpoint::System, err))?;
+        let bytes = read_body(Endpoint::System, response).await?;
+        Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
     }
 
     /// Performs a request to download a suitemasterfile.
@@ -339,15 +508,15 @@ impl<T: UrlProvider> SekaiClient<T> {
         let request = self
             .client
             .get(self.url_provider.suitemasterfile(file_path))
-            .headers(self.headers.get_map());
+            .headers(self.request_headers().await?.0);
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::Suitemasterfile, request).await {
             Ok(response) => {
                 // parse body
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::Suitemasterfile, response).await?;
                 Ok(bytes.to_vec())
             }
-            Err(err) => Err(Error::InvalidRequest(err.to_string())),
+            Err(err) => Err(err.into()),
         }
     }
 
@@ -374,7 +543,7 @@ impl<T: UrlProvider> SekaiClient<T> {
         password: &str,
         execute: bool,
     ) -> Result<UserInherit, Error> {
-        let mut headers = self.headers.get_map();
+        let (mut headers, _) = self.request_headers().await?;
 
         // create X-Inherit-Id-Verify-Token header
         let jwt_payload = UserInheritJWT {
@@ -392,17 +561,17 @@ impl<T: UrlProvider> SekaiClient<T> {
             .post(self.url_provider.inherit(inherit_id, execute))
             .headers(headers);
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::UserInherit, request).await {
             Ok(response) => {
                 // parse body
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::UserInherit, response).await?;
                 Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
             }
-            Err(err) => match err.status() {
-                Some(StatusCode::NOT_FOUND) | Some(StatusCode::FORBIDDEN) => Err(
-                    Error::InvalidRequest(error_string::INVALID_INHERIT_CREDENTIALS.into()),
-                ),
-                _ => Err(Error::InvalidRequest(err.to_string())),
+            Err(err) => match err.kind {
+                ErrorKind::NotFound | ErrorKind::Forbidden => Err(err
+                    .with_message(error_string::INVALID_INHERIT_CREDENTIALS)
+                    .into()),
+                _ => Err(err.into()),
             },
         }
     }
@@ -415,46 +584,142 @@ impl<T: UrlProvider> SekaiClient<T> {
         let request = self
             .client
             .get(self.url_provider.user_suite(user_id))
-            .headers(self.headers.get_map());
+            .headers(self.request_headers().await?.0);
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::UserSuite, request).await {
             Ok(response) => {
                 // parse body
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::UserSuite, response).await?;
                 Ok(aes_msgpack::from_slice(&bytes, &self.aes_config)?)
             }
-            Err(err) => Err(Error::InvalidRequest(err.to_string())),
+            Err(err) => Err(err.into()),
         }
     }
 
     /// Gets the game's current app hash & app version from
     /// [https://github.com/mos9527/sekai-apphash]
-    pub async fn get_app_version(url_provider: &T) -> Result<AppInfo, Error> {
-        let request = Client::new().get(url_provider.apphash());
+    pub async fn get_app_version(client: &Client, url_provider: &T) -> Result<AppInfo, Error> {
+        let request = client.get(url_provider.apphash());
 
-        match request.send().await?.error_for_status() {
+        match send(Endpoint::AppHash, request).await {
             Ok(response) => {
                 // parse body
-                let bytes = response.bytes().await?;
+                let bytes = read_body(Endpoint::AppHash, response).await?;
                 let app_hash = serde_json::from_slice(&bytes)?;
                 Ok(app_hash)
             }
-            Err(err) => Err(Error::InvalidRequest(format!(
-                "{}: {}",
-                error_string::GET_APP_INFO,
-                err.to_string()
-            ))),
+            Err(err) => Err(err.with_context(error_string::GET_APP_INFO).into()),
         }
     }
 }
 
+/// Sends a request to an endpoint, and returns the response if its status code is successful.
+///
+/// If the server is rate limiting requests or is unavailable, the error includes
+/// the delay that the server asked for with a ``Retry-After`` header.
+async fn send(endpoint: Endpoint, request: RequestBuilder) -> Result<Response, RequestError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|err| RequestError::from_reqwest(endpoint, err))?;
+
+    let status = response.status();
+    let retry_after = retry_after(&response);
+    response
+        .error_for_status()
+        .map_err(|err| RequestError::from_status(endpoint, status, retry_after, err.to_string()))
+}
+
+/// Receives the entire body of a response from an endpoint.
+///
+/// A body that could not be received, for example because the connection was closed before it ended,
+/// is reported as a [`ErrorKind::Transport`] failure of the endpoint, like a request that could not be sent.
+async fn read_body(endpoint: Endpoint, response: Response) -> Result<Bytes, RequestError> {
+    response
+        .bytes()
+        .await
+        .map_err(|err| RequestError::from_body(endpoint, err))
+}
+
+/// Returns the delay that a rate limited or unavailable response asked for with a ``Retry-After`` header.
+fn retry_after(response: &Response) -> Option<Duration> {
+    match response.status() {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => response
+            .headers()
+            .get(RETRY_AFTER)
+            .and_then(parse_retry_after),
+        _ => None,
+    }
+}
+
+/// Parses the value of a ``Retry-After`` header, which is either a number of seconds or an HTTP date.
+fn parse_retry_after(value: &HeaderValue) -> Option<Duration> {
+    let value = value.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+/// Parses the first byte position of a ``Content-Range`` header, such as ``bytes 600-999/1000``.
+fn parse_content_range_start(value: &HeaderValue) -> Option<u64> {
+    let (start, _) = value
+        .to_str()
+        .ok()?
+        .trim()
+        .strip_prefix("bytes ")?
+        .split_once('-')?;
+    start.trim().parse().ok()
+}
+
+/// The body of an assetbundle download that is received in chunks.
+pub struct AssetbundleStream {
+    response: Response,
+    offset: u64,
+}
+
+impl AssetbundleStream {
+    /// The byte offset into the assetbundle that this body starts at.
+    ///
+    /// This is 0 unless a ranged request was made and the server responded with a partial body,
+    /// in which case it is the start of the range that the server reported.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The size of the body in bytes, if the server reported it.
+    pub fn content_length(&self) -> Option<u64> {
+        self.response.content_length()
+    }
+
+    /// Receives the next chunk of the body.
+    ///
+    /// Returns None once the entire body has been received.
+    ///
+    /// A body that could not be received is reported like [`read_body`] does.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        self.response
+            .chunk()
+            .await
+            .map_err(|err| RequestError::from_body(Endpoint::Assetbundle, err).into())
+    }
+}
+
 pub struct SekaiClientBuilder<T: UrlProvider> {
     aes_config: AesConfig,
     app_hash: Option<String>,
     app_version: Option<String>,
     jwt_key: Hmac<Sha256>,
     platform: Platform,
+    device_profile: DeviceProfile,
     url_provider: T,
+    client: Option<Client>,
+    http_config: Option<HttpConfig>,
 }
 
 impl<T: UrlProvider> OptionalBuilder for SekaiClientBuilder<T> {}
@@ -473,10 +738,38 @@ impl<T: UrlProvider> SekaiClientBuilder<T> {
             app_version: None,
             jwt_key,
             platform,
+            device_profile: DeviceProfile::for_platform(platform),
             url_provider,
+            client: None,
+            http_config: None,
         }
     }
 
+    /// Set the HTTP client that the SekaiClient sends requests with.
+    ///
+    /// This takes priority over a configuration set with [`Self::http_config`].
+    pub fn client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Set the configuration of the HTTP client that the SekaiClient sends requests with,
+    /// such as a proxy, timeouts and DNS overrides.
+    ///
+    /// By default, a client without any configuration is used.
+    pub fn http_config(mut self, http_config: HttpConfig) -> Self {
+        self.http_config = Some(http_config);
+        self
+    }
+
+    /// Set the device that the SekaiClient's requests appear to be sent from.
+    ///
+    /// By default, this is the preset profile of the platform, with a new random install id.
+    pub fn device_profile(mut self, device_profile: DeviceProfile) -> Self {
+        self.device_profile = device_profile;
+        self
+    }
+
     /// Set the SekaiClient's app hash
     pub fn app_hash(mut self, hash: String) -> Self {
         self.app_hash = Some(hash);
@@ -494,24 +787,34 @@ impl<T: UrlProvider> SekaiClientBuilder<T> {
     /// If app_hash or app_version were not set,
     /// the values will be fetched from the internet.
     pub async fn build(self) -> Result<SekaiClient<T>, Error> {
-        let (app_hash, app_version) =
+        let client = match (self.client, &self.http_config) {
+            (Some(client), _) => client,
+            (None, Some(http_config)) => http_config.build_client()?,
+            (None, None) => Client::new(),
+        };
+
+        let app_info =
             if let (Some(app_hash), Some(app_version)) = (&self.app_hash, &self.app_version) {
-                (app_hash.clone(), app_version.clone())
+                AppInfo {
+                    app_hash: app_hash.clone(),
+                    app_version: app_version.clone(),
+                }
             } else {
-                let app_info = SekaiClient::get_app_version(&self.url_provider).await?;
-                (
-                    self.app_hash.unwrap_or(app_info.app_hash),
-                    self.app_version.unwrap_or(app_info.app_version),
-                )
+                let app_info = SekaiClient::get_app_version(&client, &self.url_provider).await?;
+                AppInfo {
+                    app_hash: self.app_hash.unwrap_or(app_info.app_hash),
+                    app_version: self.app_version.unwrap_or(app_info.app_version),
+                }
             };
 
         SekaiClient::new(
-            app_hash,
-            app_version,
+            app_info,
             self.aes_config,
             self.jwt_key,
             self.platform,
+            self.device_profile,
             self.url_provider,
+            client,
         )
         .await
     }
@@ -544,12 +847,16 @@ mod tests {
 
     async fn get_client(server_url: String) -> SekaiClient<TestUrlProvider> {
         SekaiClient::new(
-            "3.9".to_string(),
-            "393939".to_string(),
+            AppInfo {
+                app_hash: "3.9".to_string(),
+                app_version: "393939".to_string(),
+            },
             get_aes_config(),
             get_jwt_key(),
             Platform::Android,
+            DeviceProfile::android(),
             TestUrlProvider::new(server_url),
+            Client::new(),
         )
         .await
         .unwrap()
@@ -617,7 +924,9 @@ mod tests {
 
         mock.assert();
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), mock_system_info)
+        let system_info = result.unwrap();
+        assert!(!system_info.is_maintenance());
+        assert_eq!(system_info, mock_system_info)
     }
 
     #[tokio::test]
@@ -627,11 +936,114 @@ mod tests {
         let client = get_client(server.url()).await;
 
         assert_eq!(
-            client.headers.0.get(header_name::COOKIE).unwrap(),
+            client.signature.read().await.as_ref().unwrap().cookie,
             SIGNATURE_COOKIE_VALUE
         )
     }
 
+    /// Replaces the signature of a client with an outdated one that the CDN refuses.
+    async fn set_outdated_signature(
+        client: &SekaiClient<TestUrlProvider>,
+        expires_at: Option<SystemTime>,
+    ) {
+        *client.signature.write().await = Some(Signature {
+            cookie: HeaderValue::from_static("outdated_cookie"),
+            expires_at,
+            generation: 0,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_refresh_signature_on_forbidden() {
+        let mut server = mockito::Server::new_async().await;
+        // issued once by the client, and once more after the cdn refuses the outdated signature
+        let signature_mock = server
+            .mock("POST", "/api/signature")
+            .with_status(200)
+            .with_header(header_name::SET_COOKIE, SIGNATURE_COOKIE_VALUE)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = get_client(server.url()).await;
+        set_outdated_signature(&client, None).await;
+
+        let bundle_data = vec![39u8; 1000];
+        let forbidden_mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .match_header(header_name::COOKIE, "outdated_cookie")
+            .with_status(403)
+            .expect_at_least(1)
+            .create_async()
+            .await;
+        let bundle_mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .match_header(header_name::COOKIE, SIGNATURE_COOKIE_VALUE)
+            .with_status(200)
+            .with_body(&bundle_data)
+            .expect(4)
+            .create_async()
+            .await;
+
+        // concurrent downloads wait for a single new signature, then are retried
+        let download = |bundle_name| {
+            client.get_assetbundle("4.0.5.10", "asset_hash", "host_hash", bundle_name)
+        };
+        let bundles = tokio::try_join!(
+            download("bundle_1"),
+            download("bundle_2"),
+            download("bundle_3"),
+            download("bundle_4")
+        )
+        .unwrap();
+        let bundles = [bundles.0, bundles.1, bundles.2, bundles.3];
+
+        signature_mock.assert();
+        bundle_mock.assert();
+        forbidden_mock.assert();
+        assert!(bundles.iter().all(|bundle| bundle == &bundle_data));
+        assert_eq!(
+            client.signature.read().await.as_ref().unwrap().generation,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_signature_on_expiry() {
+        let mut server = mockito::Server::new_async().await;
+        let signature_mock = server
+            .mock("POST", "/api/signature")
+            .with_status(200)
+            .with_header(header_name::SET_COOKIE, SIGNATURE_COOKIE_VALUE)
+            .expect(2)
+            .create_async()
+            .await;
+        let client = get_client(server.url()).await;
+        set_outdated_signature(&client, Some(SystemTime::now() + Duration::from_secs(10))).await;
+
+        // the signature is replaced before it expires, without being refused by the cdn
+        let forbidden_mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .match_header(header_name::COOKIE, "outdated_cookie")
+            .with_status(403)
+            .expect(0)
+            .create_async()
+            .await;
+        let bundle_mock = server
+            .mock("GET", "/host_hash/4.0.5.10/asset_hash/android/bundle")
+            .match_header(header_name::COOKIE, SIGNATURE_COOKIE_VALUE)
+            .with_status(200)
+            .create_async()
+            .await;
+        client
+            .get_assetbundle("4.0.5.10", "asset_hash", "host_hash", "bundle")
+            .await
+            .unwrap();
+
+        signature_mock.assert();
+        forbidden_mock.assert();
+        bundle_mock.assert();
+    }
+
     #[tokio::test]
     async fn test_get_suitemasterfile() {
         let server = get_server().await;
@@ -674,14 +1086,311 @@ mod tests {
         )
     }
 
+    #[tokio::test]
+    async fn test_get_assetbundle_stream() {
+        let mut server = get_server().await;
+        let client = get_client(server.url()).await;
+
+        let bundle_data = vec![39u8; 100_000];
+        let mock = server
+            .mock("GET", "/host_hash/4.0.5.10/asset_hash/android/bundle")
+            .with_status(200)
+            .with_body(&bundle_data)
+            .create_async()
+            .await;
+
+        let mut stream = client
+            .get_assetbundle_stream("4.0.5.10", "asset_hash", "host_hash", "bundle")
+            .await
+            .unwrap();
+
+        let mut received = Vec::new();
+        while let Some(chunk) = stream.chunk().await.unwrap() {
+            received.extend_from_slice(&chunk);
+        }
+
+        mock.assert();
+        assert_eq!(stream.offset(), 0);
+        assert_eq!(received, bundle_data);
+    }
+
+    #[tokio::test]
+    async fn test_get_assetbundle_stream_range() {
+        let mut server = get_server().await;
+        let client = get_client(server.url()).await;
+
+        let bundle_data = vec![39u8; 1000];
+        let mock = server
+            .mock("GET", "/host_hash/4.0.5.10/asset_hash/android/bundle")
+            .match_header(header_name::RANGE, "bytes=600-")
+            .with_status(206)
+            .with_header("content-range", "bytes 600-999/1000")
+            .with_body(&bundle_data[600..])
+            .create_async()
+            .await;
+
+        let mut stream = client
+            .get_assetbundle_stream_range("4.0.5.10", "asset_hash", "host_hash", "bundle", 600)
+            .await
+            .unwrap();
+
+        let mut received = Vec::new();
+        while let Some(chunk) = stream.chunk().await.unwrap() {
+            received.extend_from_slice(&chunk);
+        }
+
+        mock.assert();
+        assert_eq!(stream.offset(), 600);
+        assert_eq!(received, &bundle_data[600..]);
+    }
+
     #[tokio::test]
     async fn test_get_app_info() {
         let server = get_server().await;
 
-        let response = SekaiClient::get_app_version(&TestUrlProvider::new(server.url()))
+        let response =
+            SekaiClient::get_app_version(&Client::new(), &TestUrlProvider::new(server.url()))
+                .await
+                .unwrap();
+
+        assert_eq!(response, get_app_hash());
+    }
+
+    #[tokio::test]
+    async fn test_retryable_errors() {
+        let mut server = get_server().await;
+        let client = get_client(server.url()).await;
+
+        let mock = server
+            .mock("GET", "/api/suitemasterfile/1.0.0/rate_limited")
+            .with_status(429)
+            .with_header("Retry-After", "5")
+            .create_async()
+            .await;
+        let err = client
+            .get_suitemasterfile("suitemasterfile/1.0.0/rate_limited")
+            .await
+            .unwrap_err();
+        mock.assert();
+        assert!(err.is_retryable());
+        assert_eq!(err.kind(), Some(ErrorKind::RateLimited));
+        assert_eq!(err.endpoint(), Some(Endpoint::Suitemasterfile));
+        assert_eq!(err.status(), Some(StatusCode::TOO_MANY_REQUESTS));
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(5)));
+
+        server
+            .mock("GET", "/api/suitemasterfile/1.0.0/forbidden")
+            .with_status(403)
+            .with_header("Retry-After", "5")
+            .create_async()
+            .await;
+        let err = client
+            .get_suitemasterfile("suitemasterfile/1.0.0/forbidden")
+            .await
+            .unwrap_err();
+        assert!(!err.is_retryable());
+        assert_eq!(err.kind(), Some(ErrorKind::Forbidden));
+        assert_eq!(err.retry_after(), None);
+
+        // an unavailable game api is retried after the delay that it asked for
+        server
+            .mock("GET", "/api/suitemasterfile/1.0.0/unavailable")
+            .with_status(503)
+            .with_header("Retry-After", "10")
+            .create_async()
+            .await;
+        let err = client
+            .get_suitemasterfile("suitemasterfile/1.0.0/unavailable")
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(err.kind(), Some(ErrorKind::Server));
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(10)));
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_body() {
+        let mut server = get_server().await;
+        let client = get_client(server.url()).await;
+
+        // the connection is closed after part of the body was sent
+        let interrupt = |writer: &mut dyn std::io::Write| {
+            writer.write_all(&[39u8; 100])?;
+            Err(std::io::Error::other("connection closed"))
+        };
+        server
+            .mock("GET", "/api/suitemasterfile/1.0.0/interrupted")
+            .with_status(200)
+            .with_chunked_body(interrupt)
+            .create_async()
+            .await;
+        let err = client
+            .get_suitemasterfile("suitemasterfile/1.0.0/interrupted")
+            .await
+            .unwrap_err();
+        assert!(err.is_retryable());
+        assert_eq!(err.kind(), Some(ErrorKind::Transport));
+        assert_eq!(err.endpoint(), Some(Endpoint::Suitemasterfile));
+
+        server
+            .mock("GET", "/host_hash/1.0.0/asset_hash/android/interrupted")
+            .with_status(200)
+            .with_chunked_body(interrupt)
+            .create_async()
+            .await;
+        let mut stream = client
+            .get_assetbundle_stream("1.0.0", "asset_hash", "host_hash", "interrupted")
             .await
             .unwrap();
+        let err = loop {
+            match stream.chunk().await {
+                Ok(Some(_)) => continue,
+                Ok(None) => panic!("the interrupted body was received entirely"),
+                Err(err) => break err,
+            }
+        };
+        assert!(err.is_retryable());
+        assert_eq!(err.kind(), Some(ErrorKind::Transport));
+        assert_eq!(err.endpoint(), Some(Endpoint::Assetbundle));
+    }
 
-        assert_eq!(response, get_app_hash());
+    #[tokio::test]
+    async fn test_get_system_unavailable() {
+        let mut server = get_server().await;
+        let client = get_client(server.url()).await;
+
+        // an unavailable system endpoint that describes a maintenance reports it
+        let mock_system_info = SystemInfo {
+            server_date: 1730780277695,
+            timezone: "Asia/Tokyo".into(),
+            profile: "production".into(),
+            maintenance_status: SystemInfo::MAINTENANCE_IN.into(),
+            app_versions: Vec::new(),
+        };
+        let mock = server
+            .mock("GET", "/api/system")
+            .with_status(503)
+            .with_body(aes_msgpack::into_vec(&mock_system_info, &client.aes_config).unwrap())
+            .create_async()
+            .await;
+        let system_info = client.get_system().await.unwrap();
+        mock.assert_async().await;
+        assert!(system_info.is_maintenance());
+        mock.remove_async().await;
+
+        // any other unavailable response is a retryable server error
+        let mock = server
+            .mock("GET", "/api/system")
+            .with_status(503)
+            .with_header("Retry-After", "30")
+            .with_body("unavailable")
+            .create_async()
+            .await;
+        let err = client.get_system().await.unwrap_err();
+        mock.assert_async().await;
+        assert!(err.is_retryable());
+        assert_eq!(err.kind(), Some(ErrorKind::Server));
+        assert_eq!(err.endpoint(), Some(Endpoint::System));
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_required() {
+        let mut server = get_server().await;
+        let client = get_client(server.url()).await;
+
+        server
+            .mock("POST", "/api/user")
+            .with_status(426)
+            .create_async()
+            .await;
+        let err = client.user_signup().await.unwrap_err();
+
+        // the message is kept, but the kind can be matched on
+        assert_eq!(
+            err.to_string(),
+            format!("invalid request: {}", error_string::UPGRADE_REQUIRED)
+        );
+        assert_eq!(err.kind(), Some(ErrorKind::UpgradeRequired));
+        assert_eq!(err.endpoint(), Some(Endpoint::UserSignup));
+        assert_eq!(err.status(), Some(StatusCode::UPGRADE_REQUIRED));
+    }
+
+    #[tokio::test]
+    async fn test_device_profile() {
+        let mut server = get_server().await;
+        let device_profile = DeviceProfile {
+            device_model: "Test Phone".into(),
+            ..DeviceProfile::ios()
+        };
+        let client = SekaiClientBuilder::new(
+            get_aes_config(),
+            get_jwt_key(),
+            Platform::Ios,
+            TestUrlProvider::new(server.url()),
+        )
+        .app_hash("3.9".into())
+        .app_version("393939".into())
+        .device_profile(device_profile.clone())
+        .build()
+        .await
+        .unwrap();
+
+        // every request is sent with the headers of the device
+        let mock = server
+            .mock("GET", "/api/suitemasterfile/1.0.0/device_file")
+            .match_header(header_name::INSTALL_ID, device_profile.install_id.as_str())
+            .match_header(header_name::DEVICE_MODEL, "Test Phone")
+            .match_header(header_name::USER_AGENT, device_profile.user_agent.as_str())
+            .with_status(200)
+            .create_async()
+            .await;
+        client
+            .get_suitemasterfile("suitemasterfile/1.0.0/device_file")
+            .await
+            .unwrap();
+        mock.assert();
+
+        assert_eq!(
+            UserRequest::new(client.platform, &client.device_profile),
+            UserRequest {
+                platform: Platform::Ios,
+                device_model: "Test Phone".into(),
+                operating_system: device_profile.operating_system,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("120")),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after(&HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after(&HeaderValue::from_static("soon")), None);
+    }
+
+    #[test]
+    fn test_parse_content_range_start() {
+        assert_eq!(
+            parse_content_range_start(&HeaderValue::from_static("bytes 600-999/1000")),
+            Some(600)
+        );
+        assert_eq!(
+            parse_content_range_start(&HeaderValue::from_static("bytes 0-999/*")),
+            Some(0)
+        );
+        assert_eq!(
+            parse_content_range_start(&HeaderValue::from_static("bytes */1000")),
+            None
+        );
+        assert_eq!(
+            parse_content_range_start(&HeaderValue::from_static("600-999/1000")),
+            None
+        );
     }
 }
@@ -0,0 +1,141 @@
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+
+use crate::Error;
+
+/// A cloudfront signature, which grants access to the CDN until it expires.
+#[derive(Debug, Clone)]
+pub(crate) struct Signature {
+    /// the value of the ``Cookie`` header that is sent with requests
+    pub cookie: HeaderValue,
+    /// the earliest expiry of the signature's cookies, if the server reported one
+    pub expires_at: Option<SystemTime>,
+    /// the number of times that a signature was issued before this one
+    pub generation: u64,
+}
+
+impl Signature {
+    /// Parses the ``Set-Cookie`` headers of an issue signature response into a Signature.
+    ///
+    /// Returns None if the response did not set any cookies.
+    pub fn from_headers(headers: &HeaderMap, generation: u64) -> Result<Option<Self>, Error> {
+        let now = SystemTime::now();
+        let mut cookies = Vec::new();
+        let mut expires_at: Option<SystemTime> = None;
+
+        for value in headers.get_all(SET_COOKIE) {
+            let value = value
+                .to_str()
+                .map_err(|err| Error::InvalidRequest(err.to_string()))?;
+
+            // a header can set several cookies, so every part that is not a known attribute is a cookie
+            for part in value
+                .split(';')
+                .map(str::trim)
+                .filter(|part| !part.is_empty())
+            {
+                let (name, part_value) = part.split_once('=').unwrap_or((part, ""));
+                let expiry = match name.trim().to_ascii_lowercase().as_str() {
+                    "max-age" => part_value
+                        .trim()
+                        .parse::<u64>()
+                        .ok()
+                        .map(|seconds| now + Duration::from_secs(seconds)),
+                    "expires" => httpdate::parse_http_date(part_value.trim()).ok(),
+                    "path" | "domain" | "secure" | "httponly" | "samesite" => None,
+                    _ => {
+                        cookies.push(part);
+                        None
+                    }
+                };
+
+                if let Some(expiry) = expiry {
+                    expires_at = Some(expires_at.map_or(expiry, |current| current.min(expiry)));
+                }
+            }
+        }
+
+        if cookies.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            cookie: HeaderValue::from_str(&cookies.join("; "))?,
+            expires_at,
+            generation,
+        }))
+    }
+
+    /// Whether the signature has expired, or will expire within the provided margin.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= SystemTime::now() + margin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static(
+                "CloudFront-Policy=policy; Path=/; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Secure",
+            ),
+        );
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static("CloudFront-Signature=signature; Max-Age=3600; HttpOnly"),
+        );
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static("CloudFront-Key-Pair-Id=key"),
+        );
+
+        // every cookie is sent, and the earliest expiry is used
+        let signature = Signature::from_headers(&headers, 3).unwrap().unwrap();
+        assert_eq!(
+            signature.cookie,
+            "CloudFront-Policy=policy; CloudFront-Signature=signature; CloudFront-Key-Pair-Id=key"
+        );
+        assert_eq!(
+            signature.expires_at,
+            Some(httpdate::parse_http_date("Wed, 21 Oct 2015 07:28:00 GMT").unwrap())
+        );
+        assert_eq!(signature.generation, 3);
+        assert!(signature.expires_within(Duration::ZERO));
+
+        // several cookies that are set by a single header are all kept
+        let mut headers = HeaderMap::new();
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static(
+                "CloudFront-Policy=policy; CloudFront-Signature=signature; Path=/; Domain=.example.com; Max-Age=3600; CloudFront-Key-Pair-Id=key; Secure; HttpOnly; SameSite=None",
+            ),
+        );
+        let signature = Signature::from_headers(&headers, 0).unwrap().unwrap();
+        assert_eq!(
+            signature.cookie,
+            "CloudFront-Policy=policy; CloudFront-Signature=signature; CloudFront-Key-Pair-Id=key"
+        );
+        assert!(signature.expires_at.is_some());
+        assert!(!signature.expires_within(Duration::from_secs(60)));
+
+        // a signature without expiry attributes never expires on its own
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("signature_cookie"));
+        let signature = Signature::from_headers(&headers, 0).unwrap().unwrap();
+        assert_eq!(signature.expires_at, None);
+        assert!(!signature.expires_within(Duration::from_secs(3600)));
+
+        assert!(
+            Signature::from_headers(&HeaderMap::new(), 0)
+                .unwrap()
+                .is_none()
+        );
+    }
+}
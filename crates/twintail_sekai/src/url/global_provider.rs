@@ -52,7 +52,7 @@ impl UrlProvider for GlobalUrlProvider {
             "https://assetbundle-info.urls-en.com{}/{}/os/{}",
             urls::assetbundle::INFO,
             asset_version,
-            platform.to_string()
+            platform
         )
     }
 
@@ -69,10 +69,7 @@ impl UrlProvider for GlobalUrlProvider {
     ) -> String {
         format!(
             "{}/{}/{}/{}",
-            asset_version,
-            asset_hash,
-            platform.to_string(),
-            bundle_name
+            asset_version, asset_hash, platform, bundle_name
         )
     }
 
@@ -54,7 +54,7 @@ impl UrlProvider for JapanUrlProvider {
             urls::assetbundle::INFO,
             asset_version,
             asset_hash,
-            platform.to_string()
+            platform
         )
     }
 
@@ -74,10 +74,7 @@ impl UrlProvider for JapanUrlProvider {
     ) -> String {
         format!(
             "{}/{}/{}/{}",
-            asset_version,
-            asset_hash,
-            platform.to_string(),
-            bundle_name
+            asset_version, asset_hash, platform, bundle_name
         )
     }
 
@@ -3,6 +3,7 @@ use twintail_common::models::enums::Platform;
 pub mod global_provider;
 pub mod japan_provider;
 pub mod server_provider;
+pub mod template_provider;
 
 mod urls;
 
@@ -1,11 +1,17 @@
 use twintail_common::models::enums::{Platform, Server};
 
-use super::{UrlProvider, global_provider::GlobalUrlProvider, japan_provider::JapanUrlProvider};
+use crate::Error;
+
+use super::{
+    UrlProvider, global_provider::GlobalUrlProvider, japan_provider::JapanUrlProvider,
+    template_provider::TemplateUrlProvider,
+};
 
 #[derive(Clone)]
 pub enum ServerUrlProvider {
     Japan(JapanUrlProvider),
     Global(GlobalUrlProvider),
+    Custom(Box<TemplateUrlProvider>),
 }
 
 impl Default for ServerUrlProvider {
@@ -20,6 +26,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.issue_signature(),
             Self::Global(provider) => provider.issue_signature(),
+            Self::Custom(provider) => provider.issue_signature(),
         }
     }
 
@@ -27,6 +34,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.game_version(version, hash),
             Self::Global(provider) => provider.game_version(version, hash),
+            Self::Custom(provider) => provider.game_version(version, hash),
         }
     }
 
@@ -34,6 +42,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.user(),
             Self::Global(provider) => provider.user(),
+            Self::Custom(provider) => provider.user(),
         }
     }
 
@@ -41,6 +50,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.system(),
             Self::Global(provider) => provider.system(),
+            Self::Custom(provider) => provider.system(),
         }
     }
 
@@ -48,6 +58,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.user_auth(user_id),
             Self::Global(provider) => provider.user_auth(user_id),
+            Self::Custom(provider) => provider.user_auth(user_id),
         }
     }
 
@@ -65,6 +76,9 @@ impl UrlProvider for ServerUrlProvider {
             Self::Global(provider) => {
                 provider.assetbundle_info(host_hash, asset_version, asset_hash, platform)
             }
+            Self::Custom(provider) => {
+                provider.assetbundle_info(host_hash, asset_version, asset_hash, platform)
+            }
         }
     }
 
@@ -72,6 +86,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.assetbundle(host_hash, assetbundle_path),
             Self::Global(provider) => provider.assetbundle(host_hash, assetbundle_path),
+            Self::Custom(provider) => provider.assetbundle(host_hash, assetbundle_path),
         }
     }
 
@@ -89,6 +104,9 @@ impl UrlProvider for ServerUrlProvider {
             Self::Global(provider) => {
                 provider.assetbundle_path(asset_version, asset_hash, platform, bundle_name)
             }
+            Self::Custom(provider) => {
+                provider.assetbundle_path(asset_version, asset_hash, platform, bundle_name)
+            }
         }
     }
 
@@ -96,6 +114,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.suitemasterfile(file_path),
             Self::Global(provider) => provider.suitemasterfile(file_path),
+            Self::Custom(provider) => provider.suitemasterfile(file_path),
         }
     }
 
@@ -103,6 +122,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.inherit(inherit_id, execute),
             Self::Global(provider) => provider.inherit(inherit_id, execute),
+            Self::Custom(provider) => provider.inherit(inherit_id, execute),
         }
     }
 
@@ -110,6 +130,7 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.user_suite(user_id),
             Self::Global(provider) => provider.user_suite(user_id),
+            Self::Custom(provider) => provider.user_suite(user_id),
         }
     }
 
@@ -117,15 +138,23 @@ impl UrlProvider for ServerUrlProvider {
         match self {
             Self::Japan(provider) => provider.apphash(),
             Self::Global(provider) => provider.apphash(),
+            Self::Custom(provider) => provider.apphash(),
         }
     }
 }
 
-impl From<Server> for ServerUrlProvider {
-    fn from(value: Server) -> Self {
+impl TryFrom<Server> for ServerUrlProvider {
+    type Error = Error;
+
+    /// Returns the url provider of a server with built-in urls.
+    ///
+    /// The custom server has no built-in urls, so its hosts must be loaded from a file instead,
+    /// see [`TemplateUrlProvider::load`].
+    fn try_from(value: Server) -> Result<Self, Self::Error> {
         match value {
-            Server::Japan => ServerUrlProvider::Japan(JapanUrlProvider::default()),
-            Server::Global => ServerUrlProvider::Global(GlobalUrlProvider::default()),
+            Server::Japan => Ok(ServerUrlProvider::Japan(JapanUrlProvider::default())),
+            Server::Global => Ok(ServerUrlProvider::Global(GlobalUrlProvider::default())),
+            Server::Custom => Err(Error::MissingUrl("custom server".to_string())),
         }
     }
 }
@@ -0,0 +1,470 @@
+use std::path::Path;
+
+use serde::Deserialize;
+use twintail_common::models::enums::Platform;
+
+use super::UrlProvider;
+use crate::{Error, url::urls};
+
+/// The hosts that a [`TemplateUrlProvider`] sends requests to.
+///
+/// Hosts may contain the same placeholders as the paths that they are combined with.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TemplateHosts {
+    game: String,
+    /// defaults to the game host
+    game_version: Option<String>,
+    /// if None, requests are sent without a cloudfront signature
+    issue: Option<String>,
+    assetbundle: String,
+    /// defaults to the assetbundle host
+    assetbundle_info: Option<String>,
+    /// defaults to the game host
+    apphash: Option<String>,
+}
+
+/// The path templates of each endpoint, which are appended to their host.
+///
+/// Any path that is not provided uses the path of the Japan server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TemplatePaths {
+    issue_signature: String,
+    game_version: String,
+    user: String,
+    system: String,
+    user_auth: String,
+    assetbundle_info: String,
+    assetbundle: String,
+    assetbundle_path: String,
+    suitemasterfile: String,
+    inherit: String,
+    user_suite: String,
+    apphash: String,
+}
+
+impl Default for TemplatePaths {
+    fn default() -> Self {
+        Self {
+            issue_signature: urls::issue::SIGNATURE.into(),
+            game_version: "/{version}/{hash}".into(),
+            user: urls::game::USER.into(),
+            system: urls::game::SYSTEM.into(),
+            user_auth: format!(
+                "{}/{{user_id}}/auth?refreshUpdatedResources=False",
+                urls::game::USER_AUTH
+            ),
+            assetbundle_info: format!(
+                "{}/{{asset_version}}/{{asset_hash}}/os/{{platform}}",
+                urls::assetbundle::INFO
+            ),
+            assetbundle: "/{assetbundle_path}".into(),
+            assetbundle_path: "{asset_version}/{asset_hash}/{platform}/{bundle_name}".into(),
+            suitemasterfile: format!("{}/{{file_path}}", urls::game::API),
+            inherit: format!(
+                "{}/{{inherit_id}}?isExecuteInherit={{execute}}",
+                urls::game::INHERIT
+            ),
+            user_suite: format!("{}/{{user_id}}", urls::game::USER_SUITE),
+            apphash: "/apphash".into(),
+        }
+    }
+}
+
+/// A [`UrlProvider`] that builds urls from hosts and path templates loaded from a TOML or JSON file,
+/// for servers other than the official ones, such as private or staging servers.
+///
+/// Templates may contain placeholders in braces, such as ``{host_hash}``, ``{asset_version}``, ``{asset_hash}``,
+/// ``{platform}`` and ``{user_id}``, which are replaced with the values of each request.
+///
+/// The default TemplateUrlProvider has no hosts, and must be replaced with one that was loaded before it is used.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateUrlProvider {
+    hosts: TemplateHosts,
+    #[serde(default)]
+    paths: TemplatePaths,
+}
+
+impl TemplateUrlProvider {
+    /// Parses a TemplateUrlProvider from a TOML string.
+    pub fn from_toml(toml: &str) -> Result<Self, Error> {
+        let provider: Self = toml::from_str(toml)?;
+        provider.validate()?;
+        Ok(provider)
+    }
+
+    /// Parses a TemplateUrlProvider from a JSON string.
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let provider: Self = serde_json::from_str(json)?;
+        provider.validate()?;
+        Ok(provider)
+    }
+
+    /// Loads a TemplateUrlProvider from a file.
+    ///
+    /// Files with a ``.json`` extension are parsed as JSON, and all other files are parsed as TOML.
+    pub async fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let contents = tokio::fs::read_to_string(path).await?;
+
+        if path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+        {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+    }
+
+    fn game_version_host(&self) -> &str {
+        self.hosts.game_version.as_ref().unwrap_or(&self.hosts.game)
+    }
+
+    fn assetbundle_info_host(&self) -> &str {
+        self.hosts
+            .assetbundle_info
+            .as_ref()
+            .unwrap_or(&self.hosts.assetbundle)
+    }
+
+    fn apphash_host(&self) -> &str {
+        self.hosts.apphash.as_ref().unwrap_or(&self.hosts.game)
+    }
+
+    /// Returns every template that this provider builds urls from,
+    /// along with its name and the placeholders that it may contain.
+    fn templates(&self) -> Vec<(&'static str, String, &'static [&'static str])> {
+        let mut templates = vec![
+            (
+                "game_version",
+                format!("{}{}", self.game_version_host(), self.paths.game_version),
+                &["version", "hash"][..],
+            ),
+            (
+                "user",
+                format!("{}{}", self.hosts.game, self.paths.user),
+                &[],
+            ),
+            (
+                "system",
+                format!("{}{}", self.hosts.game, self.paths.system),
+                &[],
+            ),
+            (
+                "user_auth",
+                format!("{}{}", self.hosts.game, self.paths.user_auth),
+                &["user_id"],
+            ),
+            (
+                "assetbundle_info",
+                format!(
+                    "{}{}",
+                    self.assetbundle_info_host(),
+                    self.paths.assetbundle_info
+                ),
+                &["host_hash", "asset_version", "asset_hash", "platform"],
+            ),
+            (
+                "assetbundle",
+                format!("{}{}", self.hosts.assetbundle, self.paths.assetbundle),
+                &["host_hash", "assetbundle_path"],
+            ),
+            (
+                "assetbundle_path",
+                self.paths.assetbundle_path.clone(),
+                &["asset_version", "asset_hash", "platform", "bundle_name"],
+            ),
+            (
+                "suitemasterfile",
+                format!("{}{}", self.hosts.game, self.paths.suitemasterfile),
+                &["file_path"],
+            ),
+            (
+                "inherit",
+                format!("{}{}", self.hosts.game, self.paths.inherit),
+                &["inherit_id", "execute"],
+            ),
+            (
+                "user_suite",
+                format!("{}{}", self.hosts.game, self.paths.user_suite),
+                &["user_id"],
+            ),
+            (
+                "apphash",
+                format!("{}{}", self.apphash_host(), self.paths.apphash),
+                &[],
+            ),
+        ];
+
+        if let Some(issue) = &self.hosts.issue {
+            templates.push((
+                "issue_signature",
+                format!("{}{}", issue, self.paths.issue_signature),
+                &[],
+            ));
+        }
+
+        templates
+    }
+
+    /// Checks that every template only contains placeholders that can be replaced.
+    fn validate(&self) -> Result<(), Error> {
+        for (name, template, placeholders) in self.templates() {
+            let mut rest = template.as_str();
+            while let Some(start) = rest.find('{') {
+                let Some(end) = rest[start..].find('}') else {
+                    return Err(Error::InvalidTemplate(format!(
+                        "unclosed placeholder in {}: {}",
+                        name, template
+                    )));
+                };
+
+                let placeholder = &rest[start + 1..start + end];
+                if !placeholders.contains(&placeholder) {
+                    return Err(Error::InvalidTemplate(format!(
+                        "unknown placeholder {{{}}} in {}: {}",
+                        placeholder, name, template
+                    )));
+                }
+                rest = &rest[start + end + 1..];
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the placeholders in a host and path template with their values.
+fn render(host: &str, path: &str, values: &[(&str, &str)]) -> String {
+    values
+        .iter()
+        .fold(format!("{}{}", host, path), |url, (placeholder, value)| {
+            url.replace(&format!("{{{}}}", placeholder), value)
+        })
+}
+
+impl UrlProvider for TemplateUrlProvider {
+    fn issue_signature(&self) -> Option<String> {
+        self.hosts
+            .issue
+            .as_ref()
+            .map(|host| render(host, &self.paths.issue_signature, &[]))
+    }
+
+    fn game_version(&self, version: &str, hash: &str) -> String {
+        render(
+            self.game_version_host(),
+            &self.paths.game_version,
+            &[("version", version), ("hash", hash)],
+        )
+    }
+
+    fn user(&self) -> String {
+        render(&self.hosts.game, &self.paths.user, &[])
+    }
+
+    fn system(&self) -> String {
+        render(&self.hosts.game, &self.paths.system, &[])
+    }
+
+    fn user_auth(&self, user_id: usize) -> String {
+        render(
+            &self.hosts.game,
+            &self.paths.user_auth,
+            &[("user_id", &user_id.to_string())],
+        )
+    }
+
+    fn assetbundle_info(
+        &self,
+        host_hash: &str,
+        asset_version: &str,
+        asset_hash: &str,
+        platform: &Platform,
+    ) -> String {
+        render(
+            self.assetbundle_info_host(),
+            &self.paths.assetbundle_info,
+            &[
+                ("host_hash", host_hash),
+                ("asset_version", asset_version),
+                ("asset_hash", asset_hash),
+                ("platform", &platform.to_string()),
+            ],
+        )
+    }
+
+    fn assetbundle(&self, host_hash: &str, assetbundle_path: &str) -> String {
+        render(
+            &self.hosts.assetbundle,
+            &self.paths.assetbundle,
+            &[
+                ("host_hash", host_hash),
+                ("assetbundle_path", assetbundle_path),
+            ],
+        )
+    }
+
+    fn assetbundle_path(
+        &self,
+        asset_version: &str,
+        asset_hash: &str,
+        platform: &Platform,
+        bundle_name: &str,
+    ) -> String {
+        render(
+            "",
+            &self.paths.assetbundle_path,
+            &[
+                ("asset_version", asset_version),
+                ("asset_hash", asset_hash),
+                ("platform", &platform.to_string()),
+                ("bundle_name", bundle_name),
+            ],
+        )
+    }
+
+    fn suitemasterfile(&self, file_path: &str) -> String {
+        render(
+            &self.hosts.game,
+            &self.paths.suitemasterfile,
+            &[("file_path", file_path)],
+        )
+    }
+
+    fn inherit(&self, inherit_id: &str, execute: bool) -> String {
+        render(
+            &self.hosts.game,
+            &self.paths.inherit,
+            &[
+                ("inherit_id", inherit_id),
+                ("execute", if execute { "True" } else { "False" }),
+            ],
+        )
+    }
+
+    fn user_suite(&self, user_id: usize) -> String {
+        render(
+            &self.hosts.game,
+            &self.paths.user_suite,
+            &[("user_id", &user_id.to_string())],
+        )
+    }
+
+    fn apphash(&self) -> String {
+        render(self.apphash_host(), &self.paths.apphash, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const TOML: &str = r#"
+        [hosts]
+        game = "http://localhost:8000"
+        assetbundle = "http://localhost:8001/{host_hash}"
+
+        [paths]
+        user_suite = "/suite/{user_id}"
+    "#;
+
+    #[test]
+    fn test_template_provider_toml() -> Result<(), Error> {
+        let provider = TemplateUrlProvider::from_toml(TOML)?;
+
+        // hosts that are not provided fall back to other hosts
+        assert_eq!(provider.issue_signature(), None);
+        assert_eq!(
+            provider.game_version("5.0.0", "hash"),
+            "http://localhost:8000/5.0.0/hash"
+        );
+        assert_eq!(
+            provider.assetbundle_info("abc", "5.0.0.10", "ahash", &Platform::Ios),
+            "http://localhost:8001/abc/api/version/5.0.0.10/ahash/os/ios"
+        );
+
+        // paths that are not provided use the paths of the japan server
+        assert_eq!(
+            provider.user_auth(12),
+            "http://localhost:8000/api/user/12/auth?refreshUpdatedResources=False"
+        );
+        assert_eq!(
+            provider.assetbundle(
+                "abc",
+                &provider.assetbundle_path("5.0.0.10", "ahash", &Platform::Android, "bundle")
+            ),
+            "http://localhost:8001/abc/5.0.0.10/ahash/android/bundle"
+        );
+
+        // provided paths replace the defaults
+        assert_eq!(provider.user_suite(12), "http://localhost:8000/suite/12");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_template_provider_load() -> Result<(), Error> {
+        let dir = tempdir()?;
+        let json_path = dir.path().join("endpoints.json");
+        tokio::fs::write(
+            &json_path,
+            r#"{
+                "hosts": {
+                    "game": "http://localhost:8000",
+                    "issue": "http://localhost:8002",
+                    "assetbundle": "http://localhost:8001"
+                },
+                "paths": { "inherit": "/inherit/{inherit_id}/{execute}" }
+            }"#,
+        )
+        .await?;
+
+        let provider = TemplateUrlProvider::load(&json_path).await?;
+        assert_eq!(
+            provider.issue_signature(),
+            Some("http://localhost:8002/api/signature".into())
+        );
+        assert_eq!(
+            provider.inherit("id", true),
+            "http://localhost:8000/inherit/id/True"
+        );
+
+        let toml_path = dir.path().join("endpoints.toml");
+        tokio::fs::write(&toml_path, TOML).await?;
+        assert!(TemplateUrlProvider::load(&toml_path).await.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_template_provider_invalid() {
+        // placeholders that a template does not support are rejected
+        let result = TemplateUrlProvider::from_toml(
+            r#"
+            [hosts]
+            game = "http://localhost:8000"
+            assetbundle = "http://localhost:8001"
+
+            [paths]
+            system = "/system/{user_id}"
+            "#,
+        );
+        assert!(matches!(result, Err(Error::InvalidTemplate(_))));
+
+        // unknown fields are rejected
+        let result = TemplateUrlProvider::from_toml(
+            r#"
+            [hosts]
+            game = "http://localhost:8000"
+            assetbundle = "http://localhost:8001"
+            asset_bundle = "http://localhost:8001"
+            "#,
+        );
+        assert!(matches!(result, Err(Error::Toml(_))));
+    }
+}
@@ -47,18 +47,18 @@ impl UrlProvider for TestUrlProvider {
         platform: &Platform,
     ) -> String {
         format!(
-            "{}-{}{}/{}/{}/os/{}",
+            "{}/{}{}/{}/{}/os/{}",
             self.host,
             host_hash,
             urls::assetbundle::INFO,
             asset_version,
             asset_hash,
-            platform.to_string()
+            platform
         )
     }
 
     fn assetbundle(&self, host_hash: &str, assetbundle_path: &str) -> String {
-        format!("{}-{}/{}", self.host, host_hash, assetbundle_path)
+        format!("{}/{}/{}", self.host, host_hash, assetbundle_path)
     }
 
     fn assetbundle_path(
@@ -70,10 +70,7 @@ impl UrlProvider for TestUrlProvider {
     ) -> String {
         format!(
             "{}/{}/{}/{}",
-            asset_version,
-            asset_hash,
-            platform.to_string(),
-            bundle_name
+            asset_version, asset_hash, platform, bundle_name
         )
     }
 